@@ -10,6 +10,7 @@ defmodule Duckex do
   primarily for testing DuckLake functionality.
   """
 
+  alias Duckex.Command
   alias Duckex.Error
   alias Duckex.Protocol
   alias Duckex.Query
@@ -23,6 +24,12 @@ defmodule Duckex do
           {path :: String.t(), keyword()}
           | {path :: String.t(), keyword(), keyword()}
 
+  @type database_info() :: %{
+          name: String.t(),
+          path: String.t() | nil,
+          read_only: boolean()
+        }
+
   @type secret() ::
           {atom(), keyword()}
           | {atom(), {keyword(), keyword()}}
@@ -43,6 +50,19 @@ defmodule Duckex do
   Duckex provides some helper options to setup connection before it is
   available.
 
+  - `:database` - path of the database file, `":memory:"` (default) for
+    in-memory database. Named in-memory databases, like `":memory:analytics"`,
+    are shared by all connections opened with the same name in the VM, e.g. by
+    a pool, and live as long as any of them is open. They are configured by the
+    first connection. The database can be also given as URI with DuckDB
+    settings in the query string, e.g.
+    `"duckdb:///path/to/db.duckdb?threads=4&memory_limit=2GB"`, so the whole
+    connection can be configured by single environment variable.
+    MotherDuck databases are opened with `"md:"` or `"md:database_name"`.
+  - `:motherduck_token` - token used to connect to MotherDuck, by default read
+    from `motherduck_token` or `MOTHERDUCK_TOKEN` environment variable. Failures
+    to connect to MotherDuck are reported as `Duckex.Error` with
+    `kind: :motherduck`.
   - `:secrets` - secrets to be set up before instance is made ready. It is list
     of tuples where first element is name of secret and second is keyword lists
     containing secret details.
@@ -50,6 +70,46 @@ defmodule Duckex do
     the second one is list of options, optional 3rd element contains connection
     options, see `attach/4`.
 
+  - `:lock_timeout` - time in milliseconds to wait for the calls queued before
+    on the connection to finish before failing with `Duckex.Error` of
+    `kind: :busy`. By default waits indefinitely.
+  - `:strict_floats` - fail queries returning `NaN` or infinite floats with
+    `Duckex.Error` of `kind: :conversion`. By default these are returned as
+    `:nan`, `:infinity` and `:neg_infinity` atoms, as Erlang floats cannot
+    represent them. The same atoms are accepted as parameters.
+  - `:strict_text` - fail queries with binary parameters which are not valid
+    UTF-8 with `Duckex.Error` of `kind: :invalid_input`. By default these are
+    bound as `BLOB` values.
+  - `:max_rows` and `:max_result_bytes` - default limits of query results,
+    see `query/4`.
+  - `:temp_directory` - directory DuckDB spills to when data of large joins,
+    sorts or aggregates does not fit in memory. By default it is `.tmp`
+    directory next to the database file, or in the current working directory
+    for in-memory databases.
+  - `:max_temp_directory_size` - maximum size of the spilled data, either as
+    number of bytes or DuckDB size string like `"10GB"`. See `temp_usage/2`.
+  - `:readers` - maximum number of idle connection clones kept for `read/4`,
+    4 by default.
+  - `:allow_unsigned_extensions` - allow loading extensions that are not
+    signed, e.g. built in-house. `false` by default.
+  - `:custom_extension_repository` - URL of the repository extensions are
+    installed from instead of the default one, e.g. internal mirror.
+  - `:autoinstall_known_extensions` and `:autoload_known_extensions` - whether
+    DuckDB installs and loads extensions on their first use, both `true` by
+    default. Disable them in air-gapped deployments to avoid surprise network
+    fetches.
+  - `:extensions` - list of extensions installed (when not installed yet) and
+    loaded at connect time, so there is no lazy-load latency on their first
+    use.
+  - `:icu` - load ICU extension, without which DuckDB knows only UTC and
+    `TIMESTAMPTZ` values ignore time zones.
+  - `:timezone` - session time zone, e.g. `"Europe/Warsaw"`, see
+    `set_timezone/3`. Implies `icu: true`.
+  - `:http` - keyword list of `httpfs` settings, see `set_http_options/3`.
+  - `:after_connect` - list of SQL statements, e.g. `SET` or `LOAD` ones, run
+    before the connection is made available. Connection fails to start when any
+    of them fails.
+
   Secrets are set up before attaching connections, so you can use these secrets
   for attaching (like S3 secrets).
   """
@@ -82,8 +142,127 @@ defmodule Duckex do
     DBConnection.start_link(Protocol, opts)
   end
 
+  @doc """
+  Returns version of the bundled DuckDB library.
+
+  Result contains `:library_version`, e.g. `"v1.4.1"`, `:source_id` (git
+  commit DuckDB was built from) and `:storage_compatibility_version`, oldest
+  DuckDB version able to read database files written by this one.
+  """
+  @spec version() ::
+          {:ok,
+           %{
+             library_version: String.t(),
+             source_id: String.t(),
+             storage_compatibility_version: String.t()
+           }}
+          | {:error, Error.t()}
+  def version, do: Duckex.Native.version()
+
+  @doc """
+  Quotes identifier, e.g. table or column name, so it can be safely
+  interpolated into SQL. List of names is quoted as qualified name.
+
+      iex> Duckex.quote_identifier(~s(my "table"))
+      ~s("my ""table""")
+
+      iex> Duckex.quote_identifier(["main", "users"])
+      ~s("main"."users")
+  """
+  @spec quote_identifier(String.t() | [String.t()]) :: String.t()
+  def quote_identifier(names) when is_list(names),
+    do: Enum.map_join(names, ".", &quote_identifier/1)
+
+  def quote_identifier(name) when is_binary(name), do: Duckex.Native.quote_identifier(name)
+
+  @doc """
+  Quotes string as SQL literal, so it can be safely interpolated into SQL.
+  Prefer query parameters where possible.
+
+      iex> Duckex.escape_literal("it's")
+      "'it''s'"
+  """
+  @spec escape_literal(String.t()) :: String.t()
+  def escape_literal(value) when is_binary(value), do: Duckex.Native.escape_literal(value)
+
+  @doc """
+  Parses `TIME WITH TIME ZONE` value in text form of DuckDB into time and its
+  UTC offset in seconds, the same form as it is bound as parameter.
+
+      iex> Duckex.parse_time_tz("12:30:00+02")
+      {:ok, {~T[12:30:00], 7200}}
+
+      iex> Duckex.parse_time_tz("08:15:30.25-05:30")
+      {:ok, {~T[08:15:30.25], -19800}}
+  """
+  @spec parse_time_tz(String.t()) :: {:ok, {Time.t(), integer()}} | :error
+  def parse_time_tz(value) when is_binary(value) do
+    with [_, time, sign, offset] <-
+           Regex.run(~r/^([^+-]+)([+-])(\d{2}(?::\d{2}){0,2})$/, value),
+         {:ok, time} <- Time.from_iso8601(time) do
+      seconds =
+        offset
+        |> String.split(":")
+        |> Enum.zip([3600, 60, 1])
+        |> Enum.reduce(0, fn {part, unit}, acc -> acc + String.to_integer(part) * unit end)
+
+      {:ok, {time, if(sign == "-", do: -seconds, else: seconds)}}
+    else
+      _ -> :error
+    end
+  end
+
+  @doc ~S"""
+  Splits SQL script into separate statements.
+
+  Semicolons inside string literals, quoted identifiers, comments and
+  dollar-quoted strings do not end the statement. Returned statements are
+  trimmed and do not include the trailing semicolon.
+
+      iex> Duckex.split_statements("SELECT ';'; -- done;\nSELECT 2;")
+      ["SELECT ';'", "-- done;\nSELECT 2"]
+  """
+  @spec split_statements(String.t()) :: [String.t()]
+  def split_statements(sql) when is_binary(sql), do: Duckex.Native.split_statements(sql)
+
+  @doc """
+  Checks SQL for syntax errors without running it nor touching any connection.
+
+  Statements are parsed against an empty database, so references to unknown
+  tables or columns are not reported. Returns `:ok` or `{:error, diagnostics}`
+  where each diagnostic holds `:message`, `:statement` (index of the
+  statement in `sql`) and `:position` (byte offset of the error within that
+  statement, `nil` when unknown).
+
+  ## Options
+
+  - `:split` - whether `sql` is a script of many statements, see
+    `split_statements/1`. Defaults to `true`.
+  """
+  @spec validate(String.t(), keyword()) ::
+          :ok
+          | {:error,
+             [
+               %{
+                 message: String.t(),
+                 statement: non_neg_integer(),
+                 position: non_neg_integer() | nil
+               }
+             ]}
+          | {:error, Error.t()}
+  def validate(sql, opts \\ []) when is_binary(sql) do
+    case Duckex.Native.validate(sql, opts) do
+      {:ok, []} -> :ok
+      {:ok, diagnostics} -> {:error, diagnostics}
+      {:error, error} -> {:error, error}
+    end
+  end
+
   @doc """
   Prepares query.
+
+  Prepared statement should be released with `close/3`. Statements that are
+  not closed are released once the returned query is garbage collected.
   """
   @spec prepare(DBConnection.conn(), String.t(), list()) ::
           {:ok, Query.t()} | {:error, Error.t()}
@@ -120,6 +299,72 @@ defmodule Duckex do
     DBConnection.prepare_execute!(conn, %Query{query: statement}, params, opts)
   end
 
+  @doc """
+  Prepares and executes query, then closes the prepared statement.
+
+  `params` are bound by position to `?` or `$1` placeholders. Keyword list
+  binds them by name instead, e.g. `[id: 1]` to `$id`.
+
+  Fixed-size `ARRAY` values, e.g. embedding vectors, are bound as
+  `{:array, values}` with list of numbers (or nested lists of them). Their
+  length has to match the size of the `ARRAY` type of the parameter.
+
+  `TIME WITH TIME ZONE` values are bound as `{%Time{}, utc_offset}`, offset
+  being in seconds. DuckDB returns them through Arrow without the offset, as
+  plain times, so select them as `VARCHAR` and decode with `parse_time_tz/1`
+  when the offset matters.
+
+  ## Options
+
+  - `:query_timeout` - time in milliseconds after which the running query is
+    interrupted and `Duckex.Error` with `kind: :timeout` is returned. Unlike
+    `:timeout` it stops the query inside DuckDB as well.
+  - `:layout` - `:rows` (default) or `:columnar`. With `:columnar` layout
+    `rows` of the result hold one list per column instead of one per row,
+    which suits analytics consumers like charting or Nx without pivoting.
+  - `:chunk_size` - maximum number of rows encoded by single NIF call, 10 000
+    by default. Larger results are encoded in multiple calls, so none of them
+    blocks the scheduler for too long. Calls fetching the remaining rows also
+    end early, yielding to the scheduler, once they use up their timeslice.
+  - `:lazy` - keep the rows materialized in DuckDB values and return result
+    with empty `rows` and `handle`, from which rows are decoded on demand with
+    `fetch/2` or `stream_result/2`. Useful for paginating huge results
+    without re-running the query, or for processing them without holding
+    all rows as terms at once.
+  - `:timestamp_ns` - `:datetime` (default) or `:integer`. `TIMESTAMP_NS`
+    values do not fit `DateTime`, which holds at most microseconds, so they
+    are truncated by default. With `:integer` they are returned as
+    nanoseconds since Unix epoch instead.
+  - `:max_rows` - fail with `Duckex.Error` of `kind: :result_too_large` once
+    the result has more rows, instead of loading all of them into memory.
+  - `:max_result_bytes` - the same for approximate size of the result, which
+    counts text and blob values by their length and other values as 8 bytes.
+  - `:geometry` - format of `GEOMETRY` values of the spatial extension:
+    `:wkb` (default) for Well-Known Binary, `:wkt` for Well-Known Text strings
+    or `:geojson` for GeoJSON strings. They are recognized once the extension
+    is loaded with `load_extension/3` or `:extensions` option, otherwise they
+    are returned as blobs in the internal format of the extension.
+  - `:blob` - encoding of `BLOB` values: `:base64` (default), `:raw` for the
+    bytes as they are or `:hex` for lowercase hexadecimal strings. Does not
+    apply to `:json` and `:csv` formats, which always use Base64.
+  - `:format` - `:terms` (default), `:json` or `:csv`. With `:json` `rows` of
+    the result is a single binary with JSON array of objects, one per row,
+    serialized directly from DuckDB values. Useful for passing results on to
+    HTTP clients without converting them to terms first. Dates and timestamps
+    are ISO 8601 strings, blobs are Base64 encoded, `DECIMAL` values and
+    integers outside of 64-bit range are strings to keep their precision. The
+    whole result is serialized at once, `:chunk_size` and `:lazy` do not apply.
+    With `:csv` it is RFC 4180 CSV binary with header row and CRLF line
+    endings. Values are written as with `:json`, nested ones as JSON text,
+    `NULL` and non-finite floats as empty fields.
+  - `:delimiter` - field delimiter of `:csv` format, `","` by default
+  - `:quote` - quote character of `:csv` format, double quote by default
+  - `:column_info` - fill `column_info` of the result with nullability and
+    origin of the columns, as returned by `describe/3`. Off by default, as it
+    takes extra catalog lookups after the query.
+
+  Rest of the options are passed to `DBConnection`.
+  """
   @spec query(DBConnection.conn(), String.t(), list(), list()) ::
           {:ok, Result.t()} | {:error, Error.t()}
   def query(conn, statement, params \\ [], opts \\ []) do
@@ -137,108 +382,1691 @@ defmodule Duckex do
     end
   end
 
-  defdelegate close(pid, query, opts \\ []), to: DBConnection
-  defdelegate close!(pid, query, opts \\ []), to: DBConnection
+  @doc """
+  Runs query and returns its result as a list of Arrow record batches.
 
-  defdelegate execute(pid, query, values, opts \\ []), to: DBConnection
-  defdelegate execute!(pid, query, values, opts \\ []), to: DBConnection
+  Each batch is serialized as a self-contained Arrow IPC stream binary, so it
+  can be loaded independently, for example with
+  `Explorer.DataFrame.load_ipc_stream!/1`. This skips the per-value conversion
+  done by `query/4`, which makes it much faster for wide numeric results.
+  """
+  @spec query_arrow(DBConnection.conn(), String.t(), list(), list()) ::
+          {:ok, [binary()]} | {:error, Error.t()}
+  def query_arrow(conn, statement, params \\ [], opts \\ []) do
+    command(conn, :query_arrow, [statement, params], opts)
+  end
 
-  defdelegate transaction(conn, fun, opts \\ []), to: DBConnection
+  @doc """
+  Runs query and returns Arrow record batches or raises `Duckex.Error` if there
+  is an error. See `query_arrow/4`.
+  """
+  @spec query_arrow!(DBConnection.conn(), String.t(), list(), list()) :: [binary()]
+  def query_arrow!(conn, statement, params \\ [], opts \\ []) do
+    case query_arrow(conn, statement, params, opts) do
+      {:ok, batches} -> batches
+      {:error, error} -> raise error
+    end
+  end
 
-  @spec rollback(DBConnection.t(), reason :: any()) :: no_return()
-  defdelegate rollback(conn, reason), to: DBConnection
+  @doc """
+  Opens appender for bulk loading rows into `table`.
 
-  defp escape(val), do: String.replace(to_string(val), "'", "''")
+  Rows are fed with `append_chunk/2` and buffered until there is at least
+  `:flush_threshold` of them, then written to the table at once. Use
+  `close_appender/1` to write the remaining rows, rows that were not flushed
+  are discarded when the appender is garbage collected.
+
+  ## Options
+
+  - `:schema` - schema of the table
+  - `:flush_threshold` - number of buffered rows that triggers write to the
+    table, 100 000 by default
+
+  Rest of the options are passed to `DBConnection`.
+  """
+  @spec appender(DBConnection.conn(), String.t(), keyword()) ::
+          {:ok, reference()} | {:error, Error.t()}
+  def appender(conn, table, opts \\ []) do
+    {appender_opts, opts} = Keyword.split(opts, [:schema, :flush_threshold])
+
+    command(conn, :appender_open, [to_string(table), appender_opts], opts)
+  end
 
   @doc """
-  Install extensions for DuckDB.
+  Appends list of rows, each being list of column values, to the appender.
 
-  This will spawn temporary connection and will install extensions within that
-  connection. After that each new DuckDB instance is capable of using that
-  extensions.
+  Returns number of rows written to the table so far.
+  """
+  @spec append_chunk(reference(), [list()]) :: {:ok, non_neg_integer()} | {:error, Error.t()}
+  def append_chunk(appender, rows) when is_reference(appender) and is_list(rows),
+    do: Duckex.Native.appender_append_chunk(appender, rows)
 
-  It is a helper function to provide a cleaner syntax - extensions use similar
-  syntax to `Mix.install/2` and are either:
-  - atom containing name of the extension to be installed
-  - tuple in form of `{atom(), opts}`
+  @doc """
+  Writes remaining rows and closes the appender.
+
+  Returns total number of rows written to the table.
+  """
+  @spec close_appender(reference()) :: {:ok, non_neg_integer()} | {:error, Error.t()}
+  def close_appender(appender) when is_reference(appender),
+    do: Duckex.Native.appender_close(appender)
+
+  @doc """
+  Opens ingest for `COPY`-style loading of `format` (`:csv` or newline-delimited
+  `:json`) data into existing `table`, e.g. as it arrives over a socket.
+
+  Data is fed with `ingest_write/2` in any number of chunks, which do not have
+  to be aligned with rows, and loaded with `ingest_finish/1`. Complete rows are
+  parsed and appended to the table as the chunks arrive, nothing is written to
+  disk. Values are appended as text and cast by DuckDB to the column types,
+  JSON objects are matched to the columns by key.
+
+  Parquet cannot be ingested, as it can be read only once complete.
 
   ## Options
 
-  - `:source` - source from which the extension should be installed. Supported
-    options are:
-    + `:default` (used when option is not specified) - uses default
-      registry configured for connection
-    + `:core`
-    + `:nightly` - equivalent of `core_nightly` from DuckDB
-    + `:community` - equivalent of `community` from DuckDB
-    + String representing URL from which the extension should be installed
-  - `:force` - forcefully install extension, even when it is already installed.
-    Can be used to update extension.
+  - `:header`, `:delimiter` - as in `copy_to/5`, CSV only
+  - `:compression` - compression of the data, `:gzip`, `:zstd` or `:none`,
+    detected from the content by default
+
+  Rest of the options are passed to `DBConnection`.
+
+  ## Example
+
+      {:ok, ingest} = Duckex.ingest(conn, :events, :csv, header: true)
+      :ok = Duckex.ingest_write(ingest, "id,name\\n1,a")
+      :ok = Duckex.ingest_write(ingest, "bc\\n2,def\\n")
+      {:ok, 2} = Duckex.ingest_finish(ingest)
   """
-  @spec install_extensions([extension], [connection_option()]) :: :ok | {:error, term()}
-        when extension_opt:
-               {:source, :default | :core | :nightly | String.t()} | {:force, boolean()},
-             extension: atom() | {atom(), [extension_opt]}
-  def install_extensions(extensions, opts \\ []) do
-    with {:ok, conn} <- start_link(opts) do
-      Enum.each(extensions, &do_install(conn, &1))
+  @spec ingest(DBConnection.conn(), atom() | String.t(), :csv | :json, keyword()) ::
+          {:ok, reference()} | {:error, Error.t()}
+  def ingest(conn, table, format, opts \\ []) do
+    {ingest_opts, opts} = Keyword.split(opts, [:header, :delimiter, :compression])
 
-      Process.exit(conn, :normal)
+    command(conn, :ingest_open, [to_string(table), format, ingest_opts], opts)
+  end
 
-      :ok
+  @doc """
+  Writes chunk of data to the ingest opened with `ingest/4`.
+  """
+  @spec ingest_write(reference(), iodata()) :: :ok | {:error, Error.t()}
+  def ingest_write(ingest, data) when is_reference(ingest) do
+    with {:ok, _} <- Duckex.Native.ingest_write(ingest, IO.iodata_to_binary(data)), do: :ok
+  end
+
+  @doc """
+  Loads rest of the data written to the ingest into the table. Ingest cannot be
+  used afterwards.
+
+  Returns number of loaded rows.
+  """
+  @spec ingest_finish(reference()) :: {:ok, non_neg_integer()} | {:error, Error.t()}
+  def ingest_finish(ingest) when is_reference(ingest),
+    do: Duckex.Native.ingest_finish(ingest)
+
+  @doc """
+  Runs `statement` returning single `BLOB` (or text) value and keeps it in the
+  native memory, to be read in chunks with `read_blob/3` or `stream_blob/2`.
+  Large values never have to be allocated as one binary in the VM.
+
+  Returns map with blob `:handle` and its `:size` in bytes. The value is freed
+  once the handle is garbage collected.
+
+  Options are passed to `DBConnection`.
+  """
+  @spec open_blob(DBConnection.conn(), String.t(), list(), keyword()) ::
+          {:ok, %{handle: reference(), size: non_neg_integer()}} | {:error, Error.t()}
+  def open_blob(conn, statement, params \\ [], opts \\ []),
+    do: command(conn, :blob_open, [statement, params], opts)
+
+  @doc """
+  Reads up to `length` bytes of blob opened with `open_blob/4` starting at
+  `offset`. Returns empty binary past the end of the blob.
+  """
+  @spec read_blob(reference(), non_neg_integer(), non_neg_integer()) ::
+          {:ok, binary()} | {:error, Error.t()}
+  def read_blob(handle, offset, length)
+      when is_reference(handle) and is_integer(offset) and offset >= 0 and
+             is_integer(length) and length >= 0,
+      do: Duckex.Native.blob_read(handle, offset, length)
+
+  @doc """
+  Returns stream of `chunk_size` (1 MiB by default) binaries of blob opened
+  with `open_blob/4`, e.g. to send it as chunked HTTP response.
+  """
+  @spec stream_blob(%{handle: reference(), size: non_neg_integer()}, pos_integer()) ::
+          Enumerable.t()
+  def stream_blob(%{handle: handle, size: size}, chunk_size \\ 1_048_576)
+      when is_integer(chunk_size) and chunk_size > 0 do
+    0
+    |> Stream.iterate(&(&1 + chunk_size))
+    |> Stream.take_while(&(&1 < size))
+    |> Stream.map(fn offset ->
+      case read_blob(handle, offset, chunk_size) do
+        {:ok, chunk} -> chunk
+        {:error, error} -> raise error
+      end
+    end)
+  end
+
+  @doc """
+  Appends all rows from `enumerable` to `table`, without materializing it.
+
+  Accepts the same options as `appender/3` and `:chunk_size` (1000 by default)
+  - number of rows passed to the appender at once. Returns number of appended
+  rows.
+  """
+  @spec append_stream(DBConnection.conn(), String.t(), Enumerable.t(), keyword()) ::
+          {:ok, non_neg_integer()} | {:error, Error.t()}
+  def append_stream(conn, table, enumerable, opts \\ []) do
+    {chunk_size, opts} = Keyword.pop(opts, :chunk_size, 1000)
+
+    with {:ok, appender} <- appender(conn, table, opts) do
+      enumerable
+      |> Stream.chunk_every(chunk_size)
+      |> Enum.reduce_while(:ok, fn rows, :ok ->
+        case append_chunk(appender, rows) do
+          {:ok, _} -> {:cont, :ok}
+          {:error, _} = error -> {:halt, error}
+        end
+      end)
+      |> case do
+        :ok -> close_appender(appender)
+        {:error, _} = error -> error
+      end
     end
   end
 
-  defp do_install(conn, name) when is_atom(name), do: do_install(conn, {name, []})
+  @doc """
+  Creates table `name` and loads `rows` into it in one go, so in-memory data can
+  be joined against other tables or files.
 
-  defp do_install(conn, {name, opts}) do
-    from =
-      case opts[:source] do
-        :core -> " FROM core"
-        :nightly -> " FROM core_nightly"
-        :community -> " FROM community"
-        nil -> ""
-        :default -> ""
-        repo when is_binary(repo) -> " FROM '#{escape(repo)}'"
+  The table is created in the database, not as a temporary one, as those are
+  visible only on the pooled connection which created them. Drop it once it is
+  not needed, otherwise it stays in the database file.
+
+  `columns` is a keyword list (or list of tuples) of column names and their
+  DuckDB types, e.g. `[id: :integer, name: :varchar]`. Rows are maps keyed by
+  column names (atoms or strings, missing keys are `NULL`), or tuples or lists
+  with values in column order. Existing table of the same name is replaced.
+
+  Returns number of loaded rows.
+
+  ## Example
+
+      Duckex.register_rows(conn, "users", [id: :integer, name: :varchar], [
+        %{id: 1, name: "Alice"},
+        {2, "Bob"}
+      ])
+      {:ok, 2}
+  """
+  @spec register_rows(
+          DBConnection.conn(),
+          atom() | String.t(),
+          [{atom() | String.t(), atom() | String.t()}],
+          [map() | tuple() | list()],
+          keyword()
+        ) :: {:ok, non_neg_integer()} | {:error, Error.t()}
+  def register_rows(conn, name, columns, rows, opts \\ []) do
+    columns = Enum.map(columns, fn {column, type} -> {to_string(column), to_string(type)} end)
+
+    command(conn, :register_rows, [to_string(name), columns, rows], opts)
+  end
+
+  @doc """
+  Loads rows pulled in batches from Elixir data source into table `name`, so SQL
+  can query data living in processes or ETS. The table is created the same way
+  as with `register_rows/5`.
+
+  `columns` and rows are the same as in `register_rows/5`. `source` is either an
+  enumerable (e.g. `:ets` table stream or `GenStage.stream/1`) or a pid of
+  producer process, which is asked for the next batch with
+  `GenServer.call(pid, {:duckex_fetch, batch_size})` and replies with
+  `{:rows, rows}` or `:done`.
+
+  The table is a snapshot, not a view of the source: the source is read to the
+  end once, when the table is registered, through the appender, and queries
+  scan the copied rows. Register it again to see newer data. Returns number of
+  loaded rows.
+
+  ## Options
+
+  - `:batch_size` - number of rows fetched from the source at once, 1000 by
+    default
+  - `:timeout` - time in milliseconds to wait for each batch from producer
+    process, 5000 by default. It is passed to `DBConnection` as well.
+
+  Rest of the options are passed to `appender/3`.
+  """
+  @spec register_table(
+          DBConnection.conn(),
+          atom() | String.t(),
+          [{atom() | String.t(), atom() | String.t()}],
+          Enumerable.t() | pid(),
+          keyword()
+        ) :: {:ok, non_neg_integer()} | {:error, Error.t()}
+  def register_table(conn, name, columns, source, opts \\ []) do
+    {batch_size, opts} = Keyword.pop(opts, :batch_size, 1000)
+    timeout = Keyword.get(opts, :timeout, 5000)
+    name = to_string(name)
+
+    with {:ok, 0} <- register_rows(conn, name, columns, [], opts) do
+      rows =
+        source
+        |> source_stream(batch_size, timeout)
+        |> Stream.map(&row_values(&1, columns))
+
+      append_stream(conn, name, rows, Keyword.put(opts, :chunk_size, batch_size))
+    end
+  end
+
+  defp source_stream(pid, batch_size, timeout) when is_pid(pid) do
+    Stream.resource(
+      fn -> pid end,
+      fn pid ->
+        case GenServer.call(pid, {:duckex_fetch, batch_size}, timeout) do
+          {:rows, rows} -> {rows, pid}
+          :done -> {:halt, pid}
+        end
+      end,
+      fn _ -> :ok end
+    )
+  end
+
+  defp source_stream(enumerable, _batch_size, _timeout), do: enumerable
+
+  # Appender takes rows as lists of values in column order
+  defp row_values(row, _columns) when is_list(row), do: row
+  defp row_values(row, _columns) when is_tuple(row), do: Tuple.to_list(row)
+
+  defp row_values(row, columns) when is_map(row) do
+    Enum.map(columns, fn {column, _type} -> column_value(row, column) end)
+  end
+
+  # Maps are keyed by atoms or strings, regardless of how columns are named
+  defp column_value(row, column) when is_atom(column),
+    do: Map.get_lazy(row, column, fn -> Map.get(row, Atom.to_string(column)) end)
+
+  defp column_value(row, column) when is_binary(column) do
+    Map.get_lazy(row, column, fn ->
+      # Atom which does not exist cannot be a key either
+      try do
+        Map.get(row, String.to_existing_atom(column))
+      rescue
+        ArgumentError -> nil
       end
+    end)
+  end
 
-    force =
-      if opts[:force] do
-        "FORCE "
-      else
-        ""
+  @doc """
+  Runs query and returns its result as Arrow C stream, for zero-copy handoff to
+  other NIFs loaded in the same VM, e.g. Explorer or Polars based ones.
+
+  Returns reference owning the stream. Pass its address from
+  `arrow_stream_address/1` to the consumer, which takes the stream over. Keep
+  the reference alive until the consumer imported it.
+  """
+  @spec query_arrow_stream(DBConnection.conn(), String.t(), list(), list()) ::
+          {:ok, reference()} | {:error, Error.t()}
+  def query_arrow_stream(conn, statement, params \\ [], opts \\ []) do
+    command(conn, :query_arrow_stream, [statement, params], opts)
+  end
+
+  @doc """
+  Runs query with numeric result and returns each of its columns packed into
+  contiguous binary, ready for `Nx`:
+
+      {:ok, [%{data: data, type: type, shape: shape} | _]} =
+        Duckex.query_tensors(conn, "SELECT x, y FROM points")
+
+      data |> Nx.from_binary(type) |> Nx.reshape(shape)
+
+  Each column is map with `:name`, `:type` (Nx type like `{:f, 64}`),
+  `:shape` and `:data` keys. Integer, floating point and boolean columns are
+  supported, columns of other types or holding `NULL` values fail the query.
+  """
+  @spec query_tensors(DBConnection.conn(), String.t(), list(), list()) ::
+          {:ok,
+           [
+             %{
+               name: String.t(),
+               type: {:s | :u | :f, pos_integer()},
+               shape: {non_neg_integer()},
+               data: binary()
+             }
+           ]}
+          | {:error, Error.t()}
+  def query_tensors(conn, statement, params \\ [], opts \\ []) do
+    command(conn, :query_tensors, [statement, params], opts)
+  end
+
+  @doc """
+  Returns memory address of the `ArrowArrayStream` struct owned by the stream
+  reference returned from `query_arrow_stream/4`.
+  """
+  @spec arrow_stream_address(reference()) :: {:ok, non_neg_integer()} | {:error, Error.t()}
+  def arrow_stream_address(stream) when is_reference(stream),
+    do: Duckex.Native.arrow_stream_address(stream)
+
+  @doc """
+  Exports result of the query to the file at `path` using `COPY ... TO`.
+
+  `format` is one of `:csv`, `:parquet` or `:json`. Returns number of exported
+  rows.
+
+  ## Options
+
+  - `:compression` - compression codec, for example `:zstd`, `:snappy` or
+    `:gzip`
+  - `:delimiter` - column delimiter, CSV only
+  - `:header` - whether to write header row, CSV only
+  - `:partition_by` - list of columns used for Hive partitioned output, `path`
+    is then treated as a directory
+  - `:overwrite` - overwrite existing files in the target directory
+
+  Rest of the options are passed to `DBConnection`.
+  """
+  @spec copy_to(DBConnection.conn(), String.t(), Path.t(), :csv | :parquet | :json, keyword()) ::
+          {:ok, non_neg_integer()} | {:error, Error.t()}
+  def copy_to(conn, statement, path, format, opts \\ []) do
+    {copy_opts, opts} =
+      Keyword.split(opts, [:compression, :delimiter, :header, :partition_by, :overwrite])
+
+    copy_opts =
+      case copy_opts[:partition_by] do
+        nil -> copy_opts
+        columns -> Keyword.put(copy_opts, :partition_by, Enum.map(columns, &to_string/1))
       end
 
-    query!(conn, "#{force}INSTALL #{name}#{from}", [])
+    command(conn, :copy_to, [statement, to_string(path), format, copy_opts], opts)
   end
 
-  def attach(conn, path, opts \\ [], conn_opts \\ []) do
-    path = escape(path)
-    as = if val = opts[:as], do: " AS #{val}"
-    options = format_attach_options(opts[:options])
-    options_part = if options == "", do: "", else: " (#{options})"
+  @doc """
+  Runs `statement` with `params` and writes its result straight to the file at
+  `path`, without converting rows to Elixir terms. Meant for large extracts,
+  which would not fit into memory of the VM.
+
+  Returns map with the `:path`, `:size` of the written file in bytes (total
+  size of all files with `:partition_by`) and number of written `:rows`.
+
+  ## Options
+
+  - `:format` - `:csv`, `:parquet`, `:json` or `:arrow` (Arrow IPC file, also
+    known as Feather v2). By default it is derived from extension of `path`.
+  - `:compression`, `:delimiter`, `:header`, `:partition_by` and `:overwrite` -
+    the same as in `copy_to/5`. Only `:compression` is supported for `:arrow`,
+    see `export_arrow/5`.
+
+  Rest of the options are passed to `DBConnection`.
+  """
+  @spec execute_to_file(DBConnection.conn(), String.t(), list(), Path.t(), keyword()) ::
+          {:ok, %{path: String.t(), size: non_neg_integer(), rows: non_neg_integer()}}
+          | {:error, Error.t()}
+  def execute_to_file(conn, statement, params, path, opts \\ []) do
+    {copy_opts, opts} =
+      Keyword.split(opts, [:compression, :delimiter, :header, :partition_by, :overwrite])
 
-    query(conn, "ATTACH '#{path}'#{as}#{options_part}", [], conn_opts)
+    {format, opts} = Keyword.pop_lazy(opts, :format, fn -> file_format(path) end)
+
+    copy_opts =
+      case copy_opts[:partition_by] do
+        nil -> copy_opts
+        columns -> Keyword.put(copy_opts, :partition_by, Enum.map(columns, &to_string/1))
+      end
+
+    command(conn, :execute_to_file, [statement, params, to_string(path), format, copy_opts], opts)
   end
 
-  def attach!(conn, path, opts \\ [], conn_opts \\ []) do
-    case attach(conn, path, opts, conn_opts) do
-      {:ok, result} -> result
-      {:error, error} -> raise error
+  @doc """
+  Writes result of `statement` run with `params` to Arrow IPC file (Feather v2)
+  at `path`, which can be read by `pyarrow.feather`, R `arrow` package or
+  Polars without any Parquet dependency.
+
+  ## Options
+
+  - `:compression` - `:zstd` or `:lz4` to compress buffers of the file,
+    uncompressed by default
+
+  Rest of the options are passed to `DBConnection`. Returns the same as
+  `execute_to_file/5`.
+  """
+  @spec export_arrow(DBConnection.conn(), String.t(), list(), Path.t(), keyword()) ::
+          {:ok, %{path: String.t(), size: non_neg_integer(), rows: non_neg_integer()}}
+          | {:error, Error.t()}
+  def export_arrow(conn, statement, params, path, opts \\ []) do
+    execute_to_file(conn, statement, params, path, Keyword.put(opts, :format, :arrow))
+  end
+
+  defp file_format(path) do
+    case path |> Path.extname() |> String.downcase() do
+      ".parquet" -> :parquet
+      ".json" -> :json
+      ".ndjson" -> :json
+      ext when ext in [".arrow", ".feather", ".ipc"] -> :arrow
+      _ -> :csv
     end
   end
 
-  defp format_attach_options(nil), do: ""
+  @doc """
+  Runs query and returns its result encoded as Parquet file contents.
+
+  Useful for serving Parquet downloads without managing temporary files. Only
+  `:compression` option from `copy_to/5` is supported, rest of the options are
+  passed to `DBConnection`.
+  """
+  @spec export_parquet(DBConnection.conn(), String.t(), keyword()) ::
+          {:ok, binary()} | {:error, Error.t()}
+  def export_parquet(conn, statement, opts \\ []) do
+    {parquet_opts, opts} = Keyword.split(opts, [:compression])
+
+    command(conn, :export_parquet, [statement, parquet_opts], opts)
+  end
+
+  @doc """
+  Loads CSV `data` held in memory, e.g. uploaded file, into new table `table`
+  without persisting it first. Returns number of loaded rows.
+
+  Column names and types are detected by DuckDB.
 
-  defp format_attach_options(opts) do
-    opts
-    |> Enum.flat_map(fn
-      {_key, false} -> []
-      {key, true} -> ["#{key}"]
-      {key, value} when is_atom(value) -> ["#{key} #{value}"]
-      {key, value} -> ["#{key} '#{String.replace(to_string(value), "'", "''")}'"]
-    end)
-    |> Enum.join(", ")
+  ## Options
+
+  - `:temporary` - create temporary table, dropped when the connection closes,
+    `true` by default
+  - `:replace` - replace the table when it already exists
+  - `:header` - whether the first row holds column names, detected by default
+  - `:delimiter` - column delimiter, detected by default
+  - `:quote` - quoting character
+  - `:skip` - number of lines to skip at the beginning
+  - `:null` - string representing `NULL` values
+
+  Rest of the options are passed to `DBConnection`.
+  """
+  @spec load_csv_binary(DBConnection.conn(), String.t(), iodata(), keyword()) ::
+          {:ok, non_neg_integer()} | {:error, Error.t()}
+  def load_csv_binary(conn, table, data, opts \\ []) do
+    {csv_opts, opts} =
+      Keyword.split(opts, [:temporary, :replace, :header, :delimiter, :quote, :skip, :null])
+
+    command(
+      conn,
+      :load_csv_binary,
+      [to_string(table), IO.iodata_to_binary(data), csv_opts],
+      opts
+    )
+  end
+
+  @doc """
+  Loads newline-delimited JSON `data` held in memory into new table `table`,
+  each line becoming one row. Columns are inferred by DuckDB JSON reader.
+
+  Returns map with number of loaded `:rows` and `:columns`, list of maps with
+  `:name` and `:type`.
+
+  ## Options
+
+  - `:temporary` - create temporary table, dropped when the connection closes,
+    `true` by default
+  - `:replace` - replace the table when it already exists
+
+  Rest of the options are passed to `DBConnection`.
+  """
+  @spec load_ndjson_binary(DBConnection.conn(), String.t(), iodata(), keyword()) ::
+          {:ok, %{rows: non_neg_integer(), columns: [%{name: String.t(), type: String.t()}]}}
+          | {:error, Error.t()}
+  def load_ndjson_binary(conn, table, data, opts \\ []) do
+    {load_opts, opts} = Keyword.split(opts, [:temporary, :replace])
+
+    command(
+      conn,
+      :load_ndjson_binary,
+      [to_string(table), IO.iodata_to_binary(data), load_opts],
+      opts
+    )
+  end
+
+  @doc """
+  Detects dialect and schema of CSV file at `path` without loading it, so it
+  can be validated first.
+
+  Returns map with `:delimiter`, `:quote`, `:header` (whether the first row
+  holds column names) and `:columns`, list of maps with `:name` and `:type`.
+  """
+  @spec sniff_csv(DBConnection.conn(), Path.t(), keyword()) ::
+          {:ok,
+           %{
+             delimiter: String.t(),
+             quote: String.t(),
+             header: boolean(),
+             columns: [%{name: String.t(), type: String.t()}]
+           }}
+          | {:error, Error.t()}
+  def sniff_csv(conn, path, opts \\ []), do: command(conn, :sniff_csv, [to_string(path)], opts)
+
+  @doc """
+  Exports whole database (schema and data of all tables) to directory `dir`
+  using `EXPORT DATABASE`.
+
+  Export can be followed with `progress/1` while it runs.
+
+  ## Options
+
+  - `:format` - `:parquet` (default), `:csv` or `:json`
+  - `:compression`, `:delimiter`, `:header` - same as in `copy_to/5`
+
+  Rest of the options are passed to `DBConnection`.
+  """
+  @spec export_database(DBConnection.conn(), Path.t(), keyword()) :: :ok | {:error, Error.t()}
+  def export_database(conn, dir, opts \\ []) do
+    {format, opts} = Keyword.pop(opts, :format, :parquet)
+    {export_opts, opts} = Keyword.split(opts, [:compression, :delimiter, :header])
+
+    with {:ok, _} <-
+           command(conn, :export_database, [to_string(dir), format, export_opts], opts),
+         do: :ok
+  end
+
+  @doc """
+  Imports database exported with `export_database/3` from directory `dir`.
+  """
+  @spec import_database(DBConnection.conn(), Path.t(), keyword()) :: :ok | {:error, Error.t()}
+  def import_database(conn, dir, opts \\ []) do
+    with {:ok, _} <- command(conn, :import_database, [to_string(dir)], opts), do: :ok
+  end
+
+  @doc """
+  Sets DuckDB setting `name` on the connection, e.g.
+  `Duckex.set_setting(conn, :threads, 4)`.
+
+  Booleans and numbers are passed as they are, strings and atoms as string
+  literals. Note that with a pool each connection has to be configured
+  separately.
+  """
+  @spec set_setting(DBConnection.conn(), atom() | String.t(), term(), keyword()) ::
+          :ok | {:error, Error.t()}
+  def set_setting(conn, name, value, opts \\ []) do
+    with {:ok, _} <- command(conn, :set_setting, [to_string(name), value], opts), do: :ok
+  end
+
+  @doc """
+  Sets session time zone used for `TIMESTAMPTZ` values, e.g. `"Europe/Warsaw"`.
+
+  Time zones are provided by ICU extension, which is loaded (and installed if
+  needed) first.
+  """
+  @spec set_timezone(DBConnection.conn(), String.t(), keyword()) :: :ok | {:error, Error.t()}
+  def set_timezone(conn, timezone, opts \\ []) do
+    with {:ok, _} <- command(conn, :set_timezone, [timezone], opts), do: :ok
+  end
+
+  @doc """
+  Sets HTTP settings of `httpfs` extension, used for S3 and HTTP(S) access.
+  The extension is loaded automatically on first use of them.
+
+  All `http_opts` are validated before anything is set, they are:
+
+  - `:timeout` - HTTP timeout in seconds
+  - `:retries` - number of retries on I/O errors
+  - `:retry_wait_ms` - time between retries in milliseconds
+  - `:retry_backoff` - multiplier of the time between consecutive retries
+  - `:keep_alive` - whether to keep connections alive
+  - `:proxy` - proxy URL, e.g. `"http://proxy.internal:3128"`
+  - `:proxy_username` and `:proxy_password` - proxy credentials
+
+  `opts` are passed to `DBConnection`.
+  """
+  @spec set_http_options(DBConnection.conn(), keyword(), keyword()) ::
+          :ok | {:error, Error.t()}
+  def set_http_options(conn, http_opts, opts \\ []) do
+    with {:ok, _} <- command(conn, :set_http_options, [http_opts], opts), do: :ok
+  end
+
+  @doc """
+  Returns current value of DuckDB setting `name`.
+
+  Value is converted according to the setting type, so boolean settings return
+  booleans, numeric ones numbers and the rest strings, e.g. `"1.5 GiB"` for
+  `:memory_limit`. Unknown settings fail with `:not_found` error.
+  """
+  @spec get_setting(DBConnection.conn(), atom() | String.t(), keyword()) ::
+          {:ok, boolean() | number() | String.t() | nil} | {:error, Error.t()}
+  def get_setting(conn, name, opts \\ []),
+    do: command(conn, :get_setting, [to_string(name)], opts)
+
+  @doc """
+  Enables or disables query profiling on the connection.
+
+  When enabled, DuckDB records JSON profile (operator timings, cardinalities)
+  of every query, which can be fetched with `last_profile/2`.
+
+  Note that with a pool each connection has to be configured separately, so
+  this is mostly useful with `pool_size: 1` or inside `transaction/3`.
+  """
+  @spec enable_profiling(DBConnection.conn(), boolean(), keyword()) :: :ok | {:error, Error.t()}
+  def enable_profiling(conn, enabled \\ true, opts \\ []) do
+    with {:ok, _} <- command(conn, :enable_profiling, [enabled], opts), do: :ok
+  end
+
+  @doc """
+  Returns decoded JSON profile of the last query executed on the connection, or
+  `nil` when no query was run since profiling was enabled.
+  """
+  @spec last_profile(DBConnection.conn(), keyword()) :: {:ok, map() | nil} | {:error, Error.t()}
+  def last_profile(conn, opts \\ []) do
+    case command(conn, :last_profile, [], opts) do
+      {:ok, nil} -> {:ok, nil}
+      {:ok, json} -> {:ok, JSON.decode!(json)}
+      {:error, _} = error -> error
+    end
+  end
+
+  @doc """
+  Returns query plan of the prepared query (or SQL string) as nested maps.
+
+  Each plan node is a map with `"name"`, `"children"` and `"extra_info"` keys,
+  as produced by DuckDB's `EXPLAIN (FORMAT json)`.
+
+  ## Options
+
+  - `:analyze` - run the query and include actual timings and cardinalities
+  - `:params` - parameters to bind to the query, needed for `:analyze`
+
+  Rest of the options are passed to `DBConnection`.
+  """
+  @spec explain(DBConnection.conn(), Query.t() | String.t(), keyword()) ::
+          {:ok, [map()]} | {:error, Error.t()}
+  def explain(conn, query, opts \\ [])
+
+  def explain(conn, %Query{stmt: stmt}, opts) do
+    {explain_opts, opts} = Keyword.split(opts, [:analyze, :params])
+
+    command(conn, :explain, [stmt, explain_opts], opts)
+  end
+
+  def explain(conn, statement, opts) when is_binary(statement) do
+    DBConnection.run(
+      conn,
+      fn conn ->
+        with {:ok, query} <- prepare(conn, statement, opts) do
+          result = explain(conn, query, opts)
+          close(conn, query, opts)
+          result
+        end
+      end,
+      opts
+    )
+  end
+
+  @doc """
+  Returns names and DuckDB types of the columns the query would return, without
+  running it.
+
+  Queries with parameters cannot be described.
+
+  Each column also has `:nullable` flag. For simple queries selecting columns
+  or `*` from single table `:table` and `:column` tell where the value comes
+  from, then `:nullable` reflects `NOT NULL` constraint of that column. They
+  are `nil` for computed values.
+
+  ## Example
+
+      Duckex.describe(conn, "SELECT id, upper(name) AS label FROM users")
+      {:ok,
+       [
+         %{name: "id", type: "INTEGER", nullable: false, table: "users", column: "id"},
+         %{name: "label", type: "VARCHAR", nullable: true, table: nil, column: nil}
+       ]}
+  """
+  @spec describe(DBConnection.conn(), String.t(), keyword()) ::
+          {:ok,
+           [
+             %{
+               name: String.t(),
+               type: String.t(),
+               nullable: boolean() | nil,
+               table: String.t() | nil,
+               column: String.t() | nil
+             }
+           ]}
+          | {:error, Error.t()}
+  def describe(conn, statement, opts \\ []) when is_binary(statement),
+    do: command(conn, :describe, [statement], opts)
+
+  @doc """
+  Returns parameters expected by the prepared query.
+
+  Result is a map with `:count` of parameters and their `:types`, as DuckDB
+  type names, e.g. `"INTEGER"`. Type is `nil` when DuckDB cannot infer it,
+  e.g. for `SELECT ?`.
+  """
+  @spec statement_params(DBConnection.conn(), Query.t(), keyword()) ::
+          {:ok, %{count: non_neg_integer(), types: [String.t() | nil]}} | {:error, Error.t()}
+  def statement_params(conn, %Query{stmt: stmt}, opts \\ []),
+    do: command(conn, :statement_params, [stmt], opts)
+
+  @doc """
+  Returns statistics of the prepared statements cache of the connection.
+
+  When the cache is full, preparing new statement evicts the least recently
+  used one, which then fails to execute and needs to be prepared again.
+
+  Returns map with `:size` (statements currently cached), `:capacity`, `:hits`
+  and `:misses` (lookups of prepared statements that were found or not) and
+  `:evictions` keys.
+  """
+  @spec cache_stats(DBConnection.conn(), keyword()) ::
+          {:ok,
+           %{
+             size: non_neg_integer(),
+             capacity: non_neg_integer(),
+             hits: non_neg_integer(),
+             misses: non_neg_integer(),
+             evictions: non_neg_integer()
+           }}
+          | {:error, Error.t()}
+  def cache_stats(conn, opts \\ []), do: command(conn, :cache_stats, [], opts)
+
+  @doc """
+  Returns how much data DuckDB currently spills to the temporary directory.
+
+  Result has `:temp_directory`, `:max_temp_directory_size` (as reported by
+  DuckDB, e.g. `"90% of available disk space"`), `:files` with number of
+  temporary files and their total `:size` in bytes.
+  """
+  @spec temp_usage(DBConnection.conn(), keyword()) ::
+          {:ok,
+           %{
+             temp_directory: String.t(),
+             max_temp_directory_size: String.t(),
+             files: non_neg_integer(),
+             size: non_neg_integer()
+           }}
+          | {:error, Error.t()}
+  def temp_usage(conn, opts \\ []), do: command(conn, :temp_usage, [], opts)
+
+  @doc """
+  Returns memory usage of DuckDB buffer manager.
+
+  Result has current `:memory_usage` and `:temporary_storage` in bytes,
+  `:memory_limit` as reported by DuckDB (e.g. `"12.4 GiB"`) and `:by_tag` with
+  memory usage per component, like `"BASE_TABLE"` or `"HASH_TABLE"`.
+
+  DuckDB does not track peak usage, so `:peak_memory_usage` is the highest
+  usage returned by this function for the connection so far.
+  """
+  @spec memory_stats(DBConnection.conn(), keyword()) ::
+          {:ok,
+           %{
+             memory_usage: non_neg_integer(),
+             peak_memory_usage: non_neg_integer(),
+             memory_limit: String.t(),
+             temporary_storage: non_neg_integer(),
+             by_tag: %{String.t() => non_neg_integer()}
+           }}
+          | {:error, Error.t()}
+  def memory_stats(conn, opts \\ []), do: command(conn, :memory_stats, [], opts)
+
+  @doc """
+  Removes all statements from the prepared statements cache, for example after
+  migrations. Queries prepared before need to be prepared again.
+  """
+  @spec cache_clear(DBConnection.conn(), keyword()) :: :ok | {:error, Error.t()}
+  def cache_clear(conn, opts \\ []) do
+    with {:ok, _} <- command(conn, :cache_clear, [], opts), do: :ok
+  end
+
+  @doc """
+  Changes capacity of the prepared statements cache of the connection, evicting
+  least recently used statements that do not fit anymore.
+  """
+  @spec cache_resize(DBConnection.conn(), non_neg_integer(), keyword()) ::
+          :ok | {:error, Error.t()}
+  def cache_resize(conn, capacity, opts \\ []) when is_integer(capacity) and capacity >= 0 do
+    with {:ok, _} <- command(conn, :cache_resize, [capacity], opts), do: :ok
+  end
+
+  @doc """
+  Returns reference to the underlying DuckDB connection.
+
+  It can be used to call functions that need to work while the connection is
+  busy running a query, like `progress/1`. With a pool, connection that was
+  checked out to serve this call is returned.
+  """
+  @spec resource(DBConnection.conn(), keyword()) :: {:ok, reference()} | {:error, Error.t()}
+  def resource(conn, opts \\ []), do: command(conn, :resource, [], opts)
+
+  @doc """
+  Reports progress of the query currently running on the connection.
+
+  Returns map with `:running`, `:query`, `:percentage`, `:rows_processed`,
+  `:total_rows`, `:rows_fetched` and `:elapsed_us` keys. Percentage and rows
+  processed come from DuckDB progress bar, `:percentage` is `nil` until DuckDB
+  can estimate it. `:rows_fetched` counts result rows fetched so far. Takes
+  connection reference returned by `resource/2`, as the connection itself is
+  busy while the query runs.
+  """
+  @spec progress(reference()) :: %{
+          running: boolean(),
+          query: String.t() | nil,
+          percentage: float() | nil,
+          rows_processed: non_neg_integer(),
+          total_rows: non_neg_integer(),
+          rows_fetched: non_neg_integer(),
+          elapsed_us: non_neg_integer()
+        }
+  def progress(resource) when is_reference(resource), do: Duckex.Native.progress(resource)
+
+  @doc """
+  Runs reading query on a clone of the connection, in the calling process.
+
+  Takes connection reference returned by `resource/2`. Unlike `query/4`, which
+  runs queries on the connection one by one, multiple processes can read
+  concurrently this way, also while the connection is busy. Clones share the
+  database with the connection, but not its transaction, so they see only
+  committed data. Only `SELECT`-like statements are accepted.
+
+  Supports `:query_timeout`, `:layout`, `:chunk_size`, `:max_rows`,
+  `:max_result_bytes` and `:column_info` options of `query/4`.
+  """
+  @spec read(reference(), String.t(), list(), keyword()) ::
+          {:ok, Result.t()} | {:error, Error.t()}
+  def read(resource, statement, params \\ [], opts \\ []) when is_reference(resource) do
+    with {:ok, result} <- Duckex.NIF.read(resource, statement, params, opts) do
+      {:ok, DBConnection.Query.decode(%Query{}, result, opts)}
+    end
+  end
+
+  @doc """
+  Writes the WAL into the main database file, e.g. before taking a filesystem
+  snapshot.
+
+  Fails with `:transaction` error when other transactions are running, unless
+  `force: true` is given, in which case they are aborted.
+  """
+  @spec checkpoint(DBConnection.conn(), keyword()) :: :ok | {:error, Error.t()}
+  def checkpoint(conn, opts \\ []) do
+    {force, opts} = Keyword.pop(opts, :force, false)
+
+    with {:ok, _} <- command(conn, :checkpoint, [force], opts), do: :ok
+  end
+
+  @doc """
+  Closes the underlying DuckDB connection, releasing the database file lock.
+
+  Open transaction is rolled back and prepared queries are dropped. Afterwards
+  all calls on the connection fail with `:closed` error, which makes
+  `DBConnection` reconnect.
+  """
+  @spec disconnect(DBConnection.conn(), keyword()) :: :ok | {:error, Error.t()}
+  def disconnect(conn, opts \\ []) do
+    with {:ok, _} <- command(conn, :disconnect, [], opts), do: :ok
+  end
+
+  @doc """
+  Checks health of the connection.
+
+  Returns map with `:state` - `:idle` when connection answered check query,
+  `:busy` when it is running other queries, `:broken` when it needs to be
+  reopened (with the reason in `:error`) or `:closed` after `disconnect/2`,
+  `:database` path and DuckDB
+  `:access_mode` and `:queued` number of calls waiting for or running on the
+  worker thread of the connection. Accepts also connection reference returned by
+  `resource/2`, which can be checked while the connection is busy.
+  """
+  @spec status(DBConnection.conn() | reference(), keyword()) ::
+          {:ok,
+           %{
+             state: :idle | :busy | :broken | :closed,
+             database: String.t(),
+             access_mode: String.t() | nil,
+             error: String.t() | nil,
+             queued: non_neg_integer()
+           }}
+          | {:error, Error.t()}
+  def status(conn, opts \\ [])
+
+  def status(resource, _opts) when is_reference(resource), do: Duckex.Native.status(resource)
+
+  def status(conn, opts), do: command(conn, :status, [], opts)
+
+  @doc """
+  Registers process notified about every query executed on the connection,
+  e.g. for logging slow queries in one place. Pass `nil` to remove it.
+
+  After each query the process receives `{:duckex_query, sql, duration_us,
+  num_rows}` message, where `duration_us` is the time query ran, not counting
+  time it waited for the connection.
+  """
+  @spec set_log_handler(DBConnection.conn(), pid() | nil, keyword()) ::
+          :ok | {:error, Error.t()}
+  def set_log_handler(conn, handler, opts \\ []) when is_pid(handler) or is_nil(handler) do
+    with {:ok, _} <- command(conn, :set_log_handler, [handler], opts), do: :ok
+  end
+
+  @doc """
+  Executes prepared query once for each list of parameters in `params_list`.
+
+  All executions happen in a single NIF call within single transaction (or the
+  one already open), which makes it much faster than calling `execute/4` for
+  each row, e.g. for batch inserts. Returns total number of changed rows.
+  """
+  @spec execute_many(DBConnection.conn(), Query.t(), [list()], keyword()) ::
+          {:ok, non_neg_integer()} | {:error, Error.t()}
+  def execute_many(conn, %Query{stmt: stmt}, params_list, opts \\ []) when is_list(params_list) do
+    command(conn, :execute_many, [stmt, params_list], opts)
+  end
+
+  @doc """
+  Executes prepared query on the worker thread of the connection, without
+  waiting for it. Every call on the connection runs on its worker thread one by
+  one, in the order they were submitted, so other calls on the connection wait
+  for the query, except `status/2` of the reference from `resource/2`, which
+  returns `:busy` state instead.
+
+  Returns `{:ok, ref}` immediately, then `{:duckex_result, ref, result}` is sent
+  to the calling process, where `result` is `{:ok, result}` with not decoded
+  rows or `{:error, error}`. Use `await/2` to receive and decode it. The query
+  must stay prepared until the result arrives.
+
+  Supports `:query_timeout` and `:layout` options of `query/4`.
+
+  The calling process is monitored. When it exits before the result arrives,
+  its query is interrupted, or not started at all when still queued, and the
+  open transaction is rolled back, so abandoned queries do not keep the
+  connection busy.
+  """
+  @spec execute_async(DBConnection.conn(), Query.t(), list(), keyword()) ::
+          {:ok, reference()} | {:error, Error.t()}
+  def execute_async(conn, %Query{stmt: stmt}, params, opts \\ []) do
+    nif_opts = [timeout: opts[:query_timeout], layout: opts[:layout], reply_to: self()]
+
+    command(conn, :execute_async, [stmt, params, nif_opts], opts)
+  end
+
+  @doc """
+  Waits for the result of `execute_async/4` and decodes it.
+  """
+  @spec await(reference(), timeout()) :: {:ok, Result.t()} | {:error, Error.t()}
+  def await(ref, timeout \\ 5_000) when is_reference(ref) do
+    receive do
+      {:duckex_result, ^ref, {:ok, result}} ->
+        {:ok, DBConnection.Query.decode(%Query{}, result, [])}
+
+      {:duckex_result, ^ref, {:error, error}} ->
+        {:error, error}
+    after
+      timeout ->
+        {:error, %Error{message: "Timed out waiting for the result", kind: :timeout}}
+    end
+  end
+
+  @doc """
+  Fetches next `count` rows of the result returned for query run with
+  `lazy: true`. Fewer rows may be returned when encoding them would block the
+  scheduler for too long.
+
+  Returns `{:ok, result, :more}` while there are rows left and
+  `{:ok, result, :done}` with the last chunk, `result` holding the fetched rows
+  in its layout.
+  """
+  @spec fetch(Result.t(), pos_integer()) ::
+          {:ok, Result.t(), :more | :done} | {:error, Error.t()}
+  def fetch(%Result{handle: nil} = result, _count), do: {:ok, %{result | rows: []}, :done}
+
+  def fetch(%Result{handle: handle} = result, count) when is_integer(count) and count > 0 do
+    with {:ok, {rows, done}} <- Duckex.Native.result_fetch(handle, count) do
+      chunk = DBConnection.Query.decode(%Query{}, %{result | rows: rows, handle: nil}, [])
+
+      {:ok, %{chunk | handle: handle}, if(done, do: :done, else: :more)}
+    end
+  end
+
+  @doc """
+  Frees rows of the lazy result that were not fetched yet, without waiting for
+  the garbage collection.
+  """
+  @spec close_result(Result.t()) :: :ok | {:error, Error.t()}
+  def close_result(%Result{handle: nil}), do: :ok
+
+  def close_result(%Result{handle: handle}) do
+    with {:ok, _} <- Duckex.Native.result_close(handle), do: :ok
+  end
+
+  @doc """
+  Returns stream of rows of the result returned for query run with
+  `lazy: true`, decoding `chunk_size` (10 000 by default) rows per NIF call
+  only as the stream is consumed. Results in `:columnar` layout yield one list
+  of columns per chunk instead.
+
+  Rows left unfetched when the stream is halted are freed with
+  `close_result/1`.
+  """
+  @spec stream_result(Result.t(), pos_integer()) :: Enumerable.t()
+  def stream_result(%Result{} = result, chunk_size \\ 10_000)
+      when is_integer(chunk_size) and chunk_size > 0 do
+    Stream.resource(
+      fn -> result end,
+      fn
+        nil ->
+          {:halt, nil}
+
+        result ->
+          case fetch(result, chunk_size) do
+            {:ok, chunk, :more} -> {chunk_rows(chunk), result}
+            {:ok, chunk, :done} -> {chunk_rows(chunk), nil}
+            {:error, error} -> raise error
+          end
+      end,
+      fn
+        nil -> :ok
+        result -> close_result(result)
+      end
+    )
+  end
+
+  defp chunk_rows(%Result{layout: :columnar, rows: columns}), do: [columns]
+  defp chunk_rows(%Result{rows: rows}), do: rows
+
+  @doc """
+  Returns `:transaction` when there is transaction open on the connection and
+  `:idle` otherwise.
+
+  Transactions started and finished with `transaction/3` as well as with plain
+  `BEGIN`, `COMMIT` and `ROLLBACK` queries are tracked, so transactions leaked
+  by the latter can be detected and rolled back. Takes connection or its
+  reference returned by `resource/2`.
+  """
+  @spec transaction_status(DBConnection.conn() | reference(), keyword()) ::
+          {:ok, :idle | :transaction} | {:error, Error.t()}
+  def transaction_status(conn, opts \\ [])
+
+  def transaction_status(resource, _opts) when is_reference(resource),
+    do: Duckex.Native.transaction_status(resource)
+
+  def transaction_status(conn, opts), do: command(conn, :transaction_status, [], opts)
+
+  @doc """
+  Executes a script containing multiple SQL statements separated by semicolons.
+
+  All statements are run inside a single transaction, so either the whole
+  script is applied or none of it is. This is useful for running migrations
+  without splitting statements in Elixir.
+  """
+  @spec execute_batch(DBConnection.conn(), String.t(), list()) :: :ok | {:error, Error.t()}
+  def execute_batch(conn, sql, opts \\ []) do
+    with {:ok, _} <- command(conn, :execute_batch, [sql], opts), do: :ok
+  end
+
+  @doc """
+  Executes a multi-statement script or raises `Duckex.Error` if there is an
+  error. See `execute_batch/3`.
+  """
+  @spec execute_batch!(DBConnection.conn(), String.t(), list()) :: :ok
+  def execute_batch!(conn, sql, opts \\ []) do
+    case execute_batch(conn, sql, opts) do
+      :ok -> :ok
+      {:error, error} -> raise error
+    end
+  end
+
+  # Calls `Duckex.Native` function `name` with the connection resource
+  # prepended to `args`
+  defp command(conn, name, args, opts) do
+    with {:ok, _command, result} <-
+           DBConnection.execute(conn, %Command{name: name, args: args}, [], opts) do
+      {:ok, result}
+    end
+  end
+
+  defdelegate close(pid, query, opts \\ []), to: DBConnection
+  defdelegate close!(pid, query, opts \\ []), to: DBConnection
+
+  defdelegate execute(pid, query, values, opts \\ []), to: DBConnection
+  defdelegate execute!(pid, query, values, opts \\ []), to: DBConnection
+
+  @doc """
+  Runs `fun` inside a transaction, see `DBConnection.transaction/3`.
+
+  DuckDB has no savepoints, so nested calls run within the outer transaction
+  and rolling back any of them rolls back the whole transaction.
+
+  ## Options
+
+  - `:read_only` - start the transaction as read-only. DuckDB then fails
+    queries modifying the database with `Duckex.Error` of `kind: :read_only`.
+
+  Rest of the options are passed to `DBConnection`.
+  """
+  defdelegate transaction(conn, fun, opts \\ []), to: DBConnection
+
+  @spec rollback(DBConnection.t(), reason :: any()) :: no_return()
+  defdelegate rollback(conn, reason), to: DBConnection
+
+  defp escape(val), do: String.replace(to_string(val), "'", "''")
+
+  @doc """
+  Install extensions for DuckDB.
+
+  This will spawn temporary connection and will install extensions within that
+  connection. After that each new DuckDB instance is capable of using that
+  extensions.
+
+  It is a helper function to provide a cleaner syntax - extensions use similar
+  syntax to `Mix.install/2` and are either:
+  - atom containing name of the extension to be installed
+  - tuple in form of `{atom(), opts}`
+
+  ## Options
+
+  - `:source` - source from which the extension should be installed. Supported
+    options are:
+    + `:default` (used when option is not specified) - uses default
+      registry configured for connection
+    + `:core`
+    + `:nightly` - equivalent of `core_nightly` from DuckDB
+    + `:community` - equivalent of `community` from DuckDB
+    + String representing URL from which the extension should be installed
+  - `:force` - forcefully install extension, even when it is already installed.
+    Can be used to update extension.
+  """
+  @spec install_extensions([extension], [connection_option()]) :: :ok | {:error, term()}
+        when extension_opt:
+               {:source, :default | :core | :nightly | String.t()} | {:force, boolean()},
+             extension: atom() | {atom(), [extension_opt]}
+  def install_extensions(extensions, opts \\ []) do
+    with {:ok, conn} <- start_link(opts) do
+      Enum.each(extensions, &do_install(conn, &1))
+
+      Process.exit(conn, :normal)
+
+      :ok
+    end
+  end
+
+  defp do_install(conn, name) when is_atom(name), do: do_install(conn, {name, []})
+
+  defp do_install(conn, {name, opts}) do
+    from =
+      case opts[:source] do
+        :core -> " FROM core"
+        :nightly -> " FROM core_nightly"
+        :community -> " FROM community"
+        nil -> ""
+        :default -> ""
+        repo when is_binary(repo) -> " FROM '#{escape(repo)}'"
+      end
+
+    force =
+      if opts[:force] do
+        "FORCE "
+      else
+        ""
+      end
+
+    query!(conn, "#{force}INSTALL #{name}#{from}", [])
+  end
+
+  @doc """
+  Creates SQL macro `name` taking `params`, which expands to SQL expression
+  `body` over them.
+
+  DuckDB expands the macro into the queries calling it, so it costs the same as
+  writing the expression by hand. The body is SQL only, it cannot call back
+  into Elixir.
+
+  ## Options
+
+  - `:temporary` - whether the macro exists only for this connection, `true`
+    by default. Otherwise it is stored in the database.
+
+  Rest of the options are passed to `DBConnection`.
+
+  ## Example
+
+      Duckex.create_macro(conn, :normalize_email, [:email], "lower(trim(email))")
+      Duckex.query(conn, "SELECT normalize_email(?)", ["  Bob@Example.COM "])
+  """
+  @spec create_macro(
+          DBConnection.conn(),
+          atom() | String.t(),
+          [atom() | String.t()],
+          String.t(),
+          keyword()
+        ) :: :ok | {:error, Error.t()}
+  def create_macro(conn, name, params, body, opts \\ []) do
+    {macro_opts, opts} = Keyword.split(opts, [:temporary])
+    args = [to_string(name), Enum.map(params, &to_string/1), body, macro_opts]
+
+    with {:ok, _} <- command(conn, :create_macro, args, opts), do: :ok
+  end
+
+  @doc """
+  Drops macro created with `create_macro/5`, if it exists.
+  """
+  @spec drop_macro(DBConnection.conn(), atom() | String.t(), keyword()) ::
+          :ok | {:error, Error.t()}
+  def drop_macro(conn, name, opts \\ []) do
+    with {:ok, _} <- command(conn, :drop_macro, [to_string(name)], opts), do: :ok
+  end
+
+  @doc """
+  Creates full-text search index over text `columns` of `table`, which rows are
+  identified by unique `id` column. Search it with `fts_search/5`.
+
+  The index is not updated when the table changes, it has to be recreated
+  with `overwrite: true`.
+
+  ## Options
+
+  - `:stemmer` - stemmer language, e.g. `:english` or `:none`, `:porter` by
+    default
+  - `:stopwords` - table with stop words, `:english` by default or `:none`
+  - `:ignore` - regular expression of ignored characters
+  - `:strip_accents` - whether to remove accents, `true` by default
+  - `:lower` - whether to lowercase text, `true` by default
+  - `:overwrite` - replace existing index
+
+  Rest of the options are passed to `DBConnection`.
+  """
+  @spec create_fts_index(
+          DBConnection.conn(),
+          atom() | String.t(),
+          atom() | String.t(),
+          [atom() | String.t()],
+          keyword()
+        ) :: :ok | {:error, Error.t()}
+  def create_fts_index(conn, table, id, columns, opts \\ []) do
+    {index_opts, opts} =
+      Keyword.split(opts, [:stemmer, :stopwords, :ignore, :strip_accents, :lower, :overwrite])
+
+    args = [to_string(table), to_string(id), Enum.map(columns, &to_string/1), index_opts]
+
+    with {:ok, _} <- command(conn, :create_fts_index, args, opts), do: :ok
+  end
+
+  @doc """
+  Drops full-text search index of `table` created with `create_fts_index/5`.
+  """
+  @spec drop_fts_index(DBConnection.conn(), atom() | String.t(), keyword()) ::
+          :ok | {:error, Error.t()}
+  def drop_fts_index(conn, table, opts \\ []) do
+    with {:ok, _} <- command(conn, :drop_fts_index, [to_string(table)], opts), do: :ok
+  end
+
+  @doc """
+  Searches `table` indexed with `create_fts_index/5` for `text`, scoring rows
+  with BM25.
+
+  Returns all columns of matching rows and their `score` as the last column,
+  best matches first.
+
+  ## Options
+
+  - `:fields` - list of indexed columns to search, all by default
+  - `:limit` - maximal number of returned rows
+  - `:conjunctive` - require all terms of `text` to match
+  - `:k` and `:b` - BM25 parameters, `1.2` and `0.75` by default
+
+  Rest of the options are passed to `query/4`.
+
+  ## Example
+
+      Duckex.create_fts_index(conn, :documents, :id, [:title, :body])
+      Duckex.fts_search(conn, :documents, :id, "duck pond", limit: 10)
+  """
+  @spec fts_search(
+          DBConnection.conn(),
+          atom() | String.t(),
+          atom() | String.t(),
+          String.t(),
+          keyword()
+        ) :: {:ok, Result.t()} | {:error, Error.t()}
+  def fts_search(conn, table, id, text, opts \\ []) do
+    {search_opts, opts} = Keyword.split(opts, [:fields, :limit, :conjunctive, :k, :b])
+
+    args =
+      Enum.flat_map(search_opts, fn
+        {:fields, fields} -> [", fields := '#{escape(Enum.join(fields, ","))}'"]
+        {:conjunctive, conjunctive} -> [", conjunctive := #{if conjunctive, do: 1, else: 0}"]
+        {key, value} when key in [:k, :b] and is_number(value) -> [", #{key} := #{value}"]
+        _ -> []
+      end)
+
+    limit = if is_integer(search_opts[:limit]), do: " LIMIT #{search_opts[:limit]}", else: ""
+
+    # Index lives in schema named after the table
+    schema = quote_identifier("fts_main_#{table}")
+    score = "#{schema}.match_bm25(#{quote_identifier(to_string(id))}, ?#{args})"
+
+    sql = """
+    SELECT * FROM (SELECT *, #{score} AS score FROM #{quote_identifier(to_string(table))})
+    WHERE score IS NOT NULL ORDER BY score DESC#{limit}
+    """
+
+    query(conn, sql, [text], opts)
+  end
+
+  @doc """
+  Installs extension `name` on the connection.
+
+  Accepts the same `:source` and `:force` options as `install_extensions/2`,
+  rest of the options are passed to `DBConnection`.
+
+  On failure `Duckex.Error` has `:kind` set to one of:
+
+  - `:network` - extension could not be downloaded
+  - `:signature` - extension signature is missing or invalid
+  - `:not_found` - extension does not exist in the given repository
+  - `:extension` - any other failure
+  """
+  @spec install_extension(DBConnection.conn(), atom() | String.t(), keyword()) ::
+          :ok | {:error, Error.t()}
+  def install_extension(conn, name, opts \\ []) do
+    {install_opts, opts} = Keyword.split(opts, [:source, :force])
+
+    with {:ok, _} <- command(conn, :install_extension, [to_string(name), install_opts], opts),
+         do: :ok
+  end
+
+  @doc """
+  Loads already installed extension `name` into the connection.
+
+  Errors are classified the same way as in `install_extension/3`.
+  """
+  @spec load_extension(DBConnection.conn(), atom() | String.t(), keyword()) ::
+          :ok | {:error, Error.t()}
+  def load_extension(conn, name, opts \\ []) do
+    with {:ok, _} <- command(conn, :load_extension, [to_string(name)], opts), do: :ok
+  end
+
+  @doc """
+  Attaches database at `path` to the connection.
+
+  Returns attached database info, see `list_databases/2`.
+
+  ## Options
+
+  - `:as` - name (alias) of the attached database, by default DuckDB derives it
+    from the file name
+  - `:options` - keyword list of `ATTACH` options, e.g. `[type: :sqlite,
+    read_only: true]`. `true` values are passed as flags, atoms as keywords and
+    everything else as string literals.
+
+  `conn_opts` are passed to `DBConnection`.
+  """
+  @spec attach(DBConnection.conn(), String.t(), keyword(), keyword()) ::
+          {:ok, database_info()} | {:error, Error.t()}
+  def attach(conn, path, opts \\ [], conn_opts \\ []) do
+    command(conn, :attach, [path, Keyword.take(opts, [:as, :options])], conn_opts)
+  end
+
+  def attach!(conn, path, opts \\ [], conn_opts \\ []) do
+    case attach(conn, path, opts, conn_opts) do
+      {:ok, result} -> result
+      {:error, error} -> raise error
+    end
+  end
+
+  @doc """
+  Detaches database `name` from the connection.
+  """
+  @spec detach(DBConnection.conn(), atom() | String.t(), keyword()) :: :ok | {:error, Error.t()}
+  def detach(conn, name, opts \\ []) do
+    with {:ok, _} <- command(conn, :detach, [to_string(name)], opts), do: :ok
+  end
+
+  @doc """
+  Attaches Iceberg REST catalog `warehouse`, installing and loading `iceberg`
+  extension when needed.
+
+  Returns attached database info, see `list_databases/2`.
+
+  ## Options
+
+  - `:as` - name of the attached catalog, `warehouse` by default
+  - `:endpoint` - URL of the REST catalog
+  - `:endpoint_type` - `:glue` or `:s3_tables` for AWS managed catalogs instead
+    of `:endpoint`
+  - `:authorization_type` - `:oauth2` (default), `:sigv4` or `:none`
+  - `:default_region` - region of the catalog storage
+  - `:secret` - name of already existing secret to use
+  - `:client_id`, `:client_secret`, `:oauth2_server_uri`, `:oauth2_scope` -
+    OAuth2 client credentials
+  - `:token` - bearer token, instead of client credentials
+
+  When credentials are given, secret `duckex_iceberg_<name>` holding them is
+  created. As with `create_s3_secret/3` they are passed as bind parameters, so
+  they never end up in SQL text.
+
+  Rejected credentials are reported with `kind: :unauthorized`.
+
+  `conn_opts` are passed to `DBConnection`.
+  """
+  @spec attach_iceberg(DBConnection.conn(), String.t(), keyword(), keyword()) ::
+          {:ok, database_info()} | {:error, Error.t()}
+  def attach_iceberg(conn, warehouse, opts \\ [], conn_opts \\ []) do
+    {credentials, opts} =
+      Keyword.split(opts, [:client_id, :client_secret, :oauth2_server_uri, :oauth2_scope, :token])
+
+    command(conn, :attach_iceberg, [warehouse, opts, credentials], conn_opts)
+  end
+
+  @doc """
+  Attaches Delta Lake table at `path` as a database, installing and loading
+  `delta` extension when needed. The table is then queried by the name of the
+  database, e.g. `SELECT * FROM sales`.
+
+  Returns attached database info, see `list_databases/2`.
+
+  ## Options
+
+  - `:as` - name of the attached database, by default DuckDB derives it from
+    the path
+  - `:pin_snapshot` - read the snapshot current at attach time, instead of
+    checking for new versions on each query
+
+  Rejected credentials of the storage are reported with `kind: :unauthorized`.
+
+  `conn_opts` are passed to `DBConnection`.
+  """
+  @spec attach_delta(DBConnection.conn(), String.t(), keyword(), keyword()) ::
+          {:ok, database_info()} | {:error, Error.t()}
+  def attach_delta(conn, path, opts \\ [], conn_opts \\ []) do
+    command(conn, :attach_delta, [path, Keyword.take(opts, [:as, :pin_snapshot])], conn_opts)
+  end
+
+  @doc """
+  Reads Iceberg table at `path` (table directory or metadata file) with
+  `iceberg_scan`, installing and loading `iceberg` extension when needed.
+
+  ## Options
+
+  - `:allow_moved_paths` - allow reading table which was moved from the path
+    recorded in its metadata
+  - `:version` - metadata version to read, e.g. `"2"`
+
+  Rest of the options are passed to `query/4`.
+  """
+  @spec iceberg_scan(DBConnection.conn(), String.t(), keyword()) ::
+          {:ok, Result.t()} | {:error, Error.t()}
+  def iceberg_scan(conn, path, opts \\ []) do
+    {scan_opts, opts} = Keyword.split(opts, [:allow_moved_paths, :version])
+
+    with :ok <- ensure_extension(conn, :iceberg, opts) do
+      query(conn, "SELECT * FROM iceberg_scan(#{scan_args(path, scan_opts)})", [], opts)
+    end
+  end
+
+  @doc """
+  Reads Delta Lake table at `path` with `delta_scan`, installing and loading
+  `delta` extension when needed.
+
+  Options are passed to `query/4`.
+  """
+  @spec delta_scan(DBConnection.conn(), String.t(), keyword()) ::
+          {:ok, Result.t()} | {:error, Error.t()}
+  def delta_scan(conn, path, opts \\ []) do
+    with :ok <- ensure_extension(conn, :delta, opts) do
+      query(conn, "SELECT * FROM delta_scan(#{scan_args(path, [])})", [], opts)
+    end
+  end
+
+  defp ensure_extension(conn, name, opts) do
+    with :ok <- install_extension(conn, name, opts), do: load_extension(conn, name, opts)
+  end
+
+  defp scan_args(path, opts) do
+    Enum.map_join([{nil, path} | opts], ", ", fn
+      {nil, value} -> "'#{escape(value)}'"
+      {key, value} when is_boolean(value) -> "#{key} = #{value}"
+      {key, value} -> "#{key} = '#{escape(value)}'"
+    end)
+  end
+
+  @doc """
+  Lists databases attached to the connection, including the main one.
+
+  Each database is a map with `:name`, `:path` (`nil` for in-memory databases)
+  and `:read_only` keys.
+  """
+  @spec list_databases(DBConnection.conn(), keyword()) ::
+          {:ok, [database_info()]} | {:error, Error.t()}
+  def list_databases(conn, opts \\ []), do: command(conn, :list_databases, [], opts)
+
+  @doc """
+  Returns storage statistics of the attached databases, like
+  `PRAGMA database_size`, but with sizes in bytes.
+
+  Each database is a map with `:name`, `:block_size`, `:total_blocks`,
+  `:used_blocks`, `:free_blocks`, `:size` of the database file, `:wal_size`
+  (`nil` when there is no write-ahead log, e.g. for in-memory databases) and
+  `:memory_usage` of the whole database instance.
+  """
+  @spec database_size(DBConnection.conn(), keyword()) ::
+          {:ok,
+           [
+             %{
+               name: String.t(),
+               block_size: non_neg_integer(),
+               total_blocks: non_neg_integer(),
+               used_blocks: non_neg_integer(),
+               free_blocks: non_neg_integer(),
+               size: non_neg_integer(),
+               wal_size: non_neg_integer() | nil,
+               memory_usage: non_neg_integer()
+             }
+           ]}
+          | {:error, Error.t()}
+  def database_size(conn, opts \\ []), do: command(conn, :database_size, [], opts)
+
+  @doc """
+  Creates (or replaces) S3 secret `name` used by `httpfs` extension.
+
+  Credentials are passed to DuckDB as bind parameters, so they never end up in
+  SQL text, logs or error messages.
+
+  ## Options
+
+  - `:key_id` - access key ID
+  - `:secret` - secret access key
+  - `:session_token` - session token for temporary credentials
+  - `:region` - bucket region
+  - `:endpoint` - custom endpoint, e.g. for MinIO or R2
+  - `:url_style` - `:vhost` or `:path`
+  - `:use_ssl` - whether to use HTTPS
+  - `:scope` - path prefix the secret applies to
+  - `:persistent` - store the secret on disk
+
+  Rest of the options are passed to `DBConnection`.
+  """
+  @spec create_s3_secret(DBConnection.conn(), atom() | String.t(), keyword()) ::
+          :ok | {:error, Error.t()}
+  def create_s3_secret(conn, name, opts \\ []) do
+    {secret_opts, opts} =
+      Keyword.split(opts, [
+        :key_id,
+        :secret,
+        :session_token,
+        :region,
+        :endpoint,
+        :url_style,
+        :use_ssl,
+        :scope,
+        :persistent
+      ])
+
+    with {:ok, _} <- command(conn, :create_s3_secret, [to_string(name), secret_opts], opts),
+         do: :ok
+  end
+
+  @doc """
+  Creates (or replaces) Azure Blob Storage secret `name` used by `azure`
+  extension.
+
+  As with `create_s3_secret/3` credentials are passed as bind parameters.
+
+  ## Options
+
+  - `:connection_string` - storage account connection string
+  - `:account_name` - storage account name, for service principal and
+    credential chain authentication
+  - `:tenant_id`, `:client_id` and `:client_secret` or
+    `:client_certificate_path` - service principal credentials
+  - `:provider` - `:config` (default), `:service_principal` (default when
+    `:tenant_id` is given) or `:credential_chain`
+  - `:chain` - credential sources tried with `:credential_chain` provider,
+    e.g. `"cli;managed_identity"`
+  - `:scope` - path prefix the secret applies to
+  - `:persistent` - store the secret on disk
+
+  Rest of the options are passed to `DBConnection`.
+  """
+  @spec create_azure_secret(DBConnection.conn(), atom() | String.t(), keyword()) ::
+          :ok | {:error, Error.t()}
+  def create_azure_secret(conn, name, opts \\ []) do
+    {secret_opts, opts} =
+      Keyword.split(opts, [
+        :connection_string,
+        :account_name,
+        :tenant_id,
+        :client_id,
+        :client_secret,
+        :client_certificate_path,
+        :provider,
+        :chain,
+        :scope,
+        :persistent
+      ])
+
+    with {:ok, _} <- command(conn, :create_azure_secret, [to_string(name), secret_opts], opts),
+         do: :ok
+  end
+
+  @doc """
+  Creates (or replaces) Google Cloud Storage secret `name` used by `httpfs`
+  extension.
+
+  As with `create_s3_secret/3` credentials are passed as bind parameters.
+
+  ## Options
+
+  - `:key_id` - HMAC key ID
+  - `:secret` - HMAC secret
+  - `:endpoint` - custom endpoint
+  - `:provider` - `:config` (default) for HMAC keys or `:credential_chain` to
+    use service account from the environment, e.g. from
+    `GOOGLE_APPLICATION_CREDENTIALS` file
+  - `:scope` - path prefix the secret applies to
+  - `:persistent` - store the secret on disk
+
+  Rest of the options are passed to `DBConnection`.
+  """
+  @spec create_gcs_secret(DBConnection.conn(), atom() | String.t(), keyword()) ::
+          :ok | {:error, Error.t()}
+  def create_gcs_secret(conn, name, opts \\ []) do
+    {secret_opts, opts} =
+      Keyword.split(opts, [:key_id, :secret, :endpoint, :provider, :scope, :persistent])
+
+    with {:ok, _} <- command(conn, :create_gcs_secret, [to_string(name), secret_opts], opts),
+         do: :ok
   end
 
   def create_secret(conn, name, spec, opts \\ []) do
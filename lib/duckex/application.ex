@@ -0,0 +1,15 @@
+# SPDX-FileCopyrightText: 2025 Stas Muzhyk <sts@abc3.dev>
+# SPDX-FileCopyrightText: 2025 Łukasz Niemier <~@hauleth.dev>
+#
+# SPDX-License-Identifier: Apache-2.0
+
+defmodule Duckex.Application do
+  @moduledoc false
+
+  use Application
+
+  @impl true
+  def start(_type, _args) do
+    Supervisor.start_link([Duckex.Shutdown], strategy: :one_for_one, name: __MODULE__)
+  end
+end
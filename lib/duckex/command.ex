@@ -0,0 +1,33 @@
+# SPDX-FileCopyrightText: 2025 Stas Muzhyk <sts@abc3.dev>
+# SPDX-FileCopyrightText: 2025 Łukasz Niemier <~@hauleth.dev>
+#
+# SPDX-License-Identifier: Apache-2.0
+
+defmodule Duckex.Command do
+  @moduledoc false
+
+  # Query-like struct used to call `Duckex.Native` functions that live outside
+  # of the prepare/execute cycle (like `execute_batch/2`) through DBConnection.
+  # `name` is the NIF function name, `args` are passed after the resource.
+
+  @type t :: %__MODULE__{
+          name: atom(),
+          args: list()
+        }
+
+  defstruct [:name, args: []]
+
+  defimpl DBConnection.Query do
+    def decode(_command, result, _opts), do: result
+
+    def describe(command, _opts), do: command
+
+    def encode(_command, params, _opts), do: params
+
+    def parse(command, _opts), do: command
+  end
+
+  defimpl String.Chars do
+    def to_string(%@for{} = command), do: Kernel.to_string(command.name)
+  end
+end
@@ -4,10 +4,51 @@
 # SPDX-License-Identifier: Apache-2.0
 
 defmodule Duckex.Error do
+  @moduledoc """
+  Error returned when DuckDB or Duckex operation fails. Its fields are:
+
+  - `kind` - atom classifying the error, e.g. `:syntax`, `:catalog`,
+    `:constraint`, `:conversion`, `:io`, `:busy`, `:closed`, `:read_only`,
+    `:invalid_date`, `:result_too_large`, `:motherduck`, `:unauthorized`,
+    `:invalid_enum`, `:timeout` or `:interrupted`, `:internal` for DuckDB
+    internal errors and panics of the native code, `:unknown` when error could
+    not be classified
+  - `message` - error message as reported by DuckDB
+  - `code` - SQLSTATE-like code corresponding to `kind` (following PostgreSQL
+    where possible), `nil` when there is no sensible one
+  - `constraint` - for constraint violations map with `:type` (`:not_null`,
+    `:unique`, `:primary_key`, `:check` or `:foreign_key`), `:table` and
+    `:column` (comma separated for composite keys), when DuckDB reports them;
+    `nil` otherwise
+  - `arity` - with `kind: :arity`, when wrong number of parameters was passed
+    to the query, map with `:expected` and `:got` parameter counts and `:sql`
+    of the query; `nil` otherwise
+  - `invalid_enum` - with `kind: :invalid_enum`, when value bound to `ENUM`
+    parameter is not one of its members, map with the `:parameter` position
+    (starting from 1), the bound `:value` and `:allowed` members; `nil`
+    otherwise
+  - `query` - command that caused the error
+  """
+
+  @type constraint :: %{
+          type: :not_null | :unique | :primary_key | :check | :foreign_key,
+          table: String.t() | nil,
+          column: String.t() | nil
+        }
+
+  @type arity :: %{expected: non_neg_integer(), got: non_neg_integer(), sql: String.t()}
+
+  @type invalid_enum :: %{parameter: pos_integer(), value: String.t(), allowed: [String.t()]}
+
   @type t :: %__MODULE__{
+          kind: atom() | nil,
           message: String.t(),
-          query: map()
+          code: String.t() | nil,
+          constraint: constraint() | nil,
+          arity: arity() | nil,
+          invalid_enum: invalid_enum() | nil,
+          query: map() | nil
         }
 
-  defexception [:message, :query]
+  defexception [:kind, :message, :code, :constraint, :arity, :invalid_enum, :query]
 end
@@ -8,12 +8,92 @@ defmodule Duckex.Native do
   use Rustler, otp_app: :duckex, crate: "duckex"
 
   # When your NIF is loaded, it will override these functions.
-  def new(_database_path, _cache_size \\ nil), do: :erlang.nif_error(:nif_not_loaded)
+  def new(_database_path, _cache_size \\ nil, _opts \\ []),
+    do: :erlang.nif_error(:nif_not_loaded)
+  def version, do: :erlang.nif_error(:nif_not_loaded)
+  def close_all, do: :erlang.nif_error(:nif_not_loaded)
+  def quote_identifier(_name), do: :erlang.nif_error(:nif_not_loaded)
+  def escape_literal(_value), do: :erlang.nif_error(:nif_not_loaded)
+  def split_statements(_sql), do: :erlang.nif_error(:nif_not_loaded)
+  def validate(_sql, _opts), do: :erlang.nif_error(:nif_not_loaded)
   def prepare(_resource, _query), do: :erlang.nif_error(:nif_not_loaded)
-  def execute(_resource, _stmt_id, _params), do: :erlang.nif_error(:nif_not_loaded)
-  def close(_resource, _stmt_id), do: :erlang.nif_error(:nif_not_loaded)
-  def begin(_resource), do: :erlang.nif_error(:nif_not_loaded)
+  def execute(_resource, _stmt, _params, _opts \\ []),
+    do: :erlang.nif_error(:nif_not_loaded)
+  def set_log_handler(_resource, _handler), do: :erlang.nif_error(:nif_not_loaded)
+  def execute_many(_resource, _stmt, _params), do: :erlang.nif_error(:nif_not_loaded)
+  def read(_resource, _query, _params, _opts), do: :erlang.nif_error(:nif_not_loaded)
+
+  def execute_async(_resource, _stmt, _params, _opts),
+    do: :erlang.nif_error(:nif_not_loaded)
+  def result_fetch(_result, _count), do: :erlang.nif_error(:nif_not_loaded)
+  def result_close(_result), do: :erlang.nif_error(:nif_not_loaded)
+  def query_arrow(_resource, _query, _params), do: :erlang.nif_error(:nif_not_loaded)
+  def query_arrow_stream(_resource, _query, _params), do: :erlang.nif_error(:nif_not_loaded)
+  def query_tensors(_resource, _query, _params), do: :erlang.nif_error(:nif_not_loaded)
+  def arrow_stream_address(_stream), do: :erlang.nif_error(:nif_not_loaded)
+  def appender_open(_resource, _table, _opts), do: :erlang.nif_error(:nif_not_loaded)
+  def appender_append_chunk(_appender, _rows), do: :erlang.nif_error(:nif_not_loaded)
+  def appender_close(_appender), do: :erlang.nif_error(:nif_not_loaded)
+  def ingest_open(_resource, _table, _format, _opts), do: :erlang.nif_error(:nif_not_loaded)
+  def ingest_write(_ingest, _data), do: :erlang.nif_error(:nif_not_loaded)
+  def ingest_finish(_ingest), do: :erlang.nif_error(:nif_not_loaded)
+  def blob_open(_resource, _query, _params), do: :erlang.nif_error(:nif_not_loaded)
+  def blob_read(_blob, _offset, _length), do: :erlang.nif_error(:nif_not_loaded)
+  def register_rows(_resource, _name, _columns, _rows), do: :erlang.nif_error(:nif_not_loaded)
+  def execute_to_file(_resource, _query, _params, _path, _format, _opts),
+    do: :erlang.nif_error(:nif_not_loaded)
+
+  def copy_to(_resource, _query, _path, _format, _opts), do: :erlang.nif_error(:nif_not_loaded)
+  def export_parquet(_resource, _query, _opts), do: :erlang.nif_error(:nif_not_loaded)
+  def load_csv_binary(_resource, _table, _data, _opts), do: :erlang.nif_error(:nif_not_loaded)
+  def load_ndjson_binary(_resource, _table, _data, _opts),
+    do: :erlang.nif_error(:nif_not_loaded)
+  def sniff_csv(_resource, _path), do: :erlang.nif_error(:nif_not_loaded)
+  def export_database(_resource, _dir, _format, _opts), do: :erlang.nif_error(:nif_not_loaded)
+  def import_database(_resource, _dir), do: :erlang.nif_error(:nif_not_loaded)
+  def create_macro(_resource, _name, _params, _body, _opts),
+    do: :erlang.nif_error(:nif_not_loaded)
+
+  def drop_macro(_resource, _name), do: :erlang.nif_error(:nif_not_loaded)
+  def create_fts_index(_resource, _table, _id, _columns, _opts),
+    do: :erlang.nif_error(:nif_not_loaded)
+
+  def drop_fts_index(_resource, _table), do: :erlang.nif_error(:nif_not_loaded)
+  def install_extension(_resource, _name, _opts), do: :erlang.nif_error(:nif_not_loaded)
+  def load_extension(_resource, _name), do: :erlang.nif_error(:nif_not_loaded)
+  def create_s3_secret(_resource, _name, _opts), do: :erlang.nif_error(:nif_not_loaded)
+  def create_azure_secret(_resource, _name, _opts), do: :erlang.nif_error(:nif_not_loaded)
+  def create_gcs_secret(_resource, _name, _opts), do: :erlang.nif_error(:nif_not_loaded)
+  def set_setting(_resource, _name, _value), do: :erlang.nif_error(:nif_not_loaded)
+  def get_setting(_resource, _name), do: :erlang.nif_error(:nif_not_loaded)
+  def set_timezone(_resource, _timezone), do: :erlang.nif_error(:nif_not_loaded)
+  def set_http_options(_resource, _opts), do: :erlang.nif_error(:nif_not_loaded)
+  def enable_profiling(_resource, _enabled), do: :erlang.nif_error(:nif_not_loaded)
+  def last_profile(_resource), do: :erlang.nif_error(:nif_not_loaded)
+  def explain(_resource, _stmt, _opts), do: :erlang.nif_error(:nif_not_loaded)
+  def describe(_resource, _query), do: :erlang.nif_error(:nif_not_loaded)
+  def statement_params(_resource, _stmt), do: :erlang.nif_error(:nif_not_loaded)
+  def cache_stats(_resource), do: :erlang.nif_error(:nif_not_loaded)
+  def temp_usage(_resource), do: :erlang.nif_error(:nif_not_loaded)
+  def memory_stats(_resource), do: :erlang.nif_error(:nif_not_loaded)
+  def cache_clear(_resource), do: :erlang.nif_error(:nif_not_loaded)
+  def cache_resize(_resource, _capacity), do: :erlang.nif_error(:nif_not_loaded)
+  def progress(_resource), do: :erlang.nif_error(:nif_not_loaded)
+  def close(_resource, _stmt), do: :erlang.nif_error(:nif_not_loaded)
+  def begin(_resource, _opts \\ []), do: :erlang.nif_error(:nif_not_loaded)
   def commit(_resource), do: :erlang.nif_error(:nif_not_loaded)
   def rollback(_resource), do: :erlang.nif_error(:nif_not_loaded)
+  def transaction_status(_resource), do: :erlang.nif_error(:nif_not_loaded)
+  def attach(_resource, _path, _opts), do: :erlang.nif_error(:nif_not_loaded)
+  def detach(_resource, _name), do: :erlang.nif_error(:nif_not_loaded)
+  def attach_iceberg(_resource, _warehouse, _opts, _credentials),
+    do: :erlang.nif_error(:nif_not_loaded)
+
+  def attach_delta(_resource, _path, _opts), do: :erlang.nif_error(:nif_not_loaded)
+  def list_databases(_resource), do: :erlang.nif_error(:nif_not_loaded)
+  def database_size(_resource), do: :erlang.nif_error(:nif_not_loaded)
+  def checkpoint(_resource, _force), do: :erlang.nif_error(:nif_not_loaded)
+  def disconnect(_resource), do: :erlang.nif_error(:nif_not_loaded)
   def status(_resource), do: :erlang.nif_error(:nif_not_loaded)
+  def execute_batch(_resource, _sql), do: :erlang.nif_error(:nif_not_loaded)
 end
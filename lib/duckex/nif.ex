@@ -14,6 +14,7 @@ defmodule Duckex.NIF do
   alias Duckex.Result
 
   @default_timeout :timer.seconds(15)
+  @default_chunk_size 10_000
 
   ## ------------------------------------------------------------------
   ## API Function Definitions
@@ -29,6 +30,29 @@ defmodule Duckex.NIF do
 
   def stop(pid, timeout \\ :timer.seconds(25)), do: GenServer.stop(pid, timeout)
 
+  # Runs in the calling process, not the connection one, see `Duckex.read/4`
+  def read(resource, query, params, opts) do
+    chunk_size = opts[:chunk_size] || @default_chunk_size
+
+    nif_opts = [
+      timeout: opts[:query_timeout],
+      layout: opts[:layout],
+      max_rows: opts[:max_rows],
+      max_result_bytes: opts[:max_result_bytes],
+      geometry: opts[:geometry],
+      blob: opts[:blob],
+      format: opts[:format],
+      delimiter: opts[:delimiter],
+      quote: opts[:quote],
+      column_info: opts[:column_info],
+      chunk_size: chunk_size
+    ]
+
+    resource
+    |> Duckex.Native.read(query, params, nif_opts)
+    |> fetch_rest(chunk_size)
+  end
+
   ## ------------------------------------------------------------------
   ## gen_server Function Definitions
   ## ------------------------------------------------------------------
@@ -49,14 +73,35 @@ defmodule Duckex.NIF do
     # Get cache size option (default 1024 is handled in Rust)
     cache_size = Keyword.get(opts, :cache_size)
 
+    native_opts =
+      Keyword.take(opts, [
+        :lock_timeout,
+        :strict_floats,
+        :strict_text,
+        :max_rows,
+        :max_result_bytes,
+        :temp_directory,
+        :max_temp_directory_size,
+        :readers,
+        :motherduck_token,
+        :allow_unsigned_extensions,
+        :custom_extension_repository,
+        :autoinstall_known_extensions,
+        :autoload_known_extensions,
+        :icu,
+        :timezone,
+        :http,
+        :after_connect
+      ])
+
     # Create the DuckDB connection via NIF
-    case Duckex.Native.new(database, cache_size) do
+    case Duckex.Native.new(database, cache_size, native_opts) do
       {:ok, resource} ->
         Logger.debug("Started Duckex NIF with database: #{database}")
         {:ok, %{resource: resource}}
 
       {:error, reason} ->
-        Logger.error("Failed to start Duckex NIF: #{reason}")
+        Logger.error("Failed to start Duckex NIF: #{Exception.message(reason)}")
         {:stop, {:error, reason}}
     end
   end
@@ -71,28 +116,31 @@ defmodule Duckex.NIF do
           Logger.debug("duckex <- #{inspect(result)}")
           {:ok, result}
 
-        {:error, message} ->
-          Logger.debug("duckex <- error: #{message}")
-          {:error, %Error{message: message, query: %{command: "prepare", query: query}}}
+        {:error, reason} ->
+          Logger.debug("duckex <- error: #{inspect(reason)}")
+          {:error, to_error(reason, %{command: "prepare", query: query})}
       end
 
     {:reply, result, state}
   end
 
-  def handle_call({:command, %{command: "execute", stmt: stmt_id, params: params}}, _from, state) do
+  def handle_call(
+        {:command, %{command: "execute", stmt: stmt_id, params: params} = command},
+        _from,
+        state
+      ) do
     Logger.debug("duckex -> execute: #{inspect({stmt_id, params})}")
 
     result =
-      case Duckex.Native.execute(state.resource, stmt_id, params) do
+      case execute(state.resource, stmt_id, params, command) do
         {:ok, %Result{} = result} ->
           Logger.debug("duckex <- #{inspect(result)}")
           {:ok, result}
 
-        {:error, message} ->
-          Logger.debug("duckex <- error: #{message}")
+        {:error, reason} ->
+          Logger.debug("duckex <- error: #{inspect(reason)}")
 
-          {:error,
-           %Error{message: message, query: %{command: "execute", stmt: stmt_id, params: params}}}
+          {:error, to_error(reason, %{command: "execute", stmt: stmt_id, params: params})}
       end
 
     {:reply, result, state}
@@ -107,26 +155,26 @@ defmodule Duckex.NIF do
           Logger.debug("duckex <- ok")
           {:ok, %Result{columns: [], rows: [], num_rows: 0}}
 
-        {:error, message} ->
-          Logger.debug("duckex <- error: #{message}")
-          {:error, %Error{message: message, query: %{command: "close", stmt: stmt_id}}}
+        {:error, reason} ->
+          Logger.debug("duckex <- error: #{inspect(reason)}")
+          {:error, to_error(reason, %{command: "close", stmt: stmt_id})}
       end
 
     {:reply, result, state}
   end
 
-  def handle_call({:command, %{command: "begin"}}, _from, state) do
+  def handle_call({:command, %{command: "begin"} = command}, _from, state) do
     Logger.debug("duckex -> begin")
 
     result =
-      case Duckex.Native.begin(state.resource) do
+      case Duckex.Native.begin(state.resource, read_only: command[:read_only] || false) do
         {:ok, _} ->
           Logger.debug("duckex <- ok")
           {:ok, %Result{columns: [], rows: [], num_rows: 0}}
 
-        {:error, message} ->
-          Logger.debug("duckex <- error: #{message}")
-          {:error, %Error{message: message, query: %{command: "begin"}}}
+        {:error, reason} ->
+          Logger.debug("duckex <- error: #{inspect(reason)}")
+          {:error, to_error(reason, %{command: "begin"})}
       end
 
     {:reply, result, state}
@@ -141,9 +189,9 @@ defmodule Duckex.NIF do
           Logger.debug("duckex <- ok")
           {:ok, %Result{columns: [], rows: [], num_rows: 0}}
 
-        {:error, message} ->
-          Logger.debug("duckex <- error: #{message}")
-          {:error, %Error{message: message, query: %{command: "commit"}}}
+        {:error, reason} ->
+          Logger.debug("duckex <- error: #{inspect(reason)}")
+          {:error, to_error(reason, %{command: "commit"})}
       end
 
     {:reply, result, state}
@@ -158,9 +206,9 @@ defmodule Duckex.NIF do
           Logger.debug("duckex <- ok")
           {:ok, %Result{columns: [], rows: [], num_rows: 0}}
 
-        {:error, message} ->
-          Logger.debug("duckex <- error: #{message}")
-          {:error, %Error{message: message, query: %{command: "rollback"}}}
+        {:error, reason} ->
+          Logger.debug("duckex <- error: #{inspect(reason)}")
+          {:error, to_error(reason, %{command: "rollback"})}
       end
 
     {:reply, result, state}
@@ -175,9 +223,32 @@ defmodule Duckex.NIF do
           Logger.debug("duckex <- ok")
           {:ok, %Result{columns: [], rows: [], num_rows: 0}}
 
-        {:error, message} ->
-          Logger.debug("duckex <- error: #{message}")
-          {:error, %Error{message: message, query: %{command: "status"}}}
+        {:error, reason} ->
+          Logger.debug("duckex <- error: #{inspect(reason)}")
+          {:error, to_error(reason, %{command: "status"})}
+      end
+
+    {:reply, result, state}
+  end
+
+  # Not an actual NIF, hands out the connection resource itself so it can be
+  # used directly, without going through this process
+  def handle_call({:command, %{command: "call", name: :resource, args: []}}, _from, state) do
+    {:reply, {:ok, state.resource}, state}
+  end
+
+  def handle_call({:command, %{command: "call", name: name, args: args}}, _from, state) do
+    Logger.debug("duckex -> #{name}")
+
+    result =
+      case apply(Duckex.Native, name, [state.resource | args]) do
+        {:ok, value} ->
+          Logger.debug("duckex <- ok")
+          {:ok, value}
+
+        {:error, reason} ->
+          Logger.debug("duckex <- error: #{inspect(reason)}")
+          {:error, to_error(reason, %{command: "call", name: name})}
       end
 
     {:reply, result, state}
@@ -187,4 +258,58 @@ defmodule Duckex.NIF do
     Logger.warning("Unsupported command: #{inspect(command)}")
     {:reply, {:error, %Error{message: "Unsupported command", query: command}}, state}
   end
+
+  ## ------------------------------------------------------------------
+  ## Internal Function Definitions
+  ## ------------------------------------------------------------------
+
+  # Large results are encoded in chunks, each by separate NIF call, so no
+  # single call blocks the scheduler for too long
+  defp execute(resource, stmt_id, params, command) do
+    chunk_size = command[:chunk_size] || @default_chunk_size
+    lazy = command[:lazy] || false
+
+    opts = [
+      timeout: command[:timeout],
+      layout: command[:layout],
+      max_rows: command[:max_rows],
+      max_result_bytes: command[:max_result_bytes],
+      geometry: command[:geometry],
+      blob: command[:blob],
+      format: command[:format],
+      delimiter: command[:delimiter],
+      quote: command[:quote],
+      column_info: command[:column_info],
+      # Lazy results keep all rows in Rust, to be fetched on demand
+      chunk_size: if(lazy, do: 0, else: chunk_size)
+    ]
+
+    case Duckex.Native.execute(resource, stmt_id, params, opts) do
+      result when lazy -> result
+      result -> fetch_rest(result, chunk_size)
+    end
+  end
+
+  defp fetch_rest({:ok, %Result{handle: rest} = result}, chunk_size) when rest != nil do
+    with {:ok, chunks} <- fetch_chunks(rest, chunk_size, [result.rows]) do
+      {:ok, %{result | rows: concat_chunks(chunks, result.layout), handle: nil}}
+    end
+  end
+
+  defp fetch_rest(other, _chunk_size), do: other
+
+  defp fetch_chunks(rest, chunk_size, acc) do
+    case Duckex.Native.result_fetch(rest, chunk_size) do
+      {:ok, {rows, true}} -> {:ok, Enum.reverse([rows | acc])}
+      {:ok, {rows, false}} -> fetch_chunks(rest, chunk_size, [rows | acc])
+      {:error, _} = error -> error
+    end
+  end
+
+  defp concat_chunks(chunks, :columnar), do: Enum.zip_with(chunks, &Enum.concat/1)
+  defp concat_chunks(chunks, _layout), do: Enum.concat(chunks)
+
+  # Errors coming from the NIF are already `Duckex.Error` structs, only the
+  # command that caused them is missing
+  defp to_error(%Error{} = error, query), do: %{error | query: query}
 end
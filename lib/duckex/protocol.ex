@@ -8,6 +8,8 @@ defmodule Duckex.Protocol do
 
   use DBConnection
 
+  alias Duckex.Command
+  alias Duckex.Error
   alias Duckex.NIF
   alias Duckex.Result
 
@@ -26,6 +28,10 @@ defmodule Duckex.Protocol do
     secrets = opts[:secrets] || []
     attach = opts[:attach] || []
     configs = opts[:configs] || []
+    extensions = opts[:extensions] || []
+
+    # Load extensions first, secrets and attached databases may need them
+    Enum.each(extensions, &load_extension_direct!(port, &1, opts))
 
     # Create secrets first (they might be needed for attaching)
     Enum.each(secrets, fn
@@ -63,6 +69,19 @@ defmodule Duckex.Protocol do
     {:ok, state}
   end
 
+  # Installing is no-op when the extension is already installed, so it does not
+  # need the network then
+  defp load_extension_direct!(nif, name, nif_opts) do
+    name = to_string(name)
+
+    for {function, args} <- [install_extension: [name, []], load_extension: [name]] do
+      case NIF.command(nif, %{command: "call", name: function, args: args}, nif_opts) do
+        {:ok, _} -> :ok
+        {:error, error} -> raise error
+      end
+    end
+  end
+
   # Execute CREATE SECRET command directly via NIF
   defp create_secret_direct!(nif, name, spec, secret_opts, nif_opts) do
     {spec_sql, params} = format_secret_options(spec)
@@ -180,7 +199,7 @@ defmodule Duckex.Protocol do
 
   @impl true
   def handle_begin(opts, %{} = state) do
-    case NIF.command(state.port, %{command: "begin"}, opts) do
+    case NIF.command(state.port, %{command: "begin", read_only: opts[:read_only]}, opts) do
       {:ok, resp} ->
         {:ok, resp, state}
 
@@ -195,6 +214,10 @@ defmodule Duckex.Protocol do
       {:ok, resp} ->
         {:ok, resp, state}
 
+      # Connection is unusable after a panic or once closed, let DBConnection reconnect
+      {:error, %Error{kind: kind} = err} when kind in [:poisoned, :closed] ->
+        {:disconnect, err, state}
+
       {:error, err} ->
         {:error, err, state}
     end
@@ -231,6 +254,24 @@ defmodule Duckex.Protocol do
   end
 
   @impl true
+  def handle_execute(%Command{} = command, _params, opts, %{} = state) do
+    case NIF.command(
+           state.port,
+           %{command: "call", name: command.name, args: command.args},
+           opts
+         ) do
+      {:ok, resp} ->
+        {:ok, command, resp, state}
+
+      # Connection is unusable after a panic or once closed, let DBConnection reconnect
+      {:error, %Error{kind: kind} = err} when kind in [:poisoned, :closed] ->
+        {:disconnect, err, state}
+
+      {:error, err} ->
+        {:error, err, state}
+    end
+  end
+
   def handle_execute(query, params, opts, %{} = state) do
     require Logger
     Logger.debug("Executing query with stmt: #{inspect(query.stmt)}, params: #{inspect(params)}")
@@ -240,7 +281,18 @@ defmodule Duckex.Protocol do
            %{
              command: "execute",
              stmt: query.stmt,
-             params: params
+             params: params,
+             timeout: opts[:query_timeout],
+             layout: opts[:layout],
+             chunk_size: opts[:chunk_size],
+             max_rows: opts[:max_rows],
+             max_result_bytes: opts[:max_result_bytes],
+             geometry: opts[:geometry],
+             blob: opts[:blob],
+             format: opts[:format],
+             delimiter: opts[:delimiter],
+             quote: opts[:quote],
+             lazy: opts[:lazy] || false
            },
            opts
          ) do
@@ -248,6 +300,10 @@ defmodule Duckex.Protocol do
         Logger.debug("Execute successful")
         {:ok, query, resp, state}
 
+      # Connection is unusable after a panic or once closed, let DBConnection reconnect
+      {:error, %Error{kind: kind} = err} when kind in [:poisoned, :closed] ->
+        {:disconnect, err, state}
+
       {:error, err} ->
         Logger.error("Execute error: #{inspect(err)}")
         {:error, err, state}
@@ -294,13 +350,17 @@ defmodule Duckex.Protocol do
 
     case NIF.command(state.port, %{command: "prepare", query: fixed_query}, opts) do
       {:ok, %Result{rows: [[stmt_id]]}} when not is_nil(stmt_id) ->
-        Logger.debug("Query prepared with stmt_id: #{stmt_id}")
+        Logger.debug("Query prepared with stmt: #{inspect(stmt_id)}")
         {:ok, %{query | stmt: stmt_id}, state}
 
       {:ok, %Result{rows: [[nil]]}} ->
         Logger.error("Exhausted prepared statements cache")
         {:error, %Duckex.Error{message: "Exhausted prepared statements cache"}, state}
 
+      # Connection is unusable after a panic or once closed, let DBConnection reconnect
+      {:error, %Error{kind: kind} = err} when kind in [:poisoned, :closed] ->
+        {:disconnect, err, state}
+
       {:error, err} ->
         Logger.error("Prepare error: #{inspect(err)}")
         {:error, err, state}
@@ -350,13 +410,11 @@ defmodule Duckex.Protocol do
   def handle_status(opts, %{} = state) do
     case NIF.command(
            state.port,
-           %{
-             command: "status"
-           },
+           %{command: "call", name: :transaction_status, args: []},
            opts
          ) do
-      {:ok, _} ->
-        {:idle, state}
+      {:ok, status} ->
+        {status, state}
 
       {:error, _} ->
         {:error, state}
@@ -364,5 +422,16 @@ defmodule Duckex.Protocol do
   end
 
   @impl true
-  def ping(state), do: {:ok, state}
+  def ping(state) do
+    case NIF.command(state.port, %{command: "call", name: :status, args: []}, []) do
+      {:ok, %{state: :broken, error: message}} ->
+        {:disconnect, %Error{kind: :internal, message: "Connection is broken: #{message}"}, state}
+
+      {:ok, _} ->
+        {:ok, state}
+
+      {:error, err} ->
+        {:disconnect, err, state}
+    end
+  end
 end
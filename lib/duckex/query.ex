@@ -10,7 +10,7 @@ defmodule Duckex.Query do
 
   @type t :: %__MODULE__{
           query: String.t(),
-          stmt: String.t(),
+          stmt: reference() | nil,
           columns: list(),
           rows: list()
         }
@@ -18,10 +18,21 @@ defmodule Duckex.Query do
   defstruct [:query, :stmt, :columns, :rows]
 
   defimpl DBConnection.Query do
-    def decode(_query, %Duckex.Result{} = result, _opts) do
+    # Serialized with `:format` option, returned as is
+    def decode(_query, %Duckex.Result{rows: rows} = result, _opts) when is_binary(rows),
+      do: result
+
+    def decode(_query, %Duckex.Result{layout: :columnar} = result, opts) do
+      columns =
+        Enum.zip_with(result.rows, result.columns, &Duckex.Result.decode_column(&1, &2, opts))
+
+      %{result | rows: columns}
+    end
+
+    def decode(_query, %Duckex.Result{} = result, opts) do
       rows =
         for row <- result.rows do
-          Duckex.Result.decode_row(row, result.columns)
+          Duckex.Result.decode_row(row, result.columns, opts)
         end
 
       %{result | rows: rows}
@@ -8,31 +8,91 @@ defmodule Duckex.Result do
   Result struct returned from any successful query. Its fields are:
 
   - `columns` - list of field names in form of `[name, type]`
+  - `types` - structured types of the columns, in the same order, like
+    `:integer`, `{:decimal, 18, 3}`, `{:list, :varchar}`,
+    `{:struct, [{"a", :integer}]}` or `{:timestamp, :microsecond}`. Types
+    without structured form are given as strings.
   - `rows` - list of rows, each row is represented as list of fields that
-    corresponds to `:column` order
-  - `num_rows` - count of rows in `:rows` field
+    corresponds to `:column` order. With `:columnar` layout it is list of
+    columns instead, each being list of values of that column. With `:format`
+    option it is a binary with the serialized rows instead. `UNION` values,
+    also nested ones, are `{tag, value}` tuples, `tag` being name of the
+    member as string (in JSON output objects with the tag as the only key).
+    `STRUCT` values are maps with string keys, `VARINT` values integers.
+  - `num_rows` - count of rows in the result
+  - `kind` - kind of the statement, one of `:select`, `:insert`, `:update`,
+    `:delete`, `:ddl`, `:transaction` or `:other`
+  - `rows_affected` - number of rows changed by `:insert`, `:update` or
+    `:delete` statement, `nil` for other kinds. With `RETURNING` clause `rows`
+    hold the changed rows, otherwise single row with their count
+  - `queue_time_us` - microseconds the query waited for the connection
+  - `execute_time_us` - microseconds the query ran, including encoding of
+    the rows returned with the result
+  - `layout` - `:rows` or `:columnar`, see `Duckex.query/4`
+  - `handle` - reference to rows not fetched yet, when the query was run with
+    `lazy: true`, see `Duckex.fetch/2`
+  - `column_info` - nullability and origin of the columns, in the same order,
+    when the query was run with `column_info: true`. Each is a map with
+    `:nullable`, `:table` and `:column` keys, see `Duckex.describe/3`
   """
 
   @type t :: %__MODULE__{
           columns: [[String.t()]],
-          rows: [[any()]],
-          num_rows: integer
+          types: [atom() | tuple() | String.t()],
+          rows: [[any()]] | binary(),
+          num_rows: integer,
+          kind: :select | :insert | :update | :delete | :ddl | :transaction | :other | nil,
+          rows_affected: non_neg_integer() | nil,
+          queue_time_us: non_neg_integer() | nil,
+          execute_time_us: non_neg_integer() | nil,
+          layout: :rows | :columnar,
+          handle: reference() | nil,
+          column_info:
+            [%{nullable: boolean() | nil, table: String.t() | nil, column: String.t() | nil}]
+            | nil
         }
 
-  defstruct [:columns, :rows, :num_rows]
+  defstruct [
+    :columns,
+    :rows,
+    :num_rows,
+    :handle,
+    :kind,
+    :rows_affected,
+    :queue_time_us,
+    :execute_time_us,
+    :column_info,
+    types: [],
+    layout: :rows
+  ]
 
   @doc false
-  def decode_row([], []), do: []
+  def decode_row(row, columns, opts \\ [])
 
-  def decode_row([value | vs], [[_name, type] | cs]) do
-    [decode_val(value, type) | decode_row(vs, cs)]
+  def decode_row([], [], _opts), do: []
+
+  def decode_row([value | vs], [[_name, type] | cs], opts) do
+    [decode_val(value, type, opts) | decode_row(vs, cs, opts)]
   end
 
-  defp decode_val(nil, _type), do: nil
+  @doc false
+  def decode_column(values, [_name, type], opts \\ []) do
+    Enum.map(values, &decode_val(&1, type, opts))
+  end
 
-  defp decode_val(us, "Timestamp(" <> _) when is_integer(us) do
-    DateTime.from_unix!(us, :microsecond)
+  defp decode_val(nil, _type, _opts), do: nil
+
+  defp decode_val(value, "Timestamp(" <> rest, opts) when is_integer(value) do
+    case timestamp_unit(rest) do
+      :nanosecond when opts[:timestamp_ns] == :integer -> value
+      unit -> DateTime.from_unix!(value, unit)
+    end
   end
 
-  defp decode_val(val, _), do: val
+  defp decode_val(val, _, _opts), do: val
+
+  defp timestamp_unit("Second" <> _), do: :second
+  defp timestamp_unit("Millisecond" <> _), do: :millisecond
+  defp timestamp_unit("Nanosecond" <> _), do: :nanosecond
+  defp timestamp_unit(_), do: :microsecond
 end
@@ -0,0 +1,34 @@
+# SPDX-FileCopyrightText: 2025 Stas Muzhyk <sts@abc3.dev>
+# SPDX-FileCopyrightText: 2025 Łukasz Niemier <~@hauleth.dev>
+#
+# SPDX-License-Identifier: Apache-2.0
+
+defmodule Duckex.Shutdown do
+  @moduledoc false
+
+  # Connection resources are not garbage collected when the VM stops, e.g. with
+  # `System.stop/0`, so connections still open by then would not checkpoint
+  # and the next start would have to replay their WAL. Applications are
+  # stopped in reverse order, so this runs after applications using Duckex
+  # closed their pools, and closes whatever is left.
+
+  # Checkpoints of large databases can take a while
+  use GenServer, shutdown: :timer.seconds(30)
+
+  def start_link(opts), do: GenServer.start_link(__MODULE__, opts, name: __MODULE__)
+
+  @impl true
+  def init(_opts) do
+    Process.flag(:trap_exit, true)
+
+    {:ok, nil}
+  end
+
+  @impl true
+  def terminate(_reason, _state) do
+    # There is nothing to close when no connection was ever opened
+    if :erlang.module_loaded(Duckex.Native), do: Duckex.Native.close_all()
+
+    :ok
+  end
+end
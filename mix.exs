@@ -20,6 +20,12 @@ defmodule Duckex.MixProject do
     ]
   end
 
+  def application do
+    [
+      mod: {Duckex.Application, []}
+    ]
+  end
+
   defp deps do
     [
       {:db_connection, "~> 2.8"},
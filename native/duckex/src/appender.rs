@@ -0,0 +1,142 @@
+// SPDX-FileCopyrightText: 2025 Stas Muzhyk <sts@abc3.dev>
+// SPDX-FileCopyrightText: 2025 Łukasz Niemier <~@hauleth.dev>
+//
+// SPDX-License-Identifier: Apache-2.0
+
+use std::sync::{Arc, Mutex};
+
+use duckdb::types::Value;
+use duckdb::{appender_params_from_iter, Appender, Connection};
+use rustler::ResourceArc;
+
+use crate::error::Error;
+use crate::options::Options;
+use crate::{lock, DuckDBResource};
+
+const DEFAULT_FLUSH_THRESHOLD: usize = 100_000;
+
+/// Appender kept open across NIF calls.
+///
+/// DuckDB appender borrows the connection, so it cannot outlive a single call.
+/// Instead rows are buffered here and written with short-lived appender on the
+/// connection worker thread once there are at least `flush_threshold` of them.
+pub struct AppenderResource {
+    db: ResourceArc<DuckDBResource>,
+    table: String,
+    schema: Option<String>,
+    flush_threshold: usize,
+    // Shared with flushes running on the worker thread
+    state: Arc<Mutex<State>>,
+}
+
+#[derive(Default)]
+struct State {
+    rows: Vec<Vec<Value>>,
+    // Number of rows already written to the table
+    appended: u64,
+    closed: bool,
+}
+
+impl AppenderResource {
+    pub(crate) fn new(
+        db: ResourceArc<DuckDBResource>,
+        table: String,
+        opts: &Options,
+    ) -> Result<Self, Error> {
+        let appender = AppenderResource {
+            db,
+            table,
+            schema: opts.get_string("schema")?,
+            flush_threshold: opts
+                .get::<usize>("flush_threshold")?
+                .unwrap_or(DEFAULT_FLUSH_THRESHOLD),
+            state: Arc::new(Mutex::new(State::default())),
+        };
+
+        // Fail early when the table does not exist
+        let (table, schema) = (appender.table.clone(), appender.schema.clone());
+        appender
+            .db
+            .with_conn(move |conn| open(conn, &table, schema.as_deref()).map(drop))?;
+
+        Ok(appender)
+    }
+
+    /// Buffer rows, flushing them when the threshold is reached. Returns number
+    /// of rows written to the table so far.
+    pub(crate) fn append(&self, rows: Vec<Vec<Value>>) -> Result<u64, Error> {
+        {
+            let mut state = lock::acquire(&self.state, None)?;
+
+            if state.closed {
+                return Err(Error::closed());
+            }
+
+            state.rows.extend(rows);
+
+            if state.rows.len() < self.flush_threshold {
+                return Ok(state.appended);
+            }
+        }
+
+        self.flush(false)
+    }
+
+    /// Flush remaining rows, appender cannot be used afterwards. Returns total
+    /// number of rows written to the table.
+    pub(crate) fn close(&self) -> Result<u64, Error> {
+        self.flush(true)
+    }
+
+    // Rows stay buffered until the whole batch is written. DuckDB does not
+    // write any of them when one fails, as appender with incomplete row cannot
+    // be flushed, nor when the flush itself fails.
+    //
+    // State is locked by the job, not while waiting for the worker, so the
+    // rows are kept when the job does not run at all.
+    fn flush(&self, close: bool) -> Result<u64, Error> {
+        let state = self.state.clone();
+        let (table, schema) = (self.table.clone(), self.schema.clone());
+
+        self.db.with_conn(move |conn| {
+            let mut state = lock::acquire(&state, None)?;
+
+            if state.closed {
+                return Ok(state.appended);
+            }
+
+            if !state.rows.is_empty() {
+                let mut appender = open(conn, &table, schema.as_deref())?;
+
+                for row in &state.rows {
+                    appender
+                        .append_row(appender_params_from_iter(row))
+                        .map_err(|e| format!("Failed to append row: {}", e))?;
+                }
+
+                appender
+                    .flush()
+                    .map_err(|e| format!("Failed to flush appender: {}", e))?;
+
+                state.appended += state.rows.len() as u64;
+                state.rows.clear();
+            }
+
+            state.closed = close;
+
+            Ok(state.appended)
+        })
+    }
+}
+
+fn open<'c>(
+    conn: &'c Connection,
+    table: &str,
+    schema: Option<&str>,
+) -> Result<Appender<'c>, Error> {
+    match schema {
+        Some(schema) => conn.appender_to_db(table, schema),
+        None => conn.appender(table),
+    }
+    .map_err(|e| format!("Failed to create appender for '{}': {}", table, e).into())
+}
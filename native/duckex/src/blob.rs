@@ -0,0 +1,110 @@
+// SPDX-FileCopyrightText: 2025 Stas Muzhyk <sts@abc3.dev>
+// SPDX-FileCopyrightText: 2025 Łukasz Niemier <~@hauleth.dev>
+//
+// SPDX-License-Identifier: Apache-2.0
+
+// Encoding of `BLOB` values, and large values read in chunks, e.g. files stored
+// as `BLOB` columns
+
+use base64::{engine::general_purpose, Engine as _};
+use duckdb::types::Value;
+use duckdb::{params_from_iter, Connection, OptionalExt};
+use rustler::{Binary, Encoder, Env, NewBinary, NifMap, NifUnitEnum, ResourceArc, Term};
+
+use crate::error::{Error, ErrorKind};
+use crate::params::Bound;
+
+/// How `BLOB` values of the result are returned
+#[derive(NifUnitEnum, Clone, Copy, PartialEq, Eq, Default)]
+pub(crate) enum Format {
+    #[default]
+    Base64,
+    // Binaries with the bytes as they are
+    Raw,
+    // Lowercase hexadecimal strings
+    Hex,
+}
+
+/// Encode blob directly into the binary, without intermediate `String`
+pub(crate) fn encode<'a>(env: Env<'a>, bytes: &[u8], format: Format) -> Term<'a> {
+    let len = match format {
+        Format::Base64 => {
+            base64::encoded_len(bytes.len(), true).expect("blob too large to encode")
+        }
+        Format::Raw => bytes.len(),
+        Format::Hex => bytes.len() * 2,
+    };
+    let mut binary = NewBinary::new(env, len);
+    let out = binary.as_mut_slice();
+
+    match format {
+        Format::Base64 => {
+            general_purpose::STANDARD
+                .encode_slice(bytes, out)
+                .expect("buffer sized with encoded_len");
+        }
+        Format::Raw => out.copy_from_slice(bytes),
+        Format::Hex => {
+            const DIGITS: &[u8; 16] = b"0123456789abcdef";
+
+            for (byte, pair) in bytes.iter().zip(out.chunks_exact_mut(2)) {
+                pair[0] = DIGITS[(byte >> 4) as usize];
+                pair[1] = DIGITS[(byte & 0x0f) as usize];
+            }
+        }
+    }
+
+    Binary::from(binary).encode(env)
+}
+
+/// Single large value kept in Rust memory, read by the BEAM in slices, so it
+/// never has to be allocated as one binary there
+pub struct BlobResource {
+    data: Vec<u8>,
+}
+
+#[derive(NifMap)]
+pub(crate) struct Opened {
+    handle: ResourceArc<BlobResource>,
+    size: u64,
+}
+
+/// Run query returning single `BLOB` (or text) value and keep the value
+pub(crate) fn open(conn: &Connection, query: &str, params: &Bound) -> Result<Opened, Error> {
+    let mut stmt = conn
+        .prepare(query)
+        .map_err(|e| format!("SQL preparation error: {}", e))?;
+    let params = params.resolve(&stmt, query)?;
+
+    let value = stmt
+        .query_row(params_from_iter(params.iter()), |row| row.get::<_, Value>(0))
+        .optional()
+        .map_err(|e| format!("SQL execution error: {}", e))?;
+
+    let data = match value {
+        Some(Value::Blob(data)) => data,
+        Some(Value::Text(text)) => text.into_bytes(),
+        Some(Value::Null) => return Err(invalid("Query returned NULL instead of BLOB")),
+        Some(_) => return Err(invalid("Query has to return BLOB or text value")),
+        None => return Err(invalid("Query returned no rows")),
+    };
+
+    Ok(Opened {
+        size: data.len() as u64,
+        handle: ResourceArc::new(BlobResource { data }),
+    })
+}
+
+impl BlobResource {
+    /// Up to `length` bytes starting at `offset`, empty past the end
+    pub(crate) fn read(&self, offset: u64, length: u64) -> &[u8] {
+        let start = offset.min(self.data.len() as u64) as usize;
+        let end = (start as u64).saturating_add(length).min(self.data.len() as u64) as usize;
+
+        &self.data[start..end]
+    }
+}
+
+fn invalid(message: &str) -> Error {
+    Error::new(ErrorKind::InvalidInput, message)
+}
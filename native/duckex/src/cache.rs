@@ -3,9 +3,38 @@
 //
 // SPDX-License-Identifier: Apache-2.0
 
+use std::collections::HashMap;
+
+use rustler::NifMap;
+
+struct Entry<T> {
+    data: T,
+    // Value of `Cache::tick` when the entry was last used
+    used: u64,
+}
+
+/// Cache with bounded capacity, evicting least recently used entry when full.
+///
+/// Ids are never reused, so id of evicted or removed entry stays invalid
+/// instead of pointing to another entry.
 pub(crate) struct Cache<T> {
-    storage: Vec<Option<T>>,
-    idx: usize,
+    storage: HashMap<u64, Entry<T>>,
+    capacity: usize,
+    next_id: u64,
+    tick: u64,
+    hits: u64,
+    misses: u64,
+    evictions: u64,
+}
+
+/// Usage counters of the cache
+#[derive(NifMap)]
+pub(crate) struct Stats {
+    size: usize,
+    capacity: usize,
+    hits: u64,
+    misses: u64,
+    evictions: u64,
 }
 
 impl<T> Default for Cache<T> {
@@ -16,49 +45,98 @@ impl<T> Default for Cache<T> {
 
 impl<T> Cache<T> {
     pub(crate) fn with_capacity(capacity: usize) -> Self {
-        let mut storage = vec![];
-        storage.resize_with(capacity, Default::default);
-
-        Cache { storage, idx: 0 }
+        Cache {
+            storage: HashMap::with_capacity(capacity),
+            capacity,
+            next_id: 0,
+            tick: 0,
+            hits: 0,
+            misses: 0,
+            evictions: 0,
+        }
     }
 
-    pub(crate) fn store(&mut self, data: T) -> Option<u32> {
-        let mut idx = self.idx;
-
-        // Find first unoccupied entry
-        while let Some(_) = self.storage[idx] {
-            idx += 1;
-            idx %= self.storage.len();
+    /// Store `data` and return its id, `None` only when capacity is zero
+    pub(crate) fn store(&mut self, data: T) -> Option<u64> {
+        if self.capacity == 0 {
+            return None;
+        }
 
-            if idx == self.idx {
-                return None;
-            }
+        while self.storage.len() >= self.capacity {
+            self.evict();
         }
 
-        self.storage[idx] = Some(data);
+        let id = self.next_id;
+        self.next_id += 1;
+        self.tick += 1;
 
-        Some(idx as u32)
+        self.storage.insert(
+            id,
+            Entry {
+                data,
+                used: self.tick,
+            },
+        );
+
+        Some(id)
     }
 
-    pub(crate) fn remove(&mut self, idx: usize) {
-        let _ = self.storage[idx].take();
+    pub(crate) fn remove(&mut self, id: u64) {
+        let _ = self.storage.remove(&id);
     }
 
-    pub(crate) fn get_ref(&self, idx: usize) -> Option<&T> {
-        self.storage[idx].as_ref()
+    pub(crate) fn clear(&mut self) {
+        self.storage.clear();
     }
-}
 
-impl<T> std::ops::Index<usize> for Cache<T> {
-    type Output = Option<T>;
+    pub(crate) fn get_ref(&mut self, id: u64) -> Option<&T> {
+        self.tick += 1;
+
+        match self.storage.get_mut(&id) {
+            Some(entry) => {
+                self.hits += 1;
+                entry.used = self.tick;
 
-    fn index(&self, idx: usize) -> &Option<T> {
-        &self.storage[idx]
+                Some(&entry.data)
+            }
+            None => {
+                self.misses += 1;
+
+                None
+            }
+        }
+    }
+
+    /// Change capacity, evicting least recently used entries that do not fit
+    pub(crate) fn resize(&mut self, capacity: usize) {
+        self.capacity = capacity;
+
+        while self.storage.len() > self.capacity {
+            self.evict();
+        }
     }
-}
 
-impl<T> std::ops::IndexMut<usize> for Cache<T> {
-    fn index_mut(&mut self, idx: usize) -> &mut Option<T> {
-        &mut self.storage[idx]
+    pub(crate) fn stats(&self) -> Stats {
+        Stats {
+            size: self.storage.len(),
+            capacity: self.capacity,
+            hits: self.hits,
+            misses: self.misses,
+            evictions: self.evictions,
+        }
+    }
+
+    // Remove least recently used entry
+    fn evict(&mut self) {
+        let lru = self
+            .storage
+            .iter()
+            .min_by_key(|(_, entry)| entry.used)
+            .map(|(&id, _)| id);
+
+        if let Some(id) = lru {
+            self.storage.remove(&id);
+            self.evictions += 1;
+        }
     }
 }
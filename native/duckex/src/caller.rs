@@ -0,0 +1,83 @@
+// SPDX-FileCopyrightText: 2025 Stas Muzhyk <sts@abc3.dev>
+// SPDX-FileCopyrightText: 2025 Łukasz Niemier <~@hauleth.dev>
+//
+// SPDX-License-Identifier: Apache-2.0
+
+use std::sync::{Arc, Mutex};
+
+use duckdb::InterruptHandle;
+use rustler::{Env, LocalPid, Monitor, Resource};
+
+use crate::lock;
+
+enum State {
+    Queued,
+    Running,
+    Finished,
+    // Caller exited before the query finished
+    Down,
+}
+
+/// Process waiting for the result of `execute_async` query. It is monitored,
+/// so its query is interrupted (or never started) once nobody waits for it
+/// anymore, instead of keeping the connection busy.
+pub struct CallerResource {
+    state: Mutex<State>,
+    interrupt: Arc<InterruptHandle>,
+}
+
+impl CallerResource {
+    pub(crate) fn new(interrupt: Arc<InterruptHandle>) -> Self {
+        CallerResource {
+            state: Mutex::new(State::Queued),
+            interrupt,
+        }
+    }
+
+    /// Mark the query as running, returns `false` when the caller is gone
+    pub(crate) fn start(&self) -> bool {
+        match lock::acquire(&self.state, None) {
+            Ok(mut state) if matches!(*state, State::Queued) => {
+                *state = State::Running;
+                true
+            }
+            _ => false,
+        }
+    }
+
+    /// Mark the query as finished, returns whether it was interrupted because
+    /// the caller exited. Has to be called by the job of the query on the
+    /// worker thread, so the interrupt cannot hit query of someone else.
+    pub(crate) fn finish(&self) -> bool {
+        match lock::acquire(&self.state, None) {
+            Ok(mut state) => {
+                let previous = std::mem::replace(&mut *state, State::Finished);
+                matches!(previous, State::Down)
+            }
+            Err(_) => false,
+        }
+    }
+
+    /// Caller exited already, before it could be monitored
+    pub(crate) fn abandon(&self) {
+        if let Ok(mut state) = lock::acquire(&self.state, None) {
+            *state = State::Down;
+        }
+    }
+}
+
+impl Resource for CallerResource {
+    const IMPLEMENTS_DOWN: bool = true;
+
+    fn down<'a>(&'a self, _env: Env<'a>, _pid: LocalPid, _monitor: Monitor) {
+        if let Ok(mut state) = lock::acquire(&self.state, None) {
+            match *state {
+                State::Running => self.interrupt.interrupt(),
+                State::Finished => return,
+                State::Queued | State::Down => {}
+            }
+
+            *state = State::Down;
+        }
+    }
+}
@@ -0,0 +1,127 @@
+// SPDX-FileCopyrightText: 2025 Stas Muzhyk <sts@abc3.dev>
+// SPDX-FileCopyrightText: 2025 Łukasz Niemier <~@hauleth.dev>
+//
+// SPDX-License-Identifier: Apache-2.0
+
+// Safe wrappers of DuckDB C API functions duckdb-rs does not wrap, called with
+// the raw handles exposed by the vendored crate
+
+use std::ffi::{c_char, c_void, CStr};
+
+use duckdb::{ffi, Statement};
+
+/// Name DuckDB gave the parameter at `idx`, starting from 1, e.g. `name` of
+/// `$name` or `1` of `?`
+pub(crate) fn parameter_name(stmt: &Statement, idx: usize) -> Option<String> {
+    // Safety: the handle is valid as long as `stmt`, the name is copied before
+    // it is freed
+    unsafe {
+        let name = ffi::duckdb_parameter_name(stmt.raw_statement(), idx as ffi::idx_t);
+
+        take_string(name as *mut c_char)
+    }
+}
+
+/// Type the parameter at `idx`, starting from 1, was bound to while preparing
+/// the statement
+pub(crate) fn parameter_type(stmt: &Statement, idx: usize) -> Option<LogicalType> {
+    // Safety: the handle is valid as long as `stmt`, the returned type is
+    // owned by the caller
+    let ty = unsafe { ffi::duckdb_param_logical_type(stmt.raw_statement(), idx as ffi::idx_t) };
+
+    (!ty.is_null()).then_some(LogicalType(ty))
+}
+
+/// Type DuckDB gave the statement while parsing it
+pub(crate) fn statement_type(stmt: &Statement) -> ffi::duckdb_statement_type {
+    // Safety: the handle is valid as long as `stmt`
+    unsafe { ffi::duckdb_prepared_statement_type(stmt.raw_statement()) }
+}
+
+/// Progress DuckDB reports for the query running on the connection
+pub(crate) struct QueryProgress {
+    /// Percentage done, `None` until DuckDB can estimate it
+    pub(crate) percentage: Option<f64>,
+    pub(crate) rows_processed: u64,
+    pub(crate) total_rows: u64,
+}
+
+/// Poll progress of the query running on `conn`, safe to call from another
+/// thread while the query runs
+///
+/// # Safety
+///
+/// `conn` must be a handle of an open connection
+pub(crate) unsafe fn query_progress(conn: ffi::duckdb_connection) -> QueryProgress {
+    let progress = ffi::duckdb_query_progress(conn);
+
+    QueryProgress {
+        percentage: (progress.percentage >= 0.0).then_some(progress.percentage),
+        rows_processed: progress.rows_processed,
+        total_rows: progress.total_rows_to_process,
+    }
+}
+
+/// Logical type returned by the C API, destroyed once dropped
+pub(crate) struct LogicalType(ffi::duckdb_logical_type);
+
+impl LogicalType {
+    /// Members of `ENUM` type, `None` for other types
+    pub(crate) fn enum_members(&self) -> Option<Vec<String>> {
+        if self.id() != ffi::DUCKDB_TYPE_DUCKDB_TYPE_ENUM {
+            return None;
+        }
+
+        // Safety: the type is valid until dropped, members are copied before
+        // they are freed
+        unsafe {
+            let size = ffi::duckdb_enum_dictionary_size(self.0);
+
+            (0..size)
+                .map(|idx| {
+                    take_string(ffi::duckdb_enum_dictionary_value(self.0, idx as ffi::idx_t))
+                })
+                .collect()
+        }
+    }
+
+    /// Size and element type of `ARRAY` type, `None` for other types
+    pub(crate) fn array(&self) -> Option<(usize, LogicalType)> {
+        if self.id() != ffi::DUCKDB_TYPE_DUCKDB_TYPE_ARRAY {
+            return None;
+        }
+
+        // Safety: the type is valid until dropped, the element type is owned
+        // by the returned value
+        unsafe {
+            let size = ffi::duckdb_array_type_array_size(self.0) as usize;
+            let child = ffi::duckdb_array_type_child_type(self.0);
+
+            Some((size, LogicalType(child)))
+        }
+    }
+
+    fn id(&self) -> ffi::DUCKDB_TYPE {
+        // Safety: the type is valid until dropped
+        unsafe { ffi::duckdb_get_type_id(self.0) }
+    }
+}
+
+impl Drop for LogicalType {
+    fn drop(&mut self) {
+        // Safety: the type is owned and not used afterwards
+        unsafe { ffi::duckdb_destroy_logical_type(&mut self.0) }
+    }
+}
+
+// Copy string allocated by DuckDB and free it
+unsafe fn take_string(ptr: *mut c_char) -> Option<String> {
+    if ptr.is_null() {
+        return None;
+    }
+
+    let value = CStr::from_ptr(ptr).to_string_lossy().into_owned();
+    ffi::duckdb_free(ptr as *mut c_void);
+
+    Some(value)
+}
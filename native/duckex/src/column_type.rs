@@ -0,0 +1,116 @@
+// SPDX-FileCopyrightText: 2025 Stas Muzhyk <sts@abc3.dev>
+// SPDX-FileCopyrightText: 2025 Łukasz Niemier <~@hauleth.dev>
+//
+// SPDX-License-Identifier: Apache-2.0
+
+use arrow::datatypes::{DataType, Fields, TimeUnit};
+use rustler::{Atom, Encoder, Env, Term};
+
+mod atoms {
+    rustler::atoms! {
+        null,
+        boolean,
+        tinyint,
+        smallint,
+        integer,
+        bigint,
+        utinyint,
+        usmallint,
+        uinteger,
+        ubigint,
+        float,
+        double,
+        decimal,
+        varchar,
+        blob,
+        date,
+        time,
+        timestamp,
+        timestamptz,
+        interval,
+        list,
+        array,
+        r#struct = "struct",
+        map,
+        r#enum = "enum",
+        union,
+        second,
+        millisecond,
+        microsecond,
+        nanosecond,
+    }
+}
+
+/// Structured descriptor of the column type, e.g. `:integer`,
+/// `{:decimal, 18, 3}` or `{:list, :varchar}`. Types without descriptor are
+/// returned as their display string.
+pub(crate) fn encode<'a>(env: Env<'a>, data_type: &DataType) -> Term<'a> {
+    let simple = |atom: Atom| atom.encode(env);
+
+    match data_type {
+        DataType::Null => simple(atoms::null()),
+        DataType::Boolean => simple(atoms::boolean()),
+        DataType::Int8 => simple(atoms::tinyint()),
+        DataType::Int16 => simple(atoms::smallint()),
+        DataType::Int32 => simple(atoms::integer()),
+        DataType::Int64 => simple(atoms::bigint()),
+        DataType::UInt8 => simple(atoms::utinyint()),
+        DataType::UInt16 => simple(atoms::usmallint()),
+        DataType::UInt32 => simple(atoms::uinteger()),
+        DataType::UInt64 => simple(atoms::ubigint()),
+        DataType::Float32 => simple(atoms::float()),
+        DataType::Float64 => simple(atoms::double()),
+        DataType::Decimal128(precision, scale) | DataType::Decimal256(precision, scale) => {
+            (atoms::decimal(), *precision, *scale).encode(env)
+        }
+        DataType::Utf8 | DataType::LargeUtf8 | DataType::Utf8View => simple(atoms::varchar()),
+        DataType::Binary | DataType::LargeBinary | DataType::BinaryView => simple(atoms::blob()),
+        DataType::Date32 | DataType::Date64 => simple(atoms::date()),
+        DataType::Time32(_) | DataType::Time64(_) => simple(atoms::time()),
+        DataType::Timestamp(unit, None) => (atoms::timestamp(), time_unit(unit)).encode(env),
+        DataType::Timestamp(unit, Some(_)) => (atoms::timestamptz(), time_unit(unit)).encode(env),
+        DataType::Interval(_) => simple(atoms::interval()),
+        DataType::List(field) | DataType::LargeList(field) => {
+            (atoms::list(), encode(env, field.data_type())).encode(env)
+        }
+        DataType::FixedSizeList(field, size) => {
+            (atoms::array(), encode(env, field.data_type()), *size).encode(env)
+        }
+        DataType::Struct(fields) => (atoms::r#struct(), encode_fields(env, fields)).encode(env),
+        DataType::Map(field, _) => match field.data_type() {
+            DataType::Struct(fields) if fields.len() == 2 => (
+                atoms::map(),
+                encode(env, fields[0].data_type()),
+                encode(env, fields[1].data_type()),
+            )
+                .encode(env),
+            _ => data_type.to_string().encode(env),
+        },
+        DataType::Dictionary(_, _) => simple(atoms::r#enum()),
+        DataType::Union(fields, _) => {
+            let fields: Vec<_> = fields
+                .iter()
+                .map(|(_, field)| (field.name().clone(), encode(env, field.data_type())))
+                .collect();
+
+            (atoms::union(), fields).encode(env)
+        }
+        other => other.to_string().encode(env),
+    }
+}
+
+fn encode_fields<'a>(env: Env<'a>, fields: &Fields) -> Vec<(String, Term<'a>)> {
+    fields
+        .iter()
+        .map(|field| (field.name().clone(), encode(env, field.data_type())))
+        .collect()
+}
+
+fn time_unit(unit: &TimeUnit) -> Atom {
+    match unit {
+        TimeUnit::Second => atoms::second(),
+        TimeUnit::Millisecond => atoms::millisecond(),
+        TimeUnit::Microsecond => atoms::microsecond(),
+        TimeUnit::Nanosecond => atoms::nanosecond(),
+    }
+}
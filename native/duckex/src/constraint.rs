@@ -0,0 +1,133 @@
+// SPDX-FileCopyrightText: 2025 Stas Muzhyk <sts@abc3.dev>
+// SPDX-FileCopyrightText: 2025 Łukasz Niemier <~@hauleth.dev>
+//
+// SPDX-License-Identifier: Apache-2.0
+
+use rustler::{Decoder, Encoder, Env, NifResult, NifUnitEnum, Term};
+
+mod atoms {
+    rustler::atoms! {
+        r#type = "type",
+        table,
+        column,
+    }
+}
+
+#[derive(NifUnitEnum, Clone, Copy, PartialEq, Eq, Debug)]
+pub(crate) enum ConstraintType {
+    NotNull,
+    Unique,
+    PrimaryKey,
+    Check,
+    ForeignKey,
+}
+
+/// Details of the violated constraint, encoded as
+/// `%{type: atom, table: String.t() | nil, column: String.t() | nil}`
+#[derive(Debug, PartialEq, Eq)]
+pub(crate) struct Constraint {
+    pub(crate) r#type: ConstraintType,
+    pub(crate) table: Option<String>,
+    pub(crate) column: Option<String>,
+}
+
+impl Encoder for Constraint {
+    fn encode<'a>(&self, env: Env<'a>) -> Term<'a> {
+        let keys = [
+            atoms::r#type().encode(env),
+            atoms::table().encode(env),
+            atoms::column().encode(env),
+        ];
+        let values = [
+            self.r#type.encode(env),
+            self.table.encode(env),
+            self.column.encode(env),
+        ];
+
+        Term::map_from_term_arrays(env, &keys, &values)
+            .unwrap_or_else(|_| rustler::types::atom::nil().encode(env))
+    }
+}
+
+// Errors are only ever sent to Elixir, but `Duckex.Error` struct derives both
+impl<'a> Decoder<'a> for Constraint {
+    fn decode(_term: Term<'a>) -> NifResult<Self> {
+        Err(rustler::Error::BadArg)
+    }
+}
+
+/// Parse DuckDB constraint violation message, for example:
+///
+/// - `NOT NULL constraint failed: users.email`
+/// - `Duplicate key "id: 1" violates primary key constraint`
+/// - `Duplicate key "email: a@b.c" violates unique constraint`
+/// - `CHECK constraint failed on table users with expression CHECK((age > 0))`
+/// - `Violates foreign key constraint because key "user_id: 5" does not exist
+///   in the referenced table`
+pub(crate) fn parse(message: &str) -> Option<Constraint> {
+    if let Some(rest) = after(message, "NOT NULL constraint failed: ") {
+        let target = rest.split_whitespace().next().unwrap_or_default();
+        let (table, column) = match target.rsplit_once('.') {
+            Some((table, column)) => (Some(table.to_string()), Some(column.to_string())),
+            None => (None, Some(target.to_string())),
+        };
+
+        return Some(Constraint {
+            r#type: ConstraintType::NotNull,
+            table,
+            column,
+        });
+    }
+
+    if let Some(rest) = after(message, "CHECK constraint failed") {
+        let table = after(rest, "on table ")
+            .or_else(|| after(rest, ": "))
+            .and_then(|t| t.split_whitespace().next())
+            .map(str::to_string);
+
+        return Some(Constraint {
+            r#type: ConstraintType::Check,
+            table,
+            column: None,
+        });
+    }
+
+    let r#type = if message.contains("violates primary key constraint") {
+        ConstraintType::PrimaryKey
+    } else if message.contains("violates unique constraint") {
+        ConstraintType::Unique
+    } else if message.contains("foreign key constraint") {
+        ConstraintType::ForeignKey
+    } else {
+        return None;
+    };
+
+    // Key violations do not name the table, it is filled in from the failed
+    // statement by `Error::in_statement`
+    Some(Constraint {
+        r#type,
+        table: None,
+        column: key_columns(message),
+    })
+}
+
+fn after<'a>(haystack: &'a str, needle: &str) -> Option<&'a str> {
+    haystack.find(needle).map(|pos| &haystack[pos + needle.len()..])
+}
+
+// Column names from `key "a: 1, b: 2"` part of the message, joined with comma
+fn key_columns(message: &str) -> Option<String> {
+    let key = after(message, "key \"")?;
+    let key = &key[..key.find('"')?];
+
+    let columns: Vec<_> = key
+        .split(", ")
+        .filter_map(|pair| pair.split_once(": ").map(|(column, _)| column))
+        .collect();
+
+    if columns.is_empty() {
+        None
+    } else {
+        Some(columns.join(", "))
+    }
+}
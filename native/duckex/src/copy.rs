@@ -0,0 +1,105 @@
+// SPDX-FileCopyrightText: 2025 Stas Muzhyk <sts@abc3.dev>
+// SPDX-FileCopyrightText: 2025 Łukasz Niemier <~@hauleth.dev>
+//
+// SPDX-License-Identifier: Apache-2.0
+
+use rustler::NifUnitEnum;
+
+use crate::options::Options;
+use crate::sql::{quote_identifier, quote_literal};
+
+#[derive(NifUnitEnum, Clone, Copy)]
+pub(crate) enum CopyFormat {
+    Csv,
+    Parquet,
+    Json,
+}
+
+impl CopyFormat {
+    pub(crate) fn as_str(self) -> &'static str {
+        match self {
+            CopyFormat::Csv => "csv",
+            CopyFormat::Parquet => "parquet",
+            CopyFormat::Json => "json",
+        }
+    }
+}
+
+/// Build `COPY (query) TO 'path' (...)` statement out of structured options
+pub(crate) fn copy_to_sql(
+    query: &str,
+    path: &str,
+    format: CopyFormat,
+    opts: &Options,
+) -> Result<String, String> {
+    Ok(format!(
+        "COPY ({}) TO {} ({})",
+        query,
+        quote_literal(path),
+        copy_options(format, opts)?.join(", ")
+    ))
+}
+
+/// `EXPORT DATABASE` writes every table with `COPY`, so it takes the same
+/// options
+pub(crate) fn export_database_sql(
+    dir: &str,
+    format: CopyFormat,
+    opts: &Options,
+) -> Result<String, String> {
+    Ok(format!(
+        "EXPORT DATABASE {} ({})",
+        quote_literal(dir),
+        copy_options(format, opts)?.join(", ")
+    ))
+}
+
+pub(crate) fn import_database_sql(dir: &str) -> String {
+    format!("IMPORT DATABASE {}", quote_literal(dir))
+}
+
+fn copy_options(format: CopyFormat, opts: &Options) -> Result<Vec<String>, String> {
+    let mut parts = vec![format!("FORMAT {}", format.as_str())];
+
+    for key in opts.keys() {
+        match key {
+            "compression" => {
+                if let Some(compression) = opts.get_string(key)? {
+                    parts.push(format!("COMPRESSION {}", quote_literal(&compression)));
+                }
+            }
+            "delimiter" => {
+                if !matches!(format, CopyFormat::Csv) {
+                    return Err("Option :delimiter is only supported for CSV".to_string());
+                }
+
+                if let Some(delimiter) = opts.get::<String>(key)? {
+                    parts.push(format!("DELIMITER {}", quote_literal(&delimiter)));
+                }
+            }
+            "header" => {
+                if !matches!(format, CopyFormat::Csv) {
+                    return Err("Option :header is only supported for CSV".to_string());
+                }
+
+                if let Some(header) = opts.get::<bool>(key)? {
+                    parts.push(format!("HEADER {}", header));
+                }
+            }
+            "partition_by" => {
+                if let Some(columns) = opts.get::<Vec<String>>(key)? {
+                    let columns: Vec<_> = columns.iter().map(|c| quote_identifier(c)).collect();
+                    parts.push(format!("PARTITION_BY ({})", columns.join(", ")));
+                }
+            }
+            "overwrite" => {
+                if let Some(true) = opts.get::<bool>(key)? {
+                    parts.push("OVERWRITE true".to_string());
+                }
+            }
+            other => return Err(format!("Unsupported COPY option :{}", other)),
+        }
+    }
+
+    Ok(parts)
+}
@@ -0,0 +1,89 @@
+// SPDX-FileCopyrightText: 2025 Stas Muzhyk <sts@abc3.dev>
+// SPDX-FileCopyrightText: 2025 Łukasz Niemier <~@hauleth.dev>
+//
+// SPDX-License-Identifier: Apache-2.0
+
+use duckdb::Connection;
+use rustler::NifMap;
+
+use crate::describe::Column;
+use crate::error::Error;
+use crate::options::Options;
+use crate::sql::{create_table_as, quote_literal};
+
+/// CSV dialect and schema detected by DuckDB
+#[derive(NifMap)]
+pub(crate) struct Sniffed {
+    delimiter: String,
+    quote: String,
+    header: bool,
+    columns: Vec<Column>,
+}
+
+/// Build statement loading CSV file at `path` into new table `table`
+pub(crate) fn load_sql(table: &str, path: &str, opts: &Options) -> Result<String, String> {
+    create_table_as(table, &read_csv_sql(path, opts)?, opts)
+}
+
+/// `read_csv` call with parsing options, other options are ignored
+fn read_csv_sql(path: &str, opts: &Options) -> Result<String, String> {
+    let mut args = vec![quote_literal(path)];
+
+    for key in opts.keys() {
+        match key {
+            "header" => {
+                if let Some(header) = opts.get::<bool>(key)? {
+                    args.push(format!("header = {}", header));
+                }
+            }
+            "delimiter" => {
+                if let Some(delimiter) = opts.get::<String>(key)? {
+                    args.push(format!("delim = {}", quote_literal(&delimiter)));
+                }
+            }
+            "quote" => {
+                if let Some(quote) = opts.get::<String>(key)? {
+                    args.push(format!("quote = {}", quote_literal(&quote)));
+                }
+            }
+            "skip" => {
+                if let Some(skip) = opts.get::<usize>(key)? {
+                    args.push(format!("skip = {}", skip));
+                }
+            }
+            "null" => {
+                if let Some(null) = opts.get::<String>(key)? {
+                    args.push(format!("nullstr = {}", quote_literal(&null)));
+                }
+            }
+            _ => {}
+        }
+    }
+
+    Ok(format!("read_csv({})", args.join(", ")))
+}
+
+/// Detect dialect and schema of CSV file at `path` without loading it
+pub(crate) fn sniff(conn: &Connection, path: &str) -> Result<Sniffed, Error> {
+    // Columns are list of structs, easiest to get them out through JSON
+    let sql = format!(
+        "SELECT Delimiter, Quote, HasHeader, to_json(Columns)::VARCHAR FROM sniff_csv({})",
+        quote_literal(path)
+    );
+
+    let (delimiter, quote, header, columns): (String, String, bool, String) = conn
+        .query_row(&sql, [], |row| {
+            Ok((row.get(0)?, row.get(1)?, row.get(2)?, row.get(3)?))
+        })
+        .map_err(|e| format!("SQL execution error: {}", e))?;
+
+    let columns = serde_json::from_str(&columns)
+        .map_err(|e| format!("Failed to parse sniffed columns: {}", e))?;
+
+    Ok(Sniffed {
+        delimiter,
+        quote,
+        header,
+        columns,
+    })
+}
@@ -0,0 +1,177 @@
+// SPDX-FileCopyrightText: 2025 Stas Muzhyk <sts@abc3.dev>
+// SPDX-FileCopyrightText: 2025 Łukasz Niemier <~@hauleth.dev>
+//
+// SPDX-License-Identifier: Apache-2.0
+
+use duckdb::Connection;
+use rustler::NifMap;
+
+use crate::error::Error;
+use crate::options::Options;
+use crate::sql::{quote_identifier, quote_literal};
+
+#[derive(NifMap, Debug, PartialEq, Eq)]
+pub(crate) struct DatabaseInfo {
+    pub(crate) name: String,
+    // `nil` for in-memory databases
+    pub(crate) path: Option<String>,
+    pub(crate) read_only: bool,
+}
+
+pub(crate) fn attach_sql(path: &str, opts: &Options) -> Result<String, Error> {
+    let mut sql = format!("ATTACH {}", quote_literal(path));
+
+    if let Some(alias) = opts.get_string("as")? {
+        sql.push_str(&format!(" AS {}", quote_identifier(&alias)));
+    }
+
+    if let Some(options) = opts.get::<Options>("options")? {
+        let options = attach_options(&options)?;
+
+        if !options.is_empty() {
+            sql.push_str(&format!(" ({})", options.join(", ")));
+        }
+    }
+
+    Ok(sql)
+}
+
+// `true` flags are given bare, atoms as keywords, e.g. `TYPE sqlite`, and
+// everything else as string literals
+fn attach_options(options: &Options) -> Result<Vec<String>, Error> {
+    let mut parts = vec![];
+
+    for key in options.keys() {
+        if !key.chars().all(|c| c.is_ascii_alphanumeric() || c == '_') {
+            return Err(format!("Invalid ATTACH option :{}", key).into());
+        }
+
+        let term = options.get_term(key).expect("key is present");
+
+        if let Ok(flag) = term.decode::<bool>() {
+            if flag {
+                parts.push(key.to_uppercase());
+            }
+        } else if term.is_atom() {
+            // `nil` is treated as if the option was not given
+            if let Some(value) = options.get_string(key)? {
+                if !value.chars().all(|c| c.is_ascii_alphanumeric() || c == '_') {
+                    return Err(format!("Invalid value for option :{}", key).into());
+                }
+
+                parts.push(format!("{} {}", key.to_uppercase(), value));
+            }
+        } else if let Ok(number) = term.decode::<i64>() {
+            parts.push(format!("{} {}", key.to_uppercase(), number));
+        } else {
+            let value = options
+                .get_string(key)?
+                .ok_or_else(|| format!("Invalid value for option :{}", key))?;
+
+            parts.push(format!("{} {}", key.to_uppercase(), quote_literal(&value)));
+        }
+    }
+
+    Ok(parts)
+}
+
+pub(crate) fn detach_sql(name: &str) -> String {
+    format!("DETACH {}", quote_identifier(name))
+}
+
+/// User attached databases, without the internal `system` and `temp` ones
+pub(crate) fn list(conn: &Connection) -> Result<Vec<DatabaseInfo>, Error> {
+    let mut stmt = conn
+        .prepare(
+            "SELECT database_name, path, readonly FROM duckdb_databases() \
+             WHERE NOT internal ORDER BY database_name",
+        )
+        .map_err(|e| format!("SQL preparation error: {}", e))?;
+
+    let databases = stmt
+        .query_map([], |row| {
+            Ok(DatabaseInfo {
+                name: row.get(0)?,
+                path: row.get(1)?,
+                read_only: row.get(2)?,
+            })
+        })
+        .map_err(|e| format!("SQL execution error: {}", e))?
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|e| format!("SQL row processing error: {}", e))?;
+
+    Ok(databases)
+}
+
+/// Database attached after `before` was listed. DuckDB derives the name from
+/// the file name when no alias is given, so it has to be looked up this way.
+pub(crate) fn attached(
+    conn: &Connection,
+    before: &[DatabaseInfo],
+    path: &str,
+) -> Result<DatabaseInfo, Error> {
+    list(conn)?
+        .into_iter()
+        .find(|db| !before.iter().any(|old| old.name == db.name))
+        .ok_or_else(|| format!("Database '{}' is already attached", path).into())
+}
+
+/// Storage statistics of the database, as `PRAGMA database_size` reports
+/// them, but with sizes in bytes instead of human-formatted strings
+#[derive(NifMap)]
+pub(crate) struct Size {
+    name: String,
+    block_size: u64,
+    total_blocks: u64,
+    used_blocks: u64,
+    free_blocks: u64,
+    // Size of the database file, `total_blocks * block_size`
+    size: u64,
+    // `nil` for in-memory databases and ones without write-ahead log
+    wal_size: Option<u64>,
+    // Memory used by the whole database instance, not only this database
+    memory_usage: u64,
+}
+
+pub(crate) fn size(conn: &Connection) -> Result<Vec<Size>, Error> {
+    let mut stmt = conn
+        .prepare(
+            "SELECT s.database_name, s.block_size, s.total_blocks, s.used_blocks, \
+             s.free_blocks, d.path, \
+             (SELECT coalesce(sum(memory_usage_bytes), 0) FROM duckdb_memory()) \
+             FROM pragma_database_size() s \
+             JOIN duckdb_databases() d ON d.database_name = s.database_name \
+             ORDER BY s.database_name",
+        )
+        .map_err(|e| format!("SQL preparation error: {}", e))?;
+
+    let sizes = stmt
+        .query_map([], |row| {
+            let block_size: u64 = row.get(1)?;
+            let total_blocks: u64 = row.get(2)?;
+            let path: Option<String> = row.get(5)?;
+
+            Ok(Size {
+                name: row.get(0)?,
+                block_size,
+                total_blocks,
+                used_blocks: row.get(3)?,
+                free_blocks: row.get(4)?,
+                size: total_blocks * block_size,
+                wal_size: path.and_then(|path| wal_size(&path)),
+                memory_usage: row.get(6)?,
+            })
+        })
+        .map_err(|e| format!("SQL execution error: {}", e))?
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|e| format!("SQL row processing error: {}", e))?;
+
+    Ok(sizes)
+}
+
+// DuckDB keeps the log next to the database file and removes it on checkpoint
+fn wal_size(path: &str) -> Option<u64> {
+    std::fs::metadata(format!("{}.wal", path))
+        .ok()
+        .map(|metadata| metadata.len())
+}
@@ -0,0 +1,135 @@
+// SPDX-FileCopyrightText: 2025 Stas Muzhyk <sts@abc3.dev>
+// SPDX-FileCopyrightText: 2025 Łukasz Niemier <~@hauleth.dev>
+//
+// SPDX-License-Identifier: Apache-2.0
+
+use std::collections::HashMap;
+use std::ops::RangeInclusive;
+
+use chrono::{NaiveDate, NaiveTime};
+use duckdb::types::{TimeUnit, Value};
+use rustler::types::map::MapIterator;
+use rustler::{Decoder, Term};
+
+use crate::error::{Error, ErrorKind};
+
+// Largest UTC offset of `TIME WITH TIME ZONE`, 15:59:59
+const MAX_OFFSET: i32 = 16 * 60 * 60 - 1;
+
+/// Module and fields of struct, `None` for other terms
+pub(crate) fn struct_fields(term: Term) -> Option<(String, HashMap<String, Term>)> {
+    let fields: HashMap<String, Term> = MapIterator::new(term)?
+        .filter_map(|(key, value)| Some((key.atom_to_string().ok()?, value)))
+        .collect();
+    let module = fields.get("__struct__")?.atom_to_string().ok()?;
+
+    Some((module, fields))
+}
+
+/// Convert `Date`, `Time` or `DateTime` struct, given its module and fields,
+/// into parameter value. Returns `None` for other structs.
+pub(crate) fn decode(module: &str, fields: &HashMap<String, Term>) -> Result<Option<Value>, Error> {
+    match module {
+        "Elixir.Date" => date(fields).map(Some),
+        "Elixir.Time" => time(fields).map(|time| Some(Value::Text(time))),
+        "Elixir.DateTime" => timestamp(fields).map(Some),
+        _ => Ok(None),
+    }
+}
+
+/// `{%Time{}, utc_offset}` parameter as `TIME WITH TIME ZONE` string, e.g.
+/// `12:30:00.000000+02:00`, which DuckDB casts. Returns `None` when `time` is
+/// not a `Time` struct.
+pub(crate) fn time_tz(term: Term, offset: i32) -> Result<Option<Value>, Error> {
+    let fields = match struct_fields(term) {
+        Some((module, fields)) if module == "Elixir.Time" => fields,
+        _ => return Ok(None),
+    };
+
+    if !(-MAX_OFFSET..=MAX_OFFSET).contains(&offset) {
+        return Err(invalid("utc_offset", offset));
+    }
+
+    let sign = if offset < 0 { '-' } else { '+' };
+    let offset = offset.unsigned_abs();
+    let (hours, minutes, seconds) = (offset / 3600, offset / 60 % 60, offset % 60);
+    let mut text = format!("{}{}{:02}:{:02}", time(&fields)?, sign, hours, minutes);
+
+    if seconds != 0 {
+        text.push_str(&format!(":{:02}", seconds));
+    }
+
+    Ok(Some(Value::Text(text)))
+}
+
+/// `Date` parameter as ISO 8601 date string (YYYY-MM-DD), as DuckDB Rust
+/// library doesn't support Date32 for parameter binding
+fn date(fields: &HashMap<String, Term>) -> Result<Value, Error> {
+    let date = naive_date(fields)?;
+
+    Ok(Value::Text(date.format("%Y-%m-%d").to_string()))
+}
+
+/// `Time` parameter as ISO 8601 time string with microseconds, for the same
+/// reason as dates
+fn time(fields: &HashMap<String, Term>) -> Result<String, Error> {
+    Ok(naive_time(fields)?.format("%H:%M:%S%.6f").to_string())
+}
+
+/// `DateTime` parameter as UTC timestamp in microseconds
+fn timestamp(fields: &HashMap<String, Term>) -> Result<Value, Error> {
+    let date = naive_date(fields)?;
+    let time = naive_time(fields)?;
+
+    // Fields hold wall time in the time zone, which is offset from UTC
+    let offset = field::<i64>(fields, "utc_offset").unwrap_or(0)
+        + field::<i64>(fields, "std_offset").unwrap_or(0);
+
+    let micros = date.and_time(time).and_utc().timestamp_micros() - offset * 1_000_000;
+
+    Ok(Value::Timestamp(TimeUnit::Microsecond, micros))
+}
+
+fn naive_time(fields: &HashMap<String, Term>) -> Result<NaiveTime, Error> {
+    let hour = in_range("hour", field(fields, "hour")?, 0..=23)?;
+    let minute = in_range("minute", field(fields, "minute")?, 0..=59)?;
+    let second = in_range("second", field(fields, "second")?, 0..=59)?;
+    // microsecond is a tuple {value, precision}
+    let (microsecond, _): (u32, u32) = field(fields, "microsecond")?;
+    let microsecond = in_range("microsecond", microsecond, 0..=999_999)?;
+
+    NaiveTime::from_hms_micro_opt(hour, minute, second, microsecond)
+        .ok_or_else(|| invalid("time", format!("{}:{}:{}", hour, minute, second)))
+}
+
+fn naive_date(fields: &HashMap<String, Term>) -> Result<NaiveDate, Error> {
+    let year: i32 = field(fields, "year")?;
+    let month = in_range("month", field(fields, "month")?, 1..=12)?;
+    let day: u32 = field(fields, "day")?;
+
+    NaiveDate::from_ymd_opt(year, month, 1).ok_or_else(|| invalid("year", year))?;
+
+    NaiveDate::from_ymd_opt(year, month, day).ok_or_else(|| invalid("day", day))
+}
+
+fn field<'a, T: Decoder<'a>>(fields: &HashMap<String, Term<'a>>, name: &str) -> Result<T, Error> {
+    fields
+        .get(name)
+        .and_then(|term| term.decode().ok())
+        .ok_or_else(|| invalid(name, "missing"))
+}
+
+fn in_range(name: &str, value: u32, range: RangeInclusive<u32>) -> Result<u32, Error> {
+    if range.contains(&value) {
+        Ok(value)
+    } else {
+        Err(invalid(name, value))
+    }
+}
+
+fn invalid(name: &str, value: impl std::fmt::Display) -> Error {
+    Error::new(
+        ErrorKind::InvalidDate,
+        format!("Invalid date parameter: {} is {}", name, value),
+    )
+}
@@ -0,0 +1,240 @@
+// SPDX-FileCopyrightText: 2025 Stas Muzhyk <sts@abc3.dev>
+// SPDX-FileCopyrightText: 2025 Łukasz Niemier <~@hauleth.dev>
+//
+// SPDX-License-Identifier: Apache-2.0
+
+use duckdb::Connection;
+use rustler::{Decoder, Encoder, Env, NifMap, NifResult, Term};
+use serde::Deserialize;
+
+use crate::error::Error;
+
+mod atoms {
+    rustler::atoms! {
+        name,
+        r#type = "type",
+        nullable,
+        table,
+        column,
+    }
+}
+
+/// Column encoded as `%{name: String.t(), type: String.t(), nullable:
+/// boolean() | nil, table: String.t() | nil, column: String.t() | nil}`
+#[derive(Deserialize)]
+pub(crate) struct Column {
+    name: String,
+    r#type: String,
+    #[serde(default)]
+    nullable: Option<bool>,
+    // Table and column the value comes from, only known for simple queries
+    #[serde(default)]
+    table: Option<String>,
+    #[serde(default)]
+    column: Option<String>,
+}
+
+impl Encoder for Column {
+    fn encode<'a>(&self, env: Env<'a>) -> Term<'a> {
+        let keys = [
+            atoms::name().encode(env),
+            atoms::r#type().encode(env),
+            atoms::nullable().encode(env),
+            atoms::table().encode(env),
+            atoms::column().encode(env),
+        ];
+        let values = [
+            self.name.encode(env),
+            self.r#type.encode(env),
+            self.nullable.encode(env),
+            self.table.encode(env),
+            self.column.encode(env),
+        ];
+
+        Term::map_from_term_arrays(env, &keys, &values)
+            .unwrap_or_else(|_| rustler::types::atom::nil().encode(env))
+    }
+}
+
+// Only needed for maps holding columns, which are never passed in from Elixir
+impl<'a> Decoder<'a> for Column {
+    fn decode(_term: Term<'a>) -> NifResult<Self> {
+        Err(rustler::Error::BadArg)
+    }
+}
+
+/// Nullability and origin of result column, encoded as `%{nullable: boolean()
+/// | nil, table: String.t() | nil, column: String.t() | nil}`. All are `nil`
+/// unless the column comes from a table, see `columns`.
+#[derive(NifMap, Default)]
+pub(crate) struct ColumnInfo {
+    nullable: Option<bool>,
+    table: Option<String>,
+    column: Option<String>,
+}
+
+/// Names and types of columns returned by the query, without running it.
+///
+/// Statement metadata in duckdb-rs is only available after execution, so this
+/// uses `DESCRIBE`, which only binds the query.
+pub(crate) fn columns(conn: &Connection, query: &str) -> Result<Vec<Column>, Error> {
+    let query = query.trim_end().trim_end_matches(';');
+
+    let mut stmt = conn
+        .prepare(&format!("DESCRIBE {}", query))
+        .map_err(|e| format!("SQL preparation error: {}", e))?;
+
+    let mut columns = stmt
+        .query_map([], |row| {
+            Ok(Column {
+                name: row.get("column_name")?,
+                r#type: row.get("column_type")?,
+                nullable: row.get::<_, Option<String>>("null")?.map(|null| null == "YES"),
+                table: None,
+                column: None,
+            })
+        })
+        .map_err(|e| format!("SQL execution error: {}", e))?
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|e| format!("SQL row processing error: {}", e))?;
+
+    // Origin is best effort, query is described even when it cannot be found
+    let names: Vec<_> = columns.iter().map(|column| column.name.clone()).collect();
+
+    for (column, info) in columns.iter_mut().zip(column_info(conn, query, &names)) {
+        if info.table.is_some() {
+            column.nullable = info.nullable;
+            column.table = info.table;
+            column.column = info.column;
+        }
+    }
+
+    Ok(columns)
+}
+
+/// Nullability and origin of columns `names` returned by the query, in the
+/// same order
+pub(crate) fn column_info(conn: &Connection, query: &str, names: &[String]) -> Vec<ColumnInfo> {
+    let query = query.trim_end().trim_end_matches(';');
+
+    match origin(conn, query) {
+        Some(origin) => origin.resolve(conn, names),
+        None => names.iter().map(|_| ColumnInfo::default()).collect(),
+    }
+}
+
+/// Table a simple `SELECT ... FROM table` query reads, with the column each
+/// of the selected values comes from
+struct Origin {
+    catalog: String,
+    schema: String,
+    table: String,
+    columns: Option<Vec<Option<String>>>,
+}
+
+// Parses the query with `json_serialize_sql`, which does not bind it. Only
+// queries selecting plain column references or `*` from single table have
+// known origin.
+fn origin(conn: &Connection, query: &str) -> Option<Origin> {
+    let json: String = conn
+        .query_row("SELECT json_serialize_sql(?)", [query], |row| row.get(0))
+        .ok()?;
+    let json: serde_json::Value = serde_json::from_str(&json).ok()?;
+
+    let [statement] = json["statements"].as_array()?.as_slice() else {
+        return None;
+    };
+
+    let node = &statement["node"];
+    let from = &node["from_table"];
+
+    let with_ctes = node["cte_map"]["map"].as_array().is_some_and(|ctes| !ctes.is_empty());
+
+    if node["type"] != "SELECT_NODE" || from["type"] != "BASE_TABLE" || with_ctes {
+        return None;
+    }
+
+    let select = node["select_list"].as_array()?;
+
+    // Plain `*` selects all columns of the table under their own names
+    let columns = match select.as_slice() {
+        [star] if star["class"] == "STAR" && is_plain_star(star) => None,
+        _ => Some(
+            select
+                .iter()
+                .map(|expr| match expr["class"].as_str()? {
+                    "COLUMN_REF" => expr["column_names"].as_array()?.last()?.as_str(),
+                    _ => None,
+                })
+                .map(|column| column.map(str::to_string))
+                .collect(),
+        ),
+    };
+
+    Some(Origin {
+        catalog: from["catalog_name"].as_str()?.to_string(),
+        schema: from["schema_name"].as_str()?.to_string(),
+        table: from["table_name"].as_str()?.to_string(),
+        columns,
+    })
+}
+
+fn is_plain_star(star: &serde_json::Value) -> bool {
+    let empty = |key: &str| star[key].as_array().is_none_or(|list| list.is_empty());
+
+    empty("exclude_list") && empty("replace_list") && star["expr"].is_null()
+}
+
+impl Origin {
+    fn resolve(self, conn: &Connection, names: &[String]) -> Vec<ColumnInfo> {
+        let nullable = self.nullable(conn).unwrap_or_default();
+
+        let origins = match self.columns {
+            Some(origins) if origins.len() == names.len() => origins,
+            Some(_) => vec![None; names.len()],
+            None => names.iter().cloned().map(Some).collect(),
+        };
+
+        origins
+            .into_iter()
+            .map(|origin| {
+                let Some(origin) = origin else {
+                    return ColumnInfo::default();
+                };
+
+                // Identifiers are case insensitive
+                let found = nullable
+                    .iter()
+                    .find(|(name, _)| name.eq_ignore_ascii_case(&origin));
+
+                match found {
+                    Some((_, is_nullable)) => ColumnInfo {
+                        nullable: Some(*is_nullable),
+                        table: Some(self.table.clone()),
+                        column: Some(origin),
+                    },
+                    None => ColumnInfo::default(),
+                }
+            })
+            .collect()
+    }
+
+    // Columns of the table with their nullability
+    fn nullable(&self, conn: &Connection) -> Option<Vec<(String, bool)>> {
+        let mut stmt = conn
+            .prepare(
+                "SELECT column_name, is_nullable FROM duckdb_columns() \
+                 WHERE database_name = coalesce(nullif(?, ''), current_database()) \
+                 AND schema_name = coalesce(nullif(?, ''), current_schema()) \
+                 AND lower(table_name) = lower(?)",
+            )
+            .ok()?;
+
+        stmt.query_map([&self.catalog, &self.schema, &self.table], |row| {
+            Ok((row.get(0)?, row.get(1)?))
+        })
+        .ok()?
+        .collect::<Result<_, _>>()
+        .ok()
+    }
+}
@@ -0,0 +1,269 @@
+// SPDX-FileCopyrightText: 2025 Stas Muzhyk <sts@abc3.dev>
+// SPDX-FileCopyrightText: 2025 Łukasz Niemier <~@hauleth.dev>
+//
+// SPDX-License-Identifier: Apache-2.0
+
+use rustler::{NifMap, NifStruct, NifUnitEnum};
+
+use crate::constraint::{self, Constraint};
+use crate::split;
+
+#[derive(NifUnitEnum, Clone, Copy, PartialEq, Eq, Debug)]
+pub(crate) enum ErrorKind {
+    Syntax,
+    Catalog,
+    Binder,
+    Constraint,
+    Conversion,
+    InvalidInput,
+    Io,
+    Network,
+    Transaction,
+    OutOfMemory,
+    Permission,
+    NotImplemented,
+    Dependency,
+    Extension,
+    Signature,
+    NotFound,
+    Interrupted,
+    Timeout,
+    Busy,
+    Poisoned,
+    Closed,
+    Arity,
+    ReadOnly,
+    InvalidDate,
+    ResultTooLarge,
+    Motherduck,
+    Unauthorized,
+    InvalidEnum,
+    Internal,
+    Unknown,
+}
+
+impl ErrorKind {
+    /// SQLSTATE-like code, following PostgreSQL where possible
+    fn code(self) -> Option<&'static str> {
+        match self {
+            ErrorKind::Syntax => Some("42601"),
+            ErrorKind::Catalog => Some("42P01"),
+            ErrorKind::Binder => Some("42000"),
+            ErrorKind::Constraint => Some("23000"),
+            ErrorKind::Conversion => Some("22000"),
+            ErrorKind::InvalidInput => Some("22023"),
+            ErrorKind::Io => Some("58030"),
+            ErrorKind::Network => Some("08000"),
+            ErrorKind::Transaction => Some("25000"),
+            ErrorKind::OutOfMemory => Some("53200"),
+            ErrorKind::Permission => Some("42501"),
+            ErrorKind::NotImplemented => Some("0A000"),
+            ErrorKind::Dependency => Some("2BP01"),
+            ErrorKind::Interrupted | ErrorKind::Timeout => Some("57014"),
+            ErrorKind::Busy => Some("55006"),
+            ErrorKind::Closed => Some("08003"),
+            ErrorKind::Arity => Some("07001"),
+            ErrorKind::ReadOnly => Some("25006"),
+            ErrorKind::InvalidDate => Some("22008"),
+            ErrorKind::ResultTooLarge => Some("54000"),
+            ErrorKind::Motherduck => Some("08001"),
+            ErrorKind::Unauthorized => Some("28000"),
+            ErrorKind::InvalidEnum => Some("22P02"),
+            ErrorKind::Internal | ErrorKind::Poisoned => Some("XX000"),
+            ErrorKind::Extension
+            | ErrorKind::Signature
+            | ErrorKind::NotFound
+            | ErrorKind::Unknown => None,
+        }
+    }
+}
+
+// DuckDB prefixes error messages with the exception type, e.g. "Parser Error:"
+const DUCKDB_ERROR_TYPES: &[(&str, ErrorKind)] = &[
+    ("Parser Error:", ErrorKind::Syntax),
+    ("Syntax Error:", ErrorKind::Syntax),
+    ("Catalog Error:", ErrorKind::Catalog),
+    ("Binder Error:", ErrorKind::Binder),
+    ("Constraint Error:", ErrorKind::Constraint),
+    ("Conversion Error:", ErrorKind::Conversion),
+    ("Out of Range Error:", ErrorKind::Conversion),
+    ("Mismatch Type Error:", ErrorKind::Conversion),
+    ("Divide by Zero Error:", ErrorKind::Conversion),
+    ("Invalid Input Error:", ErrorKind::InvalidInput),
+    ("IO Error:", ErrorKind::Io),
+    ("HTTP Error:", ErrorKind::Network),
+    ("Network Error:", ErrorKind::Network),
+    ("TransactionContext Error:", ErrorKind::Transaction),
+    ("Transaction Error:", ErrorKind::Transaction),
+    ("Out of Memory Error:", ErrorKind::OutOfMemory),
+    ("Permission Error:", ErrorKind::Permission),
+    ("Not implemented Error:", ErrorKind::NotImplemented),
+    ("Dependency Error:", ErrorKind::Dependency),
+    ("Missing Extension Error:", ErrorKind::Extension),
+    ("Extension Error:", ErrorKind::Extension),
+    ("INTERRUPT Error:", ErrorKind::Interrupted),
+    ("INTERNAL Error:", ErrorKind::Internal),
+    ("FATAL Error:", ErrorKind::Internal),
+];
+
+// Errors of the types above told apart by their message
+const DUCKDB_ERRORS: &[(&str, ErrorKind)] = &[(
+    "transaction is launched in read-only mode",
+    ErrorKind::ReadOnly,
+)];
+
+/// Number of parameters statement expects compared to the bound ones
+#[derive(NifMap, Debug)]
+pub(crate) struct Arity {
+    expected: usize,
+    got: usize,
+    sql: String,
+}
+
+/// Value bound to ENUM parameter which is not one of its members
+#[derive(NifMap, Debug)]
+pub(crate) struct InvalidEnum {
+    // Position of the parameter, starting from 1
+    parameter: usize,
+    value: String,
+    allowed: Vec<String>,
+}
+
+/// Error returned from NIFs, encoded directly as `Duckex.Error` exception
+#[derive(NifStruct, Debug)]
+#[module = "Duckex.Error"]
+pub(crate) struct Error {
+    __exception__: bool,
+    pub(crate) kind: ErrorKind,
+    pub(crate) message: String,
+    code: Option<String>,
+    // Only set for constraint violations. Details are boxed, so errors stay
+    // small in results of the happy path.
+    constraint: Option<Box<Constraint>>,
+    // Only set when wrong number of parameters was bound
+    arity: Option<Box<Arity>>,
+    // Only set when value bound to ENUM parameter is not its member
+    invalid_enum: Option<Box<InvalidEnum>>,
+    // Filled in on the Elixir side
+    query: Option<String>,
+}
+
+impl Error {
+    pub(crate) fn new(kind: ErrorKind, message: impl Into<String>) -> Self {
+        Error {
+            __exception__: true,
+            kind,
+            message: message.into(),
+            code: kind.code().map(str::to_string),
+            constraint: None,
+            arity: None,
+            invalid_enum: None,
+            query: None,
+        }
+    }
+
+    pub(crate) fn busy() -> Self {
+        Error::new(ErrorKind::Busy, "Timed out waiting for the connection")
+    }
+
+    pub(crate) fn poisoned() -> Self {
+        Error::new(
+            ErrorKind::Poisoned,
+            "Connection state is inconsistent after a panic, it needs to be reopened",
+        )
+    }
+
+    pub(crate) fn closed() -> Self {
+        Error::new(ErrorKind::Closed, "Connection is closed")
+    }
+
+    pub(crate) fn timeout() -> Self {
+        Error::new(ErrorKind::Timeout, "Query timed out")
+    }
+
+    pub(crate) fn arity(expected: usize, got: usize, sql: &str) -> Self {
+        Error {
+            arity: Some(Box::new(Arity {
+                expected,
+                got,
+                sql: sql.to_string(),
+            })),
+            ..Error::new(
+                ErrorKind::Arity,
+                format!("Expected {} parameters, got {}", expected, got),
+            )
+        }
+    }
+
+    pub(crate) fn invalid_enum(parameter: usize, value: String, allowed: Vec<String>) -> Self {
+        let message = format!(
+            "Invalid value '{}' of ENUM parameter ${}, expected one of: {}",
+            value,
+            parameter,
+            allowed.join(", ")
+        );
+
+        Error {
+            invalid_enum: Some(Box::new(InvalidEnum {
+                parameter,
+                value,
+                allowed,
+            })),
+            ..Error::new(ErrorKind::InvalidEnum, message)
+        }
+    }
+
+    /// Fill in table of the violated constraint, when DuckDB does not name it
+    /// in the message, with the one changed by the statement that failed
+    pub(crate) fn in_statement(mut self, sql: &str) -> Self {
+        if let Some(constraint) = self.constraint.as_mut().filter(|c| c.table.is_none()) {
+            constraint.table = split::target_table(sql);
+        }
+
+        self
+    }
+}
+
+/// Classify DuckDB error message by the first exception type found in it
+fn classify(message: &str) -> ErrorKind {
+    if let Some((_, kind)) = DUCKDB_ERRORS
+        .iter()
+        .find(|(text, _)| message.contains(text))
+    {
+        return *kind;
+    }
+
+    DUCKDB_ERROR_TYPES
+        .iter()
+        .filter_map(|(prefix, kind)| message.find(prefix).map(|pos| (pos, *kind)))
+        .min_by_key(|(pos, _)| *pos)
+        .map(|(_, kind)| kind)
+        .unwrap_or(ErrorKind::Unknown)
+}
+
+impl From<String> for Error {
+    fn from(message: String) -> Self {
+        let kind = classify(&message);
+        let constraint = match kind {
+            ErrorKind::Constraint => constraint::parse(&message).map(Box::new),
+            _ => None,
+        };
+
+        Error {
+            constraint,
+            ..Error::new(kind, message)
+        }
+    }
+}
+
+impl From<(ErrorKind, String)> for Error {
+    fn from((kind, message): (ErrorKind, String)) -> Self {
+        Error::new(kind, message)
+    }
+}
+
+impl From<duckdb::Error> for Error {
+    fn from(error: duckdb::Error) -> Self {
+        Error::from(error.to_string())
+    }
+}
@@ -0,0 +1,80 @@
+// SPDX-FileCopyrightText: 2025 Stas Muzhyk <sts@abc3.dev>
+// SPDX-FileCopyrightText: 2025 Łukasz Niemier <~@hauleth.dev>
+//
+// SPDX-License-Identifier: Apache-2.0
+
+use duckdb::Config;
+
+use crate::error::{Error, ErrorKind};
+use crate::options::Options;
+use crate::sql::quote_literal;
+
+/// Database configuration with `:allow_unsigned_extensions` and
+/// `:custom_extension_repository` options applied, for loading extensions
+/// outside of the signed set from the default repository, and
+/// `:autoinstall_known_extensions` and `:autoload_known_extensions` toggles
+pub(crate) fn config(mut config: Config, opts: &Options) -> Result<Config, Error> {
+    if opts.get::<bool>("allow_unsigned_extensions")?.unwrap_or(false) {
+        config = config
+            .with("allow_unsigned_extensions", "true")
+            .map_err(|e| format!("Invalid value for option :allow_unsigned_extensions: {}", e))?;
+    }
+
+    if let Some(repository) = opts.get_string("custom_extension_repository")? {
+        config = config
+            .with("custom_extension_repository", &repository)
+            .map_err(|e| format!("Invalid value for option :custom_extension_repository: {}", e))?;
+    }
+
+    // Both are enabled by default, which fetches extensions from the network
+    // or loads them lazily on first use
+    for setting in ["autoinstall_known_extensions", "autoload_known_extensions"] {
+        if let Some(enabled) = opts.get::<bool>(setting)? {
+            config = config
+                .with(setting, enabled.to_string())
+                .map_err(|e| format!("Invalid value for option :{}: {}", setting, e))?;
+        }
+    }
+
+    Ok(config)
+}
+
+/// Build `INSTALL` statement for extension `name`
+pub(crate) fn install_sql(name: &str, opts: &Options) -> Result<String, Error> {
+    let force = opts.get::<bool>("force")?.unwrap_or(false);
+
+    let from = match opts.get_string("source")?.as_deref() {
+        None | Some("default") => String::new(),
+        Some("core") => " FROM core".to_string(),
+        Some("nightly") => " FROM core_nightly".to_string(),
+        Some("community") => " FROM community".to_string(),
+        Some(url) => format!(" FROM {}", quote_literal(url)),
+    };
+
+    let force = if force { "FORCE " } else { "" };
+
+    Ok(format!("{}INSTALL {}{}", force, quote_literal(name), from))
+}
+
+/// Build `LOAD` statement for extension `name`
+pub(crate) fn load_sql(name: &str) -> String {
+    format!("LOAD {}", quote_literal(name))
+}
+
+/// Tell apart the most common reasons for extension install/load failures
+pub(crate) fn error_kind(message: &str) -> ErrorKind {
+    let lower = message.to_lowercase();
+
+    if lower.contains("signature") {
+        ErrorKind::Signature
+    } else if lower.contains("404") || lower.contains("not found") || lower.contains("no such file") {
+        ErrorKind::NotFound
+    } else if lower.contains("http")
+        || lower.contains("could not establish connection")
+        || lower.contains("failed to download")
+    {
+        ErrorKind::Network
+    } else {
+        ErrorKind::Extension
+    }
+}
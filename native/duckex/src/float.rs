@@ -0,0 +1,71 @@
+// SPDX-FileCopyrightText: 2025 Stas Muzhyk <sts@abc3.dev>
+// SPDX-FileCopyrightText: 2025 Łukasz Niemier <~@hauleth.dev>
+//
+// SPDX-License-Identifier: Apache-2.0
+
+use duckdb::types::Value;
+use rustler::{Encoder, Env, Term};
+
+use crate::error::{Error, ErrorKind};
+
+mod atoms {
+    rustler::atoms! {
+        nan,
+        infinity,
+        neg_infinity,
+    }
+}
+
+/// Encode float, BEAM floats cannot hold NaN nor infinities, so these become
+/// `:nan`, `:infinity` and `:neg_infinity` atoms
+pub(crate) fn encode<'a>(env: Env<'a>, value: f64) -> Term<'a> {
+    if value.is_nan() {
+        atoms::nan().encode(env)
+    } else if value == f64::INFINITY {
+        atoms::infinity().encode(env)
+    } else if value == f64::NEG_INFINITY {
+        atoms::neg_infinity().encode(env)
+    } else {
+        value.encode(env)
+    }
+}
+
+/// Decode one of the atoms produced by `encode`
+pub(crate) fn decode(term: Term) -> Option<f64> {
+    let atom = term.decode::<rustler::Atom>().ok()?;
+
+    if atom == atoms::nan() {
+        Some(f64::NAN)
+    } else if atom == atoms::infinity() {
+        Some(f64::INFINITY)
+    } else if atom == atoms::neg_infinity() {
+        Some(f64::NEG_INFINITY)
+    } else {
+        None
+    }
+}
+
+/// Fails when value, or any value nested in it, is a non-finite float.
+/// Used by connections started with `strict_floats: true`.
+pub(crate) fn ensure_finite(value: &Value) -> Result<(), Error> {
+    match value {
+        Value::Float(f) => ensure_finite_f64(*f as f64),
+        Value::Double(f) => ensure_finite_f64(*f),
+        Value::List(values) | Value::Array(values) => values.iter().try_for_each(ensure_finite),
+        Value::Struct(fields) => fields.iter().try_for_each(|(_, v)| ensure_finite(v)),
+        Value::Map(entries) => entries.iter().try_for_each(|(_, v)| ensure_finite(v)),
+        Value::Union(value) => ensure_finite(value),
+        _ => Ok(()),
+    }
+}
+
+fn ensure_finite_f64(value: f64) -> Result<(), Error> {
+    if value.is_finite() {
+        Ok(())
+    } else {
+        Err(Error::new(
+            ErrorKind::Conversion,
+            format!("{} is not allowed with strict_floats", value),
+        ))
+    }
+}
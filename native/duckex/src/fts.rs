@@ -0,0 +1,51 @@
+// SPDX-FileCopyrightText: 2025 Stas Muzhyk <sts@abc3.dev>
+// SPDX-FileCopyrightText: 2025 Łukasz Niemier <~@hauleth.dev>
+//
+// SPDX-License-Identifier: Apache-2.0
+
+// Full-text search indexes of `fts` extension, which DuckDB autoloads on first
+// use of its pragmas
+
+use crate::error::Error;
+use crate::options::Options;
+use crate::sql::quote_literal;
+
+/// Build `PRAGMA create_fts_index` for `columns` of `table`, identified by
+/// `id` column
+pub(crate) fn create_sql(
+    table: &str,
+    id: &str,
+    columns: &[String],
+    opts: &Options,
+) -> Result<String, Error> {
+    if columns.is_empty() {
+        return Err("At least one column has to be indexed".to_string().into());
+    }
+
+    let mut args = vec![quote_literal(table), quote_literal(id)];
+    args.extend(columns.iter().map(|column| quote_literal(column)));
+
+    for key in opts.keys() {
+        match key {
+            "stemmer" | "stopwords" | "ignore" => {
+                if let Some(value) = opts.get_string(key)? {
+                    args.push(format!("{} = {}", key, quote_literal(&value)));
+                }
+            }
+            // Pragma takes flags as 0 or 1
+            "strip_accents" | "lower" | "overwrite" => {
+                if let Some(value) = opts.get::<bool>(key)? {
+                    args.push(format!("{} = {}", key, value as u8));
+                }
+            }
+            _ => return Err(format!("Unsupported FTS index option :{}", key).into()),
+        }
+    }
+
+    Ok(format!("PRAGMA create_fts_index({})", args.join(", ")))
+}
+
+/// Build `PRAGMA drop_fts_index` for `table`
+pub(crate) fn drop_sql(table: &str) -> String {
+    format!("PRAGMA drop_fts_index({})", quote_literal(table))
+}
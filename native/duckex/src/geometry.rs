@@ -0,0 +1,335 @@
+// SPDX-FileCopyrightText: 2025 Stas Muzhyk <sts@abc3.dev>
+// SPDX-FileCopyrightText: 2025 Łukasz Niemier <~@hauleth.dev>
+//
+// SPDX-License-Identifier: Apache-2.0
+
+// Spatial extension exports GEOMETRY values as WKB blobs tagged with GeoArrow
+// extension type, once its GeoArrow conversion is registered. Without it they
+// are blobs in the internal format of the extension.
+
+use arrow::datatypes::Schema;
+use duckdb::types::Value;
+use duckdb::Connection;
+use rustler::{Binary, Encoder, Env, NewBinary, NifUnitEnum, Term};
+use serde_json::{json, Value as Json};
+
+use crate::blob;
+use crate::duckdb_value_to_term;
+
+const EXTENSION_KEY: &str = "ARROW:extension:name";
+const EXTENSION_NAME: &str = "geoarrow.wkb";
+
+/// How GEOMETRY values are returned
+#[derive(NifUnitEnum, Clone, Copy, PartialEq, Eq, Default, Debug)]
+pub(crate) enum Format {
+    // Binaries with Well-Known Binary representation
+    #[default]
+    Wkb,
+    // Strings with Well-Known Text, e.g. "POINT (1 2)"
+    Wkt,
+    // Strings with GeoJSON geometry object
+    Geojson,
+}
+
+/// Make spatial extension export GEOMETRY values as WKB. Called after the
+/// extension is loaded, fails when it is an older version without GeoArrow
+/// support, which is fine.
+pub(crate) fn register(conn: &Connection) {
+    let _ = conn.execute_batch("CALL register_geoarrow_extensions()");
+}
+
+/// Which columns of the result hold GEOMETRY values
+pub(crate) fn columns(schema: &Schema) -> Vec<bool> {
+    schema
+        .fields()
+        .iter()
+        .map(|field| {
+            field
+                .metadata()
+                .get(EXTENSION_KEY)
+                .is_some_and(|name| name == EXTENSION_NAME)
+        })
+        .collect()
+}
+
+/// Encode value of GEOMETRY column. Values which are not valid WKB are
+/// returned as raw binaries.
+pub(crate) fn encode<'a>(
+    env: Env<'a>,
+    value: Value,
+    format: Format,
+    blob_format: blob::Format,
+) -> Term<'a> {
+    let Value::Blob(wkb) = value else {
+        return duckdb_value_to_term(env, value, blob_format);
+    };
+
+    let geometry = match format {
+        Format::Wkb => None,
+        Format::Wkt | Format::Geojson => Reader::new(&wkb).geometry(),
+    };
+
+    match (format, geometry) {
+        (Format::Wkt, Some(geometry)) => geometry.to_wkt().encode(env),
+        (Format::Geojson, Some(geometry)) => geometry.to_geojson().to_string().encode(env),
+        _ => {
+            let mut binary = NewBinary::new(env, wkb.len());
+            binary.as_mut_slice().copy_from_slice(&wkb);
+
+            Binary::from(binary).encode(env)
+        }
+    }
+}
+
+#[derive(Clone, Copy)]
+struct Dims {
+    z: bool,
+    m: bool,
+}
+
+enum Shape {
+    // Empty point has no coordinates
+    Point(Vec<f64>),
+    LineString(Vec<Vec<f64>>),
+    Polygon(Vec<Vec<Vec<f64>>>),
+    MultiPoint(Vec<Geometry>),
+    MultiLineString(Vec<Geometry>),
+    MultiPolygon(Vec<Geometry>),
+    Collection(Vec<Geometry>),
+}
+
+struct Geometry {
+    dims: Dims,
+    shape: Shape,
+}
+
+struct Reader<'a> {
+    bytes: &'a [u8],
+    pos: usize,
+    little_endian: bool,
+}
+
+impl<'a> Reader<'a> {
+    fn new(bytes: &'a [u8]) -> Self {
+        Reader {
+            bytes,
+            pos: 0,
+            little_endian: true,
+        }
+    }
+
+    fn take<const N: usize>(&mut self) -> Option<[u8; N]> {
+        let bytes = self.bytes.get(self.pos..self.pos + N)?.try_into().ok()?;
+        self.pos += N;
+
+        Some(bytes)
+    }
+
+    fn u32(&mut self) -> Option<u32> {
+        let bytes = self.take::<4>()?;
+
+        Some(if self.little_endian {
+            u32::from_le_bytes(bytes)
+        } else {
+            u32::from_be_bytes(bytes)
+        })
+    }
+
+    fn f64(&mut self) -> Option<f64> {
+        let bytes = self.take::<8>()?;
+
+        Some(if self.little_endian {
+            f64::from_le_bytes(bytes)
+        } else {
+            f64::from_be_bytes(bytes)
+        })
+    }
+
+    // Every nested geometry has its own byte order and type
+    fn geometry(&mut self) -> Option<Geometry> {
+        self.little_endian = match self.take::<1>()? {
+            [0] => false,
+            [1] => true,
+            _ => return None,
+        };
+
+        let code = self.u32()?;
+
+        // Both ISO (type + 1000 for Z, + 2000 for M) and extended (flag bits)
+        // dimensions are accepted
+        let iso = code & 0xFFFF;
+        let dims = Dims {
+            z: code & 0x8000_0000 != 0 || matches!(iso / 1000, 1 | 3),
+            m: code & 0x4000_0000 != 0 || matches!(iso / 1000, 2 | 3),
+        };
+
+        // Extended WKB may carry SRID, which is skipped
+        if code & 0x2000_0000 != 0 {
+            self.u32()?;
+        }
+
+        let shape = match iso % 1000 {
+            1 => {
+                let point = self.point(dims)?;
+
+                // Empty point is encoded with NaN coordinates
+                if point.iter().all(|c| c.is_nan()) {
+                    Shape::Point(vec![])
+                } else {
+                    Shape::Point(point)
+                }
+            }
+            2 => Shape::LineString(self.points(dims)?),
+            3 => Shape::Polygon(self.many(|reader| reader.points(dims))?),
+            4 => Shape::MultiPoint(self.many(Self::geometry)?),
+            5 => Shape::MultiLineString(self.many(Self::geometry)?),
+            6 => Shape::MultiPolygon(self.many(Self::geometry)?),
+            7 => Shape::Collection(self.many(Self::geometry)?),
+            _ => return None,
+        };
+
+        Some(Geometry { dims, shape })
+    }
+
+    fn point(&mut self, dims: Dims) -> Option<Vec<f64>> {
+        let count = 2 + dims.z as usize + dims.m as usize;
+
+        (0..count).map(|_| self.f64()).collect()
+    }
+
+    fn points(&mut self, dims: Dims) -> Option<Vec<Vec<f64>>> {
+        self.many(|reader| reader.point(dims))
+    }
+
+    fn many<T>(&mut self, mut item: impl FnMut(&mut Self) -> Option<T>) -> Option<Vec<T>> {
+        let count = self.u32()? as usize;
+
+        // Count is not trusted for preallocation, each item takes some bytes
+        let mut items = Vec::with_capacity(count.min(self.bytes.len() - self.pos));
+
+        for _ in 0..count {
+            items.push(item(self)?);
+        }
+
+        Some(items)
+    }
+}
+
+impl Geometry {
+    fn to_wkt(&self) -> String {
+        let (name, body) = match &self.shape {
+            Shape::Point(point) => ("POINT", (!point.is_empty()).then(|| wkt_point(point))),
+            Shape::LineString(points) => ("LINESTRING", wkt_points(points)),
+            Shape::Polygon(rings) => ("POLYGON", wkt_list(rings, |ring| wkt_points(ring))),
+            Shape::MultiPoint(points) => ("MULTIPOINT", wkt_list(points, Geometry::wkt_body)),
+            Shape::MultiLineString(lines) => {
+                ("MULTILINESTRING", wkt_list(lines, Geometry::wkt_body))
+            }
+            Shape::MultiPolygon(polygons) => {
+                ("MULTIPOLYGON", wkt_list(polygons, Geometry::wkt_body))
+            }
+            Shape::Collection(geometries) => (
+                "GEOMETRYCOLLECTION",
+                wkt_list(geometries, |geometry| Some(geometry.to_wkt())),
+            ),
+        };
+
+        let dims = match (self.dims.z, self.dims.m) {
+            (true, true) => " ZM",
+            (true, false) => " Z",
+            (false, true) => " M",
+            (false, false) => "",
+        };
+
+        match body {
+            Some(body) => format!("{}{} {}", name, dims, body),
+            None => format!("{}{} EMPTY", name, dims),
+        }
+    }
+
+    // Parenthesized part of WKT, `None` when the geometry is empty
+    fn wkt_body(&self) -> Option<String> {
+        match &self.shape {
+            Shape::Point(point) => (!point.is_empty()).then(|| wkt_point(point)),
+            Shape::LineString(points) => wkt_points(points),
+            Shape::Polygon(rings) => wkt_list(rings, |ring| wkt_points(ring)),
+            _ => None,
+        }
+    }
+
+    fn to_geojson(&self) -> Json {
+        match &self.shape {
+            Shape::Point(point) => json!({"type": "Point", "coordinates": self.position(point)}),
+            Shape::LineString(points) => {
+                json!({"type": "LineString", "coordinates": self.positions(points)})
+            }
+            Shape::Polygon(rings) => json!({
+                "type": "Polygon",
+                "coordinates": rings.iter().map(|ring| self.positions(ring)).collect::<Vec<_>>(),
+            }),
+            Shape::MultiPoint(points) => json!({
+                "type": "MultiPoint",
+                "coordinates": points.iter().map(Geometry::coordinates).collect::<Vec<_>>(),
+            }),
+            Shape::MultiLineString(lines) => json!({
+                "type": "MultiLineString",
+                "coordinates": lines.iter().map(Geometry::coordinates).collect::<Vec<_>>(),
+            }),
+            Shape::MultiPolygon(polygons) => json!({
+                "type": "MultiPolygon",
+                "coordinates": polygons.iter().map(Geometry::coordinates).collect::<Vec<_>>(),
+            }),
+            Shape::Collection(geometries) => json!({
+                "type": "GeometryCollection",
+                "geometries": geometries.iter().map(Geometry::to_geojson).collect::<Vec<_>>(),
+            }),
+        }
+    }
+
+    fn coordinates(&self) -> Json {
+        let mut geojson = self.to_geojson();
+
+        geojson["coordinates"].take()
+    }
+
+    // GeoJSON has no M coordinate, so it is dropped
+    fn position(&self, point: &[f64]) -> Json {
+        let len = if self.dims.z { 3 } else { 2 };
+
+        json!(point.iter().take(len).collect::<Vec<_>>())
+    }
+
+    fn positions(&self, points: &[Vec<f64>]) -> Json {
+        json!(points
+            .iter()
+            .map(|point| self.position(point))
+            .collect::<Vec<_>>())
+    }
+}
+
+fn wkt_point(point: &[f64]) -> String {
+    let coords: Vec<String> = point.iter().map(f64::to_string).collect();
+
+    format!("({})", coords.join(" "))
+}
+
+fn wkt_points(points: &[Vec<f64>]) -> Option<String> {
+    let coords: Vec<String> = points
+        .iter()
+        .map(|point| {
+            let coords: Vec<String> = point.iter().map(f64::to_string).collect();
+            coords.join(" ")
+        })
+        .collect();
+
+    (!coords.is_empty()).then(|| format!("({})", coords.join(", ")))
+}
+
+fn wkt_list<T>(items: &[T], body: impl Fn(&T) -> Option<String>) -> Option<String> {
+    let parts: Vec<String> = items
+        .iter()
+        .map(|item| body(item).unwrap_or_else(|| "EMPTY".to_string()))
+        .collect();
+
+    (!parts.is_empty()).then(|| format!("({})", parts.join(", ")))
+}
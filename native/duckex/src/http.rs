@@ -0,0 +1,78 @@
+// SPDX-FileCopyrightText: 2025 Stas Muzhyk <sts@abc3.dev>
+// SPDX-FileCopyrightText: 2025 Łukasz Niemier <~@hauleth.dev>
+//
+// SPDX-License-Identifier: Apache-2.0
+
+// Settings of `httpfs` extension, which DuckDB autoloads on first `SET` of
+// any of them
+
+use duckdb::Connection;
+
+use crate::error::{Error, ErrorKind};
+use crate::options::Options;
+use crate::sql::quote_literal;
+
+enum Kind {
+    Integer,
+    Float,
+    Boolean,
+    Text,
+}
+
+/// Option name, the corresponding setting and its type
+const SETTINGS: &[(&str, &str, Kind)] = &[
+    ("timeout", "http_timeout", Kind::Integer),
+    ("retries", "http_retries", Kind::Integer),
+    ("retry_wait_ms", "http_retry_wait_ms", Kind::Integer),
+    ("retry_backoff", "http_retry_backoff", Kind::Float),
+    ("keep_alive", "http_keep_alive", Kind::Boolean),
+    ("proxy", "http_proxy", Kind::Text),
+    ("proxy_username", "http_proxy_username", Kind::Text),
+    ("proxy_password", "http_proxy_password", Kind::Text),
+];
+
+/// Build `SET` statements for the given options, all of them are validated
+/// before anything is set
+pub(crate) fn set_sql(opts: &Options) -> Result<Vec<(&'static str, String)>, Error> {
+    let mut statements = vec![];
+
+    for key in opts.keys() {
+        let (_, setting, kind) = SETTINGS.iter().find(|(k, _, _)| *k == key).ok_or_else(|| {
+            Error::new(
+                ErrorKind::InvalidInput,
+                format!("Unsupported HTTP option :{}", key),
+            )
+        })?;
+
+        let value = match kind {
+            Kind::Integer => opts.get::<u64>(key)?.map(|value| value.to_string()),
+            // Integers are accepted as well, e.g. `retry_backoff: 2`
+            Kind::Float => match opts.get::<f64>(key) {
+                Ok(value) => value.map(|value| value.to_string()),
+                Err(_) => opts.get::<u64>(key)?.map(|value| value.to_string()),
+            },
+            Kind::Boolean => opts.get::<bool>(key)?.map(|value| value.to_string()),
+            Kind::Text => opts.get_string(key)?.map(|value| quote_literal(&value)),
+        };
+
+        if let Some(value) = value {
+            statements.push((*setting, format!("SET {} = {}", setting, value)));
+        }
+    }
+
+    Ok(statements)
+}
+
+/// Apply statements built with `set_sql` to the connection
+pub(crate) fn configure(
+    conn: &Connection,
+    statements: &[(&'static str, String)],
+) -> Result<(), Error> {
+    for (setting, sql) in statements {
+        // Statement is left out of the message, as it may contain proxy password
+        conn.execute_batch(sql)
+            .map_err(|e| format!("Failed to set '{}': {}", setting, e))?;
+    }
+
+    Ok(())
+}
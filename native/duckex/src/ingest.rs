@@ -0,0 +1,411 @@
+// SPDX-FileCopyrightText: 2025 Stas Muzhyk <sts@abc3.dev>
+// SPDX-FileCopyrightText: 2025 Łukasz Niemier <~@hauleth.dev>
+//
+// SPDX-License-Identifier: Apache-2.0
+
+use std::io::Write;
+use std::sync::Mutex;
+
+use duckdb::types::Value;
+use duckdb::Connection;
+use flate2::write::MultiGzDecoder;
+use rustler::ResourceArc;
+
+use crate::appender::AppenderResource;
+use crate::copy::CopyFormat;
+use crate::error::{Error, ErrorKind};
+use crate::options::Options;
+use crate::sql::quote_identifier;
+use crate::{lock, DuckDBResource};
+
+// Magic numbers the compressed data starts with
+const GZIP_MAGIC: &[u8] = &[0x1f, 0x8b];
+const ZSTD_MAGIC: &[u8] = &[0x28, 0xb5, 0x2f, 0xfd];
+
+/// CSV or newline-delimited JSON fed across multiple NIF calls.
+///
+/// Chunks are decompressed and parsed as they arrive, complete rows are
+/// streamed into the table with appender, only the incomplete row at the end
+/// of the chunk is kept until the next one. Nothing is written to disk.
+///
+/// Values are appended as text, DuckDB casts them to the column types.
+pub struct IngestResource {
+    appender: AppenderResource,
+    // `None` once the ingest is finished
+    decoder: Mutex<Option<Decoder>>,
+}
+
+impl IngestResource {
+    pub(crate) fn new(
+        db: ResourceArc<DuckDBResource>,
+        table: String,
+        format: CopyFormat,
+        opts: &Options,
+    ) -> Result<Self, Error> {
+        let format = match format {
+            CopyFormat::Csv => Format::Csv {
+                delimiter: delimiter(opts)?,
+                header: opts.get::<bool>("header")?.unwrap_or(false),
+            },
+            CopyFormat::Json => {
+                if let Some(key) = opts
+                    .keys()
+                    .find(|key| matches!(*key, "header" | "delimiter"))
+                {
+                    return Err(format!("Option :{} is only supported for CSV", key).into());
+                }
+
+                Format::Json
+            }
+            CopyFormat::Parquet => {
+                return Err(Error::new(
+                    ErrorKind::InvalidInput,
+                    "Parquet cannot be ingested in chunks, as its metadata is at the end",
+                ))
+            }
+        };
+
+        let inflate = match opts.get_string("compression")?.as_deref() {
+            None | Some("auto") => Inflate::Detect(Vec::new()),
+            Some("none") => Inflate::Plain,
+            Some("gzip") => Inflate::gzip(),
+            Some("zstd") => Inflate::zstd()?,
+            Some(other) => return Err(format!("Unsupported compression: {}", other).into()),
+        };
+
+        let appender = AppenderResource::new(db.clone(), table.clone(), opts)?;
+        let columns = db.with_conn(move |conn| columns(conn, &table))?;
+
+        Ok(IngestResource {
+            appender,
+            decoder: Mutex::new(Some(Decoder {
+                inflate,
+                format,
+                columns,
+                pending: Vec::new(),
+                scanned: 0,
+                quoted: false,
+            })),
+        })
+    }
+
+    pub(crate) fn write(&self, data: &[u8]) -> Result<(), Error> {
+        // Held while appending, so rows of the chunks keep their order
+        let mut decoder = lock::acquire(&self.decoder, None)?;
+        let rows = decoder.as_mut().ok_or_else(Error::closed)?.write(data)?;
+
+        if !rows.is_empty() {
+            self.appender.append(rows)?;
+        }
+
+        Ok(())
+    }
+
+    /// Load the rest of the data into the table, ingest cannot be used
+    /// afterwards. Returns number of loaded rows.
+    pub(crate) fn finish(&self) -> Result<u64, Error> {
+        let mut decoder = lock::acquire(&self.decoder, None)?
+            .take()
+            .ok_or_else(Error::closed)?;
+
+        self.appender.append(decoder.finish()?)?;
+        self.appender.close()
+    }
+}
+
+enum Format {
+    Csv { delimiter: u8, header: bool },
+    Json,
+}
+
+enum Inflate {
+    // Not known yet, data is kept until there is enough to detect it
+    Detect(Vec<u8>),
+    Plain,
+    Gzip(MultiGzDecoder<Vec<u8>>),
+    Zstd(zstd::stream::write::Decoder<'static, Vec<u8>>),
+}
+
+impl Inflate {
+    fn gzip() -> Self {
+        Inflate::Gzip(MultiGzDecoder::new(Vec::new()))
+    }
+
+    fn zstd() -> Result<Self, Error> {
+        zstd::stream::write::Decoder::new(Vec::new())
+            .map(Inflate::Zstd)
+            .map_err(io_error)
+    }
+
+    /// Decompressed data, `last` once there is no more data to come
+    fn decode(&mut self, data: &[u8], last: bool) -> Result<Vec<u8>, Error> {
+        if let Inflate::Detect(buffer) = self {
+            buffer.extend_from_slice(data);
+
+            if buffer.len() < ZSTD_MAGIC.len() && !last {
+                return Ok(Vec::new());
+            }
+
+            let buffer = std::mem::take(buffer);
+
+            *self = if buffer.starts_with(GZIP_MAGIC) {
+                Inflate::gzip()
+            } else if buffer.starts_with(ZSTD_MAGIC) {
+                Inflate::zstd()?
+            } else {
+                Inflate::Plain
+            };
+
+            return self.decode(&buffer, last);
+        }
+
+        let output = match self {
+            Inflate::Detect(_) => unreachable!("compression is detected above"),
+            Inflate::Plain => return Ok(data.to_vec()),
+            Inflate::Gzip(decoder) => {
+                decoder.write_all(data).map_err(io_error)?;
+
+                if last {
+                    decoder.try_finish().map_err(io_error)?;
+                }
+
+                decoder.get_mut()
+            }
+            Inflate::Zstd(decoder) => {
+                decoder.write_all(data).map_err(io_error)?;
+
+                if last {
+                    decoder.flush().map_err(io_error)?;
+                }
+
+                decoder.get_mut()
+            }
+        };
+
+        Ok(std::mem::take(output))
+    }
+}
+
+struct Decoder {
+    inflate: Inflate,
+    format: Format,
+    columns: Vec<String>,
+    // Decompressed data not parsed yet, as the row is not complete
+    pending: Vec<u8>,
+    // How much of `pending` was searched for the end of row already, and
+    // whether it ends inside quoted CSV field
+    scanned: usize,
+    quoted: bool,
+}
+
+impl Decoder {
+    /// Rows completed by the chunk
+    fn write(&mut self, data: &[u8]) -> Result<Vec<Vec<Value>>, Error> {
+        let data = self.inflate.decode(data, false)?;
+        self.pending.extend_from_slice(&data);
+
+        self.rows(false)
+    }
+
+    /// Rest of the rows, including the last one not ended with newline
+    fn finish(&mut self) -> Result<Vec<Vec<Value>>, Error> {
+        let data = self.inflate.decode(&[], true)?;
+        self.pending.extend_from_slice(&data);
+
+        self.rows(true)
+    }
+
+    fn rows(&mut self, last: bool) -> Result<Vec<Vec<Value>>, Error> {
+        let end = if last {
+            if self.quoted_at_end() {
+                return Err(invalid("Unterminated quoted field in CSV data"));
+            }
+
+            self.pending.len()
+        } else {
+            match self.complete() {
+                Some(end) => end,
+                None => return Ok(Vec::new()),
+            }
+        };
+
+        let data: Vec<u8> = self.pending.drain(..end).collect();
+        self.scanned -= end.min(self.scanned);
+
+        match self.format {
+            Format::Csv {
+                delimiter,
+                ref mut header,
+            } => {
+                let mut records = csv_records(&data, delimiter)?;
+
+                if *header && !records.is_empty() {
+                    records.remove(0);
+                    *header = false;
+                }
+
+                for record in &records {
+                    if record.len() != self.columns.len() {
+                        return Err(invalid(format!(
+                            "CSV row has {} values, expected {}",
+                            record.len(),
+                            self.columns.len()
+                        )));
+                    }
+                }
+
+                Ok(records)
+            }
+            Format::Json => data
+                .split(|byte| *byte == b'\n')
+                .filter(|line| !line.trim_ascii().is_empty())
+                .map(|line| json_row(line, &self.columns))
+                .collect(),
+        }
+    }
+
+    // End of the last complete row in `pending`, CSV rows end with newline
+    // outside of quotes
+    fn complete(&mut self) -> Option<usize> {
+        match self.format {
+            Format::Csv { .. } => {
+                let mut end = None;
+
+                for (i, byte) in self.pending.iter().enumerate().skip(self.scanned) {
+                    match byte {
+                        b'"' => self.quoted = !self.quoted,
+                        b'\n' if !self.quoted => end = Some(i + 1),
+                        _ => {}
+                    }
+                }
+
+                self.scanned = self.pending.len();
+
+                end
+            }
+            Format::Json => self
+                .pending
+                .iter()
+                .rposition(|byte| *byte == b'\n')
+                .map(|i| i + 1),
+        }
+    }
+
+    fn quoted_at_end(&mut self) -> bool {
+        self.complete();
+
+        matches!(self.format, Format::Csv { .. }) && self.quoted
+    }
+}
+
+// Records of complete CSV rows. Empty fields are `NULL`, unless quoted, and
+// doubled quotes inside quoted fields are unescaped, same as DuckDB does.
+// Every quote toggles quoting, the same as when looking for the end of row.
+fn csv_records(data: &[u8], delimiter: u8) -> Result<Vec<Vec<Value>>, Error> {
+    let mut records = Vec::new();
+    let mut record = Vec::new();
+    let mut field = Vec::new();
+    let (mut quoted, mut in_quotes) = (false, false);
+    let mut previous = None;
+    let mut bytes = data.iter().copied().peekable();
+
+    while let Some(byte) = bytes.next() {
+        match byte {
+            b'"' => {
+                if !in_quotes && previous == Some(b'"') {
+                    field.push(b'"');
+                }
+
+                quoted = true;
+                in_quotes = !in_quotes;
+            }
+            b'\r' if !in_quotes && bytes.peek() == Some(&b'\n') => {}
+            b'\n' if !in_quotes => {
+                // Blank lines are skipped
+                if !record.is_empty() || !field.is_empty() || quoted {
+                    record.push(csv_value(std::mem::take(&mut field), quoted)?);
+                    records.push(std::mem::take(&mut record));
+                }
+
+                quoted = false;
+            }
+            byte if byte == delimiter && !in_quotes => {
+                record.push(csv_value(std::mem::take(&mut field), quoted)?);
+                quoted = false;
+            }
+            byte => field.push(byte),
+        }
+
+        previous = Some(byte);
+    }
+
+    if !record.is_empty() || !field.is_empty() || quoted {
+        record.push(csv_value(field, quoted)?);
+        records.push(record);
+    }
+
+    Ok(records)
+}
+
+fn csv_value(field: Vec<u8>, quoted: bool) -> Result<Value, Error> {
+    if field.is_empty() && !quoted {
+        return Ok(Value::Null);
+    }
+
+    String::from_utf8(field)
+        .map(Value::Text)
+        .map_err(|_| invalid("CSV data is not valid UTF-8"))
+}
+
+// Row of the JSON object with values in the order of the table columns,
+// missing keys are `NULL` and keys not matching any column are ignored
+fn json_row(line: &[u8], columns: &[String]) -> Result<Vec<Value>, Error> {
+    let mut object: serde_json::Map<String, serde_json::Value> =
+        serde_json::from_slice(line).map_err(|e| invalid(format!("Invalid JSON object: {}", e)))?;
+
+    Ok(columns
+        .iter()
+        .map(|column| match object.remove(column) {
+            None | Some(serde_json::Value::Null) => Value::Null,
+            Some(serde_json::Value::Bool(value)) => Value::Boolean(value),
+            Some(serde_json::Value::String(value)) => Value::Text(value),
+            // Numbers, arrays and objects are cast from their JSON text
+            Some(value) => Value::Text(value.to_string()),
+        })
+        .collect())
+}
+
+fn columns(conn: &Connection, table: &str) -> Result<Vec<String>, Error> {
+    let sql = format!("SELECT * FROM {} LIMIT 0", quote_identifier(table));
+    let describe = |e: duckdb::Error| format!("Failed to describe '{}': {}", table, e);
+
+    let mut stmt = conn.prepare(&sql).map_err(describe)?;
+    let rows = stmt.query([]).map_err(describe)?;
+
+    Ok(rows
+        .as_ref()
+        .map(|stmt| stmt.column_names())
+        .unwrap_or_default())
+}
+
+fn delimiter(opts: &Options) -> Result<u8, Error> {
+    match opts.get_string("delimiter")? {
+        None => Ok(b','),
+        Some(delimiter) if delimiter.len() == 1 => Ok(delimiter.as_bytes()[0]),
+        Some(delimiter) => Err(invalid(format!(
+            "Delimiter must be single byte, got: {:?}",
+            delimiter
+        ))),
+    }
+}
+
+fn invalid(message: impl Into<String>) -> Error {
+    Error::new(ErrorKind::InvalidInput, message)
+}
+
+fn io_error(e: std::io::Error) -> Error {
+    Error::new(
+        ErrorKind::Io,
+        format!("Failed to decompress ingest data: {}", e),
+    )
+}
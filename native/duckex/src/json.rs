@@ -0,0 +1,39 @@
+// SPDX-FileCopyrightText: 2025 Stas Muzhyk <sts@abc3.dev>
+// SPDX-FileCopyrightText: 2025 Łukasz Niemier <~@hauleth.dev>
+//
+// SPDX-License-Identifier: Apache-2.0
+
+use rustler::{Encoder, Env, Term};
+use serde_json::Value as Json;
+
+/// Convert JSON value into Elixir term, objects become maps with string keys
+pub(crate) fn to_term<'a>(env: Env<'a>, value: &Json) -> Term<'a> {
+    match value {
+        Json::Null => rustler::types::atom::nil().encode(env),
+        Json::Bool(b) => b.encode(env),
+        Json::Number(n) => {
+            if let Some(i) = n.as_i64() {
+                i.encode(env)
+            } else if let Some(u) = n.as_u64() {
+                u.encode(env)
+            } else {
+                n.as_f64().unwrap_or_default().encode(env)
+            }
+        }
+        Json::String(s) => s.encode(env),
+        Json::Array(vec) => vec
+            .iter()
+            .map(|v| to_term(env, v))
+            .collect::<Vec<_>>()
+            .encode(env),
+        Json::Object(obj) => {
+            let (keys, values): (Vec<_>, Vec<_>) = obj
+                .iter()
+                .map(|(k, v)| (k.encode(env), to_term(env, v)))
+                .unzip();
+
+            Term::map_from_term_arrays(env, &keys, &values)
+                .unwrap_or_else(|_| rustler::types::atom::nil().encode(env))
+        }
+    }
+}
@@ -0,0 +1,58 @@
+// SPDX-FileCopyrightText: 2025 Stas Muzhyk <sts@abc3.dev>
+// SPDX-FileCopyrightText: 2025 Łukasz Niemier <~@hauleth.dev>
+//
+// SPDX-License-Identifier: Apache-2.0
+
+use arrow::datatypes::DataType;
+use duckdb::{ffi, Statement};
+use rustler::NifUnitEnum;
+
+use crate::capi;
+
+/// Kind of the statement, telling whether result holds returned rows or just
+/// the count of changed ones
+#[derive(NifUnitEnum, Clone, Copy, PartialEq, Eq, Debug)]
+pub(crate) enum Kind {
+    Select,
+    Insert,
+    Update,
+    Delete,
+    Ddl,
+    Transaction,
+    Other,
+}
+
+impl Kind {
+    /// Whether statement changes rows, so it has count of affected ones
+    pub(crate) fn is_dml(self) -> bool {
+        matches!(self, Kind::Insert | Kind::Update | Kind::Delete)
+    }
+}
+
+/// Whether changing statement returned changed rows with `RETURNING` clause,
+/// told by its result, which otherwise is single `BIGINT` column `Count`
+pub(crate) fn is_returning(columns: &[(String, DataType)]) -> bool {
+    !matches!(columns, [(name, DataType::Int64)] if name == "Count")
+}
+
+/// Classify prepared statement by the type DuckDB parsed it as, so also
+/// statements starting with common table expressions or comments are told
+/// apart, e.g. `INSERT` of `WITH t AS (...) INSERT ...`
+pub(crate) fn classify(stmt: &Statement) -> Kind {
+    match capi::statement_type(stmt) {
+        // `SHOW`, `DESCRIBE`, `SUMMARIZE` and `PIVOT` are parsed as `SELECT`
+        ffi::duckdb_statement_type_DUCKDB_STATEMENT_TYPE_SELECT
+        | ffi::duckdb_statement_type_DUCKDB_STATEMENT_TYPE_EXPLAIN => Kind::Select,
+        ffi::duckdb_statement_type_DUCKDB_STATEMENT_TYPE_INSERT => Kind::Insert,
+        ffi::duckdb_statement_type_DUCKDB_STATEMENT_TYPE_UPDATE => Kind::Update,
+        // `TRUNCATE` as well
+        ffi::duckdb_statement_type_DUCKDB_STATEMENT_TYPE_DELETE => Kind::Delete,
+        // `COMMENT ON` is parsed as `ALTER`
+        ffi::duckdb_statement_type_DUCKDB_STATEMENT_TYPE_CREATE
+        | ffi::duckdb_statement_type_DUCKDB_STATEMENT_TYPE_CREATE_FUNC
+        | ffi::duckdb_statement_type_DUCKDB_STATEMENT_TYPE_DROP
+        | ffi::duckdb_statement_type_DUCKDB_STATEMENT_TYPE_ALTER => Kind::Ddl,
+        ffi::duckdb_statement_type_DUCKDB_STATEMENT_TYPE_TRANSACTION => Kind::Transaction,
+        _ => Kind::Other,
+    }
+}
@@ -0,0 +1,106 @@
+// SPDX-FileCopyrightText: 2025 Stas Muzhyk <sts@abc3.dev>
+// SPDX-FileCopyrightText: 2025 Łukasz Niemier <~@hauleth.dev>
+//
+// SPDX-License-Identifier: Apache-2.0
+
+// Attaching Iceberg catalogs and Delta Lake tables through `iceberg` and
+// `delta` extensions
+
+use duckdb::Connection;
+
+use crate::error::{Error, ErrorKind};
+use crate::extension;
+use crate::options::Options;
+use crate::sql::{quote_identifier, quote_literal};
+
+/// Install (no-op when already installed) and load extension `name`
+pub(crate) fn ensure_extension(conn: &Connection, name: &str) -> Result<(), Error> {
+    let sql = format!("INSTALL {}; {}", quote_literal(name), extension::load_sql(name));
+
+    conn.execute_batch(&sql).map_err(|e| {
+        let message = e.to_string();
+        Error::new(
+            extension::error_kind(&message),
+            format!("Failed to load extension '{}': {}", name, message),
+        )
+    })
+}
+
+/// Option name and the corresponding `ATTACH` option of Iceberg catalog
+const ICEBERG_OPTIONS: &[(&str, &str)] = &[
+    ("endpoint", "ENDPOINT"),
+    ("endpoint_type", "ENDPOINT_TYPE"),
+    ("authorization_type", "AUTHORIZATION_TYPE"),
+    ("default_region", "DEFAULT_REGION"),
+    ("secret", "SECRET"),
+];
+
+/// Build `ATTACH` statement of Iceberg REST catalog `warehouse`, using secret
+/// `secret` when credentials were given
+pub(crate) fn iceberg_sql(
+    warehouse: &str,
+    secret: Option<&str>,
+    opts: &Options,
+) -> Result<String, Error> {
+    let mut parts = vec!["TYPE iceberg".to_string()];
+
+    for key in opts.keys() {
+        if let Some((_, option)) = ICEBERG_OPTIONS.iter().find(|(k, _)| *k == key) {
+            if let Some(value) = opts.get_string(key)? {
+                parts.push(format!("{} {}", option, quote_literal(&value)));
+            }
+        } else if key != "as" {
+            return Err(format!("Unsupported Iceberg option :{}", key).into());
+        }
+    }
+
+    if let Some(secret) = secret {
+        parts.push(format!("SECRET {}", quote_literal(secret)));
+    }
+
+    Ok(format!("{} ({})", attach_sql(warehouse, opts)?, parts.join(", ")))
+}
+
+/// Build `ATTACH` statement of Delta table at `path`
+pub(crate) fn delta_sql(path: &str, opts: &Options) -> Result<String, Error> {
+    let mut parts = vec!["TYPE delta".to_string()];
+
+    for key in opts.keys() {
+        match key {
+            "pin_snapshot" => {
+                if opts.get::<bool>(key)?.unwrap_or(false) {
+                    parts.push("PIN_SNAPSHOT".to_string());
+                }
+            }
+            "as" => {}
+            _ => return Err(format!("Unsupported Delta option :{}", key).into()),
+        }
+    }
+
+    Ok(format!("{} ({})", attach_sql(path, opts)?, parts.join(", ")))
+}
+
+fn attach_sql(path: &str, opts: &Options) -> Result<String, Error> {
+    let mut sql = format!("ATTACH {}", quote_literal(path));
+
+    if let Some(alias) = opts.get_string("as")? {
+        sql.push_str(&format!(" AS {}", quote_identifier(&alias)));
+    }
+
+    Ok(sql)
+}
+
+/// Catalogs report rejected credentials as plain HTTP or IO errors, tell them
+/// apart so callers can react to expired tokens
+pub(crate) fn error(message: String) -> Error {
+    let lower = message.to_lowercase();
+
+    if ["401", "403", "unauthorized", "forbidden", "invalid_client", "access denied"]
+        .iter()
+        .any(|pattern| lower.contains(pattern))
+    {
+        Error::new(ErrorKind::Unauthorized, message)
+    } else {
+        Error::from(message)
+    }
+}
@@ -5,23 +5,185 @@
 
 #![allow(non_local_definitions)]
 
-use std::sync::Mutex;
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::{Arc, Mutex, MutexGuard};
+use std::time::{Duration, Instant};
 
+use arrow::datatypes::{DataType, SchemaRef};
+use arrow::record_batch::RecordBatch;
 use base64::{engine::general_purpose, Engine as _};
 
-use duckdb::arrow::datatypes::DataType;
 use duckdb::params_from_iter;
-use duckdb::types::Value;
-use duckdb::Connection;
+use duckdb::types::{Value, ValueRef};
+use duckdb::{Connection, InterruptHandle};
 
-use rustler::{Encoder, Env, NifStruct, ResourceArc, Term};
+use rustler::{
+    Binary, Encoder, Env, LocalPid, NifMap, NifStruct, NifUnitEnum, OwnedBinary, OwnedEnv,
+    ResourceArc, Term,
+};
 
+mod appender;
+mod blob;
 mod cache;
+mod caller;
+mod capi;
+mod column_type;
+mod constraint;
+mod copy;
+mod csv;
+mod database;
+mod datetime;
+mod describe;
+mod error;
+mod extension;
+mod float;
+mod fts;
+mod geometry;
+mod http;
+mod ingest;
+mod ipc;
+mod json;
+mod kind;
+mod lakehouse;
+mod limit;
+mod lock;
+mod macros;
+mod memory;
+mod motherduck;
+mod ndjson;
+mod options;
+mod output;
+mod panic;
+mod params;
+mod progress;
+mod reader;
+mod register;
+mod result;
+mod secret;
+mod setting;
+mod shared;
+mod shutdown;
+mod spill;
+mod split;
+mod spool;
+mod sql;
+mod statement;
+mod status;
+mod stream;
+mod temp;
+mod tensor;
+mod timeslice;
+mod timezone;
+mod transaction;
+mod union;
+mod uri;
+mod validate;
+mod varint;
+mod watchdog;
+mod worker;
+
+mod atoms {
+    rustler::atoms! {
+        ok,
+        error,
+        duckex_result,
+        duckex_query,
+    }
+}
 
 // Resource to hold the DuckDB connection and prepared query strings
 pub struct DuckDBResource {
-    conn: Mutex<Connection>,
-    queries: Mutex<cache::Cache<String>>,
+    // Thread owning the connection, every call on it runs there. Connection is
+    // taken out once it is explicitly closed, or when the VM shuts down, see
+    // `shutdown::close_all`
+    worker: Arc<worker::Worker>,
+    // Path the database was opened with, `:memory:` for in-memory ones
+    database: String,
+    // Shared with statement handles, which hold weak reference to it
+    queries: Arc<Mutex<cache::Cache<String>>>,
+    // File DuckDB writes JSON profile of the last query to, when enabled
+    profile: Mutex<Option<temp::TempFile>>,
+    progress: progress::Progress,
+    // How long to wait for the connection and the locks above, indefinitely
+    // when not set
+    lock_timeout: Option<Duration>,
+    // Set when a panic happened while holding one of the locks, as connection
+    // state can be inconsistent from then on and it needs to be reopened
+    poisoned: AtomicBool,
+    // Whether transaction is open, DuckDB does not nest them
+    transaction: AtomicBool,
+    // Fail queries returning NaN or infinity instead of encoding them as atoms
+    strict_floats: bool,
+    // Reject parameters which are not valid UTF-8, instead of binding them as
+    // blobs
+    strict_text: bool,
+    // Process notified about every executed query
+    log_handler: Mutex<Option<LocalPid>>,
+    // Defaults for queries not setting their own limits
+    limits: limit::Limits,
+    // Highest memory usage reported by `memory_stats`
+    peak_memory: AtomicU64,
+    // Interrupts queries of `execute_async` callers which exited, without
+    // waiting for the worker running the query
+    interrupt: Arc<InterruptHandle>,
+    // Connection clones running queries submitted with `read`
+    readers: reader::Readers,
+    // Named in-memory database the connection keeps alive
+    _shared: Option<Arc<shared::Database>>,
+}
+
+impl DuckDBResource {
+    /// Run `job` on the connection in the worker thread and wait for its
+    /// result. Callers block, so they have to run on dirty schedulers.
+    fn with_conn<T: Send + 'static>(
+        &self,
+        job: impl FnOnce(&mut Connection) -> Result<T, error::Error> + Send + 'static,
+    ) -> Result<T, error::Error> {
+        self.ensure_not_poisoned()?;
+
+        self.worker
+            .run(self.lock_timeout, |conn| match conn {
+                Some(conn) => job(conn),
+                None => Err(error::Error::closed()),
+            })
+            .inspect_err(|e| self.mark_poisoned(e))
+    }
+
+    // Query of the prepared statement, the cache is not locked while it runs
+    fn query(&self, stmt_id: u64) -> Result<String, error::Error> {
+        self.lock_queries()?
+            .get_ref(stmt_id)
+            .cloned()
+            .ok_or_else(|| "Invalid cache index".to_string().into())
+    }
+
+    fn lock_queries(&self) -> Result<MutexGuard<'_, cache::Cache<String>>, error::Error> {
+        self.ensure_not_poisoned()?;
+
+        lock::acquire(&self.queries, self.lock_timeout).inspect_err(|e| self.mark_poisoned(e))
+    }
+
+    fn ensure_not_poisoned(&self) -> Result<(), error::Error> {
+        if self.poisoned.load(Ordering::Acquire) || self.worker.panicked() {
+            Err(error::Error::poisoned())
+        } else {
+            Ok(())
+        }
+    }
+
+    fn mark_poisoned(&self, error: &error::Error) {
+        if error.kind == error::ErrorKind::Poisoned {
+            self.poisoned.store(true, Ordering::Release);
+        }
+    }
+
+    fn in_transaction(&self) -> bool {
+        self.transaction.load(Ordering::Acquire)
+    }
+
+    fn set_transaction(&self, active: bool) {
+        self.transaction.store(active, Ordering::Release);
+    }
 }
 
 // Elixir-friendly data structures
@@ -29,11 +191,46 @@ pub struct DuckDBResource {
 #[module = "Duckex.Result"]
 struct DuckexResult<'a> {
     columns: Vec<Vec<String>>,
-    rows: Vec<Vec<Term<'a>>>,
+    // Structured descriptors of column types, see `column_type::encode`
+    types: Vec<Term<'a>>,
+    // Not set for results not coming from a statement, like `prepare`
+    kind: Option<kind::Kind>,
+    // Only set for statements changing rows
+    rows_affected: Option<u64>,
+    // Time spent waiting for the connection worker
+    queue_time_us: Option<u64>,
+    // Time spent running the query, including encoding of the first chunk
+    execute_time_us: Option<u64>,
+    // List of rows or columns, binary for results serialized with `format`
+    rows: Term<'a>,
     num_rows: usize,
+    layout: Layout,
+    // Rows not encoded yet, fetched with `result_fetch`
+    handle: Option<ResourceArc<result::ResultResource>>,
+    // Only set when requested with `column_info` option
+    column_info: Option<Vec<describe::ColumnInfo>>,
+}
+
+/// Whether `rows` of the result hold list per row or list per column
+#[derive(NifUnitEnum, Clone, Copy, PartialEq, Eq, Default)]
+enum Layout {
+    #[default]
+    Rows,
+    Columnar,
+}
+
+fn duckdb_value_to_term<'a>(env: Env<'a>, value: Value, blob: blob::Format) -> Term<'a> {
+    tagged_value_to_term(env, value, blob, &union::Tags::new(&[]))
 }
 
-fn duckdb_value_to_term<'a>(env: Env<'a>, value: Value) -> Term<'a> {
+// Members of UNION values are `{tag, value}` tuples when their tag is known,
+// see `union::tags`
+fn tagged_value_to_term<'a>(
+    env: Env<'a>,
+    value: Value,
+    blob: blob::Format,
+    tags: &union::Tags,
+) -> Term<'a> {
     match value {
         Value::Null => rustler::types::atom::nil().encode(env),
         Value::Boolean(b) => b.encode(env),
@@ -45,33 +242,44 @@ fn duckdb_value_to_term<'a>(env: Env<'a>, value: Value) -> Term<'a> {
         Value::USmallInt(i) => i.encode(env),
         Value::UInt(i) => i.encode(env),
         Value::UBigInt(i) => (i as i64).encode(env),
-        Value::Float(f) => (f as f64).encode(env),
-        Value::Double(f) => f.encode(env),
-        Value::Timestamp(unit, value) => unit.to_micros(value).encode(env),
+        Value::Float(f) => float::encode(env, f as f64),
+        Value::Double(f) => float::encode(env, f),
+        // Kept in the column unit, so nanosecond timestamps are not truncated
+        Value::Timestamp(_unit, value) => value.encode(env),
         Value::Date32(days) => days.encode(env),
         Value::Text(s) => s.encode(env),
-        Value::Blob(b) => general_purpose::STANDARD.encode(b).encode(env),
+        Value::Blob(b) => blob::encode(env, &b, blob),
         Value::Time64(unit, value) => unit.to_micros(value).encode(env),
         Value::List(vec) => vec
             .into_iter()
-            .map(|v| duckdb_value_to_term(env, v))
+            .map(|v| tagged_value_to_term(env, v, blob, tags))
             .collect::<Vec<_>>()
             .encode(env),
         Value::Enum(s) => s.encode(env),
-        Value::Struct(s) => {
-            let vec: Vec<_> = s.iter().map(|(k, v)| (k.clone(), duckdb_value_to_term(env, v.clone()))).collect();
-            vec.encode(env)
-        }
+        // Maps with string keys, so fields are accessed as `value["name"]`
+        Value::Struct(s) => s.iter().fold(Term::map_new(env), |map, (k, v)| {
+            map.map_put(k.as_str(), tagged_value_to_term(env, v.clone(), blob, tags))
+                .unwrap_or(map)
+        }),
         Value::Map(m) => {
-            let vec: Vec<_> = m.iter().map(|(k, v)| (duckdb_value_to_string(k.clone()), duckdb_value_to_term(env, v.clone()))).collect();
+            let vec: Vec<_> = m
+                .iter()
+                .map(|(k, v)| {
+                    let value = tagged_value_to_term(env, v.clone(), blob, tags);
+                    (duckdb_value_to_string(k.clone()), value)
+                })
+                .collect();
             vec.encode(env)
         }
         Value::Array(vec) => vec
             .into_iter()
-            .map(|v| duckdb_value_to_term(env, v))
+            .map(|v| tagged_value_to_term(env, v, blob, tags))
             .collect::<Vec<_>>()
             .encode(env),
-        Value::Union(val) => duckdb_value_to_term(env, *val),
+        Value::Union(val) => match tags.next() {
+            Some(tag) => (tag, tagged_value_to_term(env, *val, blob, tags)).encode(env),
+            None => tagged_value_to_term(env, *val, blob, tags),
+        },
         _ => format!("{:?}", value).encode(env),
     }
 }
@@ -84,303 +292,1922 @@ fn duckdb_value_to_string(value: Value) -> String {
     }
 }
 
+fn bytes_to_binary<'a>(env: Env<'a>, bytes: &[u8]) -> Result<Binary<'a>, String> {
+    let mut binary = OwnedBinary::new(bytes.len())
+        .ok_or_else(|| "Failed to allocate binary".to_string())?;
+    binary.as_mut_slice().copy_from_slice(bytes);
+
+    Ok(binary.release(env))
+}
+
 // NIF functions
 #[rustler::nif]
-fn new(database_path: String, cache_size: Option<usize>) -> Result<ResourceArc<DuckDBResource>, String> {
+fn new(
+    database_path: String,
+    cache_size: Option<usize>,
+    opts: options::Options,
+) -> Result<ResourceArc<DuckDBResource>, error::Error> {
+    let lock_timeout = opts.get::<u64>("lock_timeout")?.map(Duration::from_millis);
+    let strict_floats = opts.get::<bool>("strict_floats")?.unwrap_or(false);
+    let strict_text = opts.get::<bool>("strict_text")?.unwrap_or(false);
+
+    // Settings from the URI query string take precedence over the options
+    let uri = uri::parse(&database_path)?;
+    let config = extension::config(spill::config(&opts)?, &opts)?;
+    let config = uri.configure(config)?;
+    let database_path = uri.path;
+
+    let mut shared = None;
+
     let conn = if database_path == ":memory:" {
-        Connection::open_in_memory()
+        Connection::open_in_memory_with_flags(config)
             .map_err(|e| format!("Failed to create in-memory DuckDB connection: {}", e))?
+    } else if let Some(name) = shared::name(&database_path) {
+        let (conn, database) = shared::connect(name, config)?;
+        shared = Some(database);
+        conn
+    } else if motherduck::is_motherduck(&database_path) {
+        let config = motherduck::configure(config, &opts)?;
+
+        Connection::open_with_flags(&database_path, config)
+            .map_err(|e| motherduck::error(&database_path, e))?
     } else {
-        Connection::open(&database_path)
+        Connection::open_with_flags(&database_path, config)
             .map_err(|e| format!("Failed to open DuckDB database at '{}': {}", database_path, e))?
     };
 
+    timezone::setup(&conn, &opts)?;
+
+    // Track progress of queries for `progress/1` without printing it
+    conn.execute_batch("SET enable_progress_bar = true; SET enable_progress_bar_print = false;")
+        .map_err(|e| format!("Failed to enable query progress: {}", e))?;
+
+    if let Some(http) = opts.get::<options::Options>("http")? {
+        http::configure(&conn, &http::set_sql(&http)?)?;
+    }
+
+    // Run before the resource is handed out, so every connection of a pool is
+    // configured the same way before its first query
+    for sql in opts.get::<Vec<String>>("after_connect")?.unwrap_or_default() {
+        conn.execute_batch(&sql)
+            .map_err(|e| format!("Failed to run after_connect statement '{}': {}", sql, e))?;
+    }
+
     let size = cache_size.unwrap_or(1024);
+    let interrupt = conn.interrupt_handle();
+    let checkpoint = !database_path.starts_with(":memory:");
+    let worker = Arc::new(worker::Worker::spawn(conn, checkpoint)?);
+    shutdown::register(&database_path, &worker);
+
     let resource = DuckDBResource {
-        conn: Mutex::new(conn),
-        queries: Mutex::new(cache::Cache::with_capacity(size)),
+        worker,
+        database: database_path,
+        queries: Arc::new(Mutex::new(cache::Cache::with_capacity(size))),
+        profile: Mutex::new(None),
+        progress: progress::Progress::default(),
+        lock_timeout,
+        poisoned: AtomicBool::new(false),
+        transaction: AtomicBool::new(false),
+        strict_floats,
+        strict_text,
+        log_handler: Mutex::new(None),
+        limits: limit::Limits::new(&opts)?,
+        peak_memory: AtomicU64::new(0),
+        interrupt,
+        readers: reader::Readers::new(opts.get::<usize>("readers")?.unwrap_or(4)),
+        _shared: shared,
     };
 
     Ok(ResourceArc::new(resource))
 }
 
+#[derive(NifMap)]
+struct VersionInfo {
+    library_version: String,
+    source_id: String,
+    // Oldest DuckDB version that can read database files written by this one
+    storage_compatibility_version: String,
+}
+
+#[rustler::nif]
+fn version() -> Result<VersionInfo, error::Error> {
+    let conn = Connection::open_in_memory()
+        .map_err(|e| format!("Failed to create in-memory DuckDB connection: {}", e))?;
+
+    let version = conn
+        .query_row(
+            "SELECT library_version, source_id, current_setting('storage_compatibility_version') \
+             FROM pragma_version()",
+            [],
+            |row| {
+                Ok(VersionInfo {
+                    library_version: row.get(0)?,
+                    source_id: row.get(1)?,
+                    storage_compatibility_version: row.get(2)?,
+                })
+            },
+        )
+        .map_err(|e| format!("Failed to read DuckDB version: {}", e))?;
+
+    Ok(version)
+}
+
+#[rustler::nif]
+fn quote_identifier(name: String) -> String {
+    sql::quote_identifier(&name)
+}
+
 #[rustler::nif]
+fn escape_literal(value: String) -> String {
+    sql::quote_literal(&value)
+}
+
+#[rustler::nif]
+fn split_statements(sql: String) -> Vec<String> {
+    split::statements(&sql)
+}
+
+#[rustler::nif]
+fn validate(
+    sql: String,
+    opts: options::Options,
+) -> Result<Vec<validate::Diagnostic>, error::Error> {
+    let split = opts.get::<bool>("split")?.unwrap_or(true);
+
+    validate::validate(&sql, split)
+}
+
+#[rustler::nif(schedule = "DirtyIo")]
 fn prepare<'a>(
     env: Env<'a>,
     resource: ResourceArc<DuckDBResource>,
     query: String,
-) -> Result<Term<'a>, String> {
-    let conn = resource.conn.lock().map_err(|e| e.to_string())?;
-    let mut queries = resource.queries.lock().map_err(|e| e.to_string())?;
-
+) -> Result<Term<'a>, error::Error> {
     // Validate the query by trying to prepare it
-    let _ = conn
-        .prepare(&query)
-        .map_err(|e| format!("SQL preparation error: {}", e))?;
+    let sql = query.clone();
+    resource.with_conn(move |conn| {
+        conn.prepare(&sql)
+            .map_err(|e| format!("SQL preparation error: {}", e))?;
+
+        Ok(())
+    })?;
 
     // Store the query string for later execution
-    let id = queries
+    let id = resource
+        .lock_queries()?
         .store(query)
         .ok_or_else(|| "Exhausted prepared statements cache".to_string())?;
 
-    let columns = vec![vec!["ref".to_string(), DataType::UInt32.to_string()]];
-    let rows = vec![vec![id.encode(env)]];
+    let statement = ResourceArc::new(statement::StatementResource::new(id, &resource.queries));
+
+    let columns = vec![vec!["ref".to_string(), "Statement".to_string()]];
+    let rows = vec![vec![statement.encode(env)]];
 
     let result = DuckexResult {
         columns,
-        rows,
+        types: vec![],
+        kind: None,
+        rows_affected: None,
+        queue_time_us: None,
+        execute_time_us: None,
+        rows: rows.encode(env),
         num_rows: 1,
+        layout: Layout::Rows,
+        handle: None,
+        column_info: None,
     };
 
     Ok(result.encode(env))
 }
 
-#[rustler::nif]
+#[derive(Clone, Copy)]
+struct ExecuteOpts {
+    timeout: Option<u64>,
+    layout: Layout,
+    chunk_size: Option<usize>,
+    limits: limit::Limits,
+    geometry: geometry::Format,
+    blob: blob::Format,
+    format: output::Format,
+    csv: output::Csv,
+    column_info: bool,
+}
+
+impl ExecuteOpts {
+    fn new(opts: &options::Options) -> Result<Self, String> {
+        Ok(ExecuteOpts {
+            timeout: opts.get::<u64>("timeout")?,
+            layout: opts.get::<Layout>("layout")?.unwrap_or_default(),
+            chunk_size: opts.get::<usize>("chunk_size")?,
+            limits: limit::Limits::new(opts)?,
+            geometry: opts.get::<geometry::Format>("geometry")?.unwrap_or_default(),
+            blob: opts.get::<blob::Format>("blob")?.unwrap_or_default(),
+            format: opts.get::<output::Format>("format")?.unwrap_or_default(),
+            csv: output::Csv::new(opts)?,
+            column_info: opts.get::<bool>("column_info")?.unwrap_or(false),
+        })
+    }
+}
+
+#[rustler::nif(schedule = "DirtyIo")]
 fn execute<'a>(
     env: Env<'a>,
     resource: ResourceArc<DuckDBResource>,
-    stmt_id: u32,
+    statement: ResourceArc<statement::StatementResource>,
+    params: Vec<Term<'a>>,
+    opts: options::Options<'a>,
+) -> Result<Term<'a>, error::Error> {
+    let stmt_id = statement.id_in(&resource.queries)?;
+    let opts = ExecuteOpts::new(&opts)?;
+
+    panic::guard(|| {
+        // Convert Elixir terms to DuckDB parameters
+        let params = params::Bound::decode(params)?;
+        let query = resource.query(stmt_id)?;
+        let queued = Instant::now();
+
+        let owner = resource.clone();
+        let executed = resource.with_conn(move |conn| {
+            run_statement(&owner, conn, &query, &params, &opts, queued, None)
+        })?;
+
+        // Worker is free by now, so other processes can use the connection
+        // while rows are encoded
+        executed.encode(env, &resource, &opts)
+    })
+}
+
+/// Run the query on separate thread and send `{:duckex_result, ref, result}`
+/// to `:reply_to` process (caller by default) when it is done. The process is
+/// monitored, when it exits first its query is interrupted and the open
+/// transaction rolled back.
+#[rustler::nif]
+fn execute_async<'a>(
+    env: Env<'a>,
+    resource: ResourceArc<DuckDBResource>,
+    statement: ResourceArc<statement::StatementResource>,
+    params: Vec<Term<'a>>,
+    opts: options::Options<'a>,
+) -> Result<Term<'a>, error::Error> {
+    let stmt_id = statement.id_in(&resource.queries)?;
+    let reply_to = opts.get::<LocalPid>("reply_to")?.unwrap_or_else(|| env.pid());
+    let opts = ExecuteOpts {
+        // Result is encoded off the schedulers, so there is no need to chunk it
+        chunk_size: None,
+        ..ExecuteOpts::new(&opts)?
+    };
+
+    let params = panic::guard(|| params::Bound::decode(params))?;
+    let query = resource.query(stmt_id)?;
+    resource.ensure_not_poisoned()?;
+
+    let reference = env.make_ref().encode(env);
+
+    let mut msg_env = OwnedEnv::new();
+    let saved_ref = msg_env.save(reference);
+    let worker_resource = resource.clone();
+
+    let caller = ResourceArc::new(caller::CallerResource::new(resource.interrupt.clone()));
+    if caller.monitor(Some(env), &reply_to).is_none() {
+        caller.abandon();
+    }
+
+    let queued = Instant::now();
+
+    resource.worker.submit(move |conn| {
+        let resource = worker_resource;
+
+        let _ = msg_env.send_and_clear(&reply_to, |env| {
+            let result = panic::guard(|| {
+                let conn = conn.as_ref().ok_or_else(error::Error::closed)?;
+
+                run_statement(&resource, conn, &query, &params, &opts, queued, Some(&caller))?
+                    .encode(env, &resource, &opts)
+            });
+            let result = match result {
+                Ok(result) => (atoms::ok(), result).encode(env),
+                Err(error) => (atoms::error(), error).encode(env),
+            };
+
+            (atoms::duckex_result(), saved_ref.load(env), result).encode(env)
+        });
+    })?;
+
+    Ok(reference)
+}
+
+// Runs on the worker thread, which owns `conn`
+fn run_statement(
+    resource: &DuckDBResource,
+    conn: &Connection,
+    query: &str,
+    params: &params::Bound,
+    opts: &ExecuteOpts,
+    queued: Instant,
+    caller: Option<&caller::CallerResource>,
+) -> Result<Executed, error::Error> {
+    let opts = ExecuteOpts {
+        limits: opts.limits.or(resource.limits),
+        ..*opts
+    };
+
+    if caller.is_some_and(|caller| !caller.start()) {
+        return Err(error::Error::new(
+            error::ErrorKind::Interrupted,
+            "Caller exited before the query started",
+        ));
+    }
+
+    let executed = run_query(resource, conn, query, params, &opts, queued, false);
+
+    // Nobody waits for the result, and transaction of the interrupted query
+    // cannot be committed anyway
+    if caller.is_some_and(|caller| caller.finish()) {
+        if resource.in_transaction() {
+            let _ = conn.execute_batch("ROLLBACK");
+            resource.set_transaction(false);
+        }
+
+        return Err(error::Error::new(
+            error::ErrorKind::Interrupted,
+            "Caller exited before the query finished",
+        ));
+    }
+
+    let executed = executed?;
+
+    // Transactions can be controlled by plain queries as well
+    if let Some(active) = transaction::active_after(query) {
+        resource.set_transaction(active);
+    }
+
+    Ok(executed)
+}
+
+/// Run read query on one of the connection clones, so it does not wait for
+/// the connection worker, nor holds it
+#[rustler::nif(schedule = "DirtyIo")]
+fn read<'a>(
+    env: Env<'a>,
+    resource: ResourceArc<DuckDBResource>,
+    query: String,
     params: Vec<Term<'a>>,
-) -> Result<Term<'a>, String> {
-    let conn = resource.conn.lock().map_err(|e| e.to_string())?;
-    let queries = resource.queries.lock().map_err(|e| e.to_string())?;
+    opts: options::Options<'a>,
+) -> Result<Term<'a>, error::Error> {
+    let opts = ExecuteOpts::new(&opts)?;
+    let opts = ExecuteOpts {
+        limits: opts.limits.or(resource.limits),
+        ..opts
+    };
+
+    let params = panic::guard(|| params::Bound::decode(params))?;
+    let queued = Instant::now();
+
+    let executed = {
+        // Cloning needs the connection, but only the first time, later the
+        // clone is reused
+        let conn = resource.readers.checkout(|| {
+            resource.with_conn(|conn| {
+                conn.try_clone()
+                    .map_err(|e| format!("Failed to clone connection: {}", e).into())
+            })
+        })?;
+
+        panic::guard(|| run_query(&resource, &conn, &query, &params, &opts, queued, true))?
+    };
+
+    panic::guard(|| executed.encode(env, &resource, &opts))
+}
+
+/// Result of the query materialized as DuckDB values, so it can be encoded
+/// once the worker is done with the connection
+struct Executed {
+    query: String,
+    columns: Vec<(String, DataType)>,
+    // Columns holding GEOMETRY values, see `geometry::columns`
+    geometry: Vec<bool>,
+    // Columns holding VARINT values, see `varint::columns`
+    varint: Vec<bool>,
+    rows: Vec<Vec<Value>>,
+    // Tags of UNION values of each row, see `union::tags`
+    tags: Vec<Vec<Vec<String>>>,
+    // Nullability and origin of the columns, see `describe::column_info`
+    column_info: Option<Vec<describe::ColumnInfo>>,
+    kind: kind::Kind,
+    rows_affected: Option<u64>,
+    queue_time: Duration,
+    execute_time: Duration,
+}
 
-    // Get the query string
-    let query = queries
-        .get_ref(stmt_id as usize)
-        .ok_or_else(|| "Invalid cache index".to_string())?;
+// Run the query on `conn`, which is either the connection itself or its clone
+// when `concurrent`
+fn run_query(
+    resource: &DuckDBResource,
+    conn: &Connection,
+    query: &str,
+    params: &params::Bound,
+    opts: &ExecuteOpts,
+    queued: Instant,
+    concurrent: bool,
+) -> Result<Executed, error::Error> {
+    let started = Instant::now();
 
     // Prepare the statement (short-lived)
     let mut stmt = conn
         .prepare(query)
         .map_err(|e| format!("SQL preparation error: {}", e))?;
 
-    // Convert Elixir terms to DuckDB parameters
-    let params_vec: Vec<Value> = params
-        .into_iter()
-        .map(|term| term_to_duckdb_value(term))
-        .collect::<Result<Vec<_>, _>>()?;
+    let kind = kind::classify(&stmt);
 
-    let rows_result = stmt
-        .query_map(params_from_iter(params_vec.iter()), |row| {
-            (0..)
-                .map_while(|i| match row.get::<_, Value>(i) {
-                    Ok(val) => Some(Ok(val)),
-                    Err(_) => None,
-                })
-                .collect()
-        })
-        .map_err(|e| format!("SQL execution error: {}", e))?;
+    if concurrent && kind != kind::Kind::Select {
+        return Err(error::Error::new(
+            error::ErrorKind::InvalidInput,
+            "Only reading queries can run on connection clones",
+        ));
+    }
+
+    let values = params.resolve(&stmt, query)?;
+    params::validate_arrays(&stmt, &values)?;
+    let params_vec = params::bind_arrays(&values)?;
+
+    if stmt.parameter_count() != params_vec.len() {
+        return Err(error::Error::arity(
+            stmt.parameter_count(),
+            params_vec.len(),
+            query,
+        ));
+    }
+
+    if resource.strict_text {
+        params::ensure_text(&params_vec)?;
+    }
+
+    params::validate_enums(&stmt, &params_vec)?;
+
+    // Progress is reported only for the query running on the connection
+    // itself, concurrent ones are tracked separately
+    let untracked = progress::Progress::default();
+    let progress = if concurrent {
+        &untracked
+    } else {
+        &resource.progress
+    };
+    let _progress = progress.start(conn, query);
+
+    // Interrupt the query when it runs longer than the timeout
+    let watchdog = opts
+        .timeout
+        .map(|ms| watchdog::Watchdog::arm(conn.interrupt_handle(), Duration::from_millis(ms)));
 
-    let rows: Vec<Vec<Value>> = rows_result
-        .collect::<Result<Vec<_>, _>>()
-        .map_err(|e| format!("SQL row processing error: {}", e))?;
+    let fetched = fetch_rows(&mut stmt, &params_vec, opts, progress);
+    let timed_out = watchdog.is_some_and(watchdog::Watchdog::disarm);
 
-    let num_rows = rows.len();
+    let execute_time = started.elapsed();
+    // A watchdog firing after the rows were fetched must not fail the query
+    let (rows, tags) = fetched.map_err(|err| match err.kind {
+        error::ErrorKind::Interrupted if timed_out => error::Error::timeout(),
+        _ => err.in_statement(query),
+    })?;
+
+    if resource.strict_floats {
+        rows.iter().flatten().try_for_each(float::ensure_finite)?;
+    }
 
-    let columns: Vec<Vec<String>> = stmt
+    let columns: Vec<_> = stmt
         .column_names()
         .into_iter()
         .enumerate()
-        .map(|(idx, name)| vec![name, stmt.column_type(idx).to_string()])
+        .map(|(idx, name)| (name, stmt.column_type(idx)))
         .collect();
 
-    let result_rows: Vec<Vec<Term<'a>>> = rows
-        .into_iter()
-        .map(|row| row.into_iter().map(|v| duckdb_value_to_term(env, v)).collect())
-        .collect();
+    let geometry = geometry::columns(&stmt.schema());
+    let varint = varint::columns(&stmt.schema());
 
-    let result = DuckexResult {
-        columns,
-        rows: result_rows,
-        num_rows,
+    let column_info = opts.column_info.then(|| {
+        let names: Vec<_> = columns.iter().map(|(name, _)| name.clone()).collect();
+
+        describe::column_info(conn, query, &names)
+    });
+
+    // Without `RETURNING` changed rows are not returned, just their count
+    let rows_affected = if !kind.is_dml() {
+        None
+    } else if kind::is_returning(&columns) {
+        Some(rows.len() as u64)
+    } else {
+        match rows.first().and_then(|values| values.first()) {
+            Some(Value::BigInt(count)) => u64::try_from(*count).ok(),
+            Some(Value::UBigInt(count)) => Some(*count),
+            _ => None,
+        }
     };
 
-    Ok(result.encode(env))
+    Ok(Executed {
+        query: query.to_string(),
+        columns,
+        geometry,
+        varint,
+        rows,
+        tags,
+        column_info,
+        kind,
+        rows_affected,
+        queue_time: started.duration_since(queued),
+        execute_time,
+    })
+}
+
+impl Executed {
+    fn encode<'a>(
+        self,
+        env: Env<'a>,
+        resource: &DuckDBResource,
+        opts: &ExecuteOpts,
+    ) -> Result<Term<'a>, error::Error> {
+        let ExecuteOpts {
+            layout,
+            chunk_size,
+            geometry,
+            blob,
+            format,
+            csv,
+            ..
+        } = *opts;
+
+        let num_rows = self.rows.len();
+
+        let (columns, types): (Vec<Vec<String>>, Vec<Term>) = self
+            .columns
+            .iter()
+            .map(|(name, data_type)| {
+                (
+                    vec![name.clone(), data_type.to_string()],
+                    column_type::encode(env, data_type),
+                )
+            })
+            .unzip();
+
+        let (rows, handle) = match format {
+            output::Format::Terms => {
+                let rest = result::ResultResource::new(
+                    self.rows,
+                    self.tags,
+                    layout,
+                    self.geometry,
+                    geometry,
+                    self.varint,
+                    blob,
+                );
+
+                // Chunked results end the chunk early once the timeslice is
+                // used, which never happens for the first one, as the NIF runs
+                // on dirty scheduler, where the timeslice is not tracked
+                let (mut rows, done) = match chunk_size {
+                    Some(size) => rest.fetch(env, size)?,
+                    None => rest.fetch_all(env)?,
+                };
+
+                // Columns of empty result still need to be there
+                if layout == Layout::Columnar {
+                    rows.resize_with(columns.len(), Vec::new);
+                }
+
+                // Remaining rows are fetched with `result_fetch`, which does
+                // not need the connection anymore
+                (rows.encode(env), (!done).then(|| ResourceArc::new(rest)))
+            }
+            // Serialized in one go, binary is not split into chunks
+            output::Format::Json => {
+                let json = output::json(&self.columns, &self.rows, &self.tags)?;
+
+                (bytes_to_binary(env, &json)?.encode(env), None)
+            }
+            output::Format::Csv => {
+                let csv = csv.write(&self.columns, &self.rows, &self.tags)?;
+
+                (bytes_to_binary(env, &csv)?.encode(env), None)
+            }
+        };
+
+        if let Some(handler) = *lock::acquire(&resource.log_handler, None)? {
+            let message = (
+                atoms::duckex_query(),
+                self.query.as_str(),
+                self.execute_time.as_micros() as u64,
+                num_rows,
+            );
+
+            // Handler process may be already gone, which is fine
+            let _ = env.send(&handler, message);
+        }
+
+        let result = DuckexResult {
+            columns,
+            types,
+            kind: Some(self.kind),
+            rows_affected: self.rows_affected,
+            queue_time_us: Some(self.queue_time.as_micros() as u64),
+            execute_time_us: Some(self.execute_time.as_micros() as u64),
+            rows,
+            num_rows,
+            layout,
+            handle,
+            column_info: self.column_info,
+        };
+
+        Ok(result.encode(env))
+    }
 }
 
+/// Register process receiving `{:duckex_query, sql, duration_us, rows}` after
+/// every executed query, `nil` removes it
 #[rustler::nif]
-fn close(resource: ResourceArc<DuckDBResource>, stmt_id: u32) -> Result<String, String> {
-    let mut queries = resource.queries.lock().map_err(|e| e.to_string())?;
-    queries.remove(stmt_id as usize);
+fn set_log_handler(
+    resource: ResourceArc<DuckDBResource>,
+    handler: Option<LocalPid>,
+) -> Result<String, error::Error> {
+    *lock::acquire(&resource.log_handler, None)? = handler;
     Ok("ok".to_string())
 }
 
-#[rustler::nif]
-fn begin(resource: ResourceArc<DuckDBResource>) -> Result<String, String> {
-    let conn = resource.conn.lock().map_err(|e| e.to_string())?;
-    let mut stmt = conn
-        .prepare("BEGIN")
-        .map_err(|e| format!("SQL preparation error: {}", e))?;
+/// Run prepared statement once for each list of parameters, returns total
+/// number of changed rows
+#[rustler::nif(schedule = "DirtyIo")]
+fn execute_many<'a>(
+    resource: ResourceArc<DuckDBResource>,
+    statement: ResourceArc<statement::StatementResource>,
+    params: Vec<Vec<Term<'a>>>,
+) -> Result<usize, error::Error> {
+    let stmt_id = statement.id_in(&resource.queries)?;
+    let rows: Vec<Vec<Value>> = panic::guard(|| {
+        params
+            .into_iter()
+            .map(|row| row.into_iter().map(term_to_duckdb_value).collect())
+            .collect::<Result<Vec<_>, _>>()
+    })?;
 
-    stmt.execute([])
-        .map_err(|e| format!("SQL execution error: {}", e))?;
+    if resource.strict_text {
+        rows.iter().try_for_each(|row| params::ensure_text(row))?;
+    }
 
-    Ok("ok".to_string())
-}
+    let query = resource.query(stmt_id)?;
+    let owner = resource.clone();
 
-#[rustler::nif]
-fn commit(resource: ResourceArc<DuckDBResource>) -> Result<String, String> {
-    let conn = resource.conn.lock().map_err(|e| e.to_string())?;
-    let mut stmt = conn
-        .prepare("COMMIT")
-        .map_err(|e| format!("SQL preparation error: {}", e))?;
+    resource.with_conn(move |conn| {
+        // Already open transaction covers all the rows, DuckDB does not nest
+        // them
+        if owner.in_transaction() {
+            return execute_rows(conn, &query, &rows);
+        }
 
-    stmt.execute([])
-        .map_err(|e| format!("SQL execution error: {}", e))?;
+        let tx = conn
+            .transaction()
+            .map_err(|e| format!("SQL execution error: {}", e))?;
 
-    Ok("ok".to_string())
+        let changed = execute_rows(&tx, &query, &rows)?;
+
+        tx.commit()
+            .map_err(|e| format!("SQL execution error: {}", e))?;
+
+        Ok(changed)
+    })
 }
 
-#[rustler::nif]
-fn rollback(resource: ResourceArc<DuckDBResource>) -> Result<String, String> {
-    let conn = resource.conn.lock().map_err(|e| e.to_string())?;
+fn execute_rows(conn: &Connection, query: &str, rows: &[Vec<Value>]) -> Result<usize, error::Error> {
     let mut stmt = conn
-        .prepare("ROLLBACK")
+        .prepare(query)
         .map_err(|e| format!("SQL preparation error: {}", e))?;
 
-    stmt.execute([])
-        .map_err(|e| format!("SQL execution error: {}", e))?;
+    let mut changed = 0;
 
-    Ok("ok".to_string())
-}
+    for row in rows {
+        if stmt.parameter_count() != row.len() {
+            return Err(error::Error::arity(stmt.parameter_count(), row.len(), query));
+        }
 
-#[rustler::nif]
-fn status(_resource: ResourceArc<DuckDBResource>) -> Result<String, String> {
-    Ok("ok".to_string())
+        changed += stmt.execute(params_from_iter(row.iter())).map_err(|e| {
+            error::Error::from(format!("SQL execution error: {}", e)).in_statement(query)
+        })?;
+    }
+
+    Ok(changed)
 }
 
-// Helper function to convert Elixir terms to DuckDB values
-fn term_to_duckdb_value(term: Term) -> Result<Value, String> {
-    // Check for DateTime struct first (map with __struct__ key)
-    if term.is_map() {
-        // Try to decode as a DateTime by using MapIterator
-        use rustler::types::map::MapIterator;
-        if let Some(iter) = MapIterator::new(term) {
-            let mut map_data: std::collections::HashMap<String, Term> = std::collections::HashMap::new();
-            for (key, value) in iter {
-                if let Ok(key_str) = key.atom_to_string() {
-                    map_data.insert(key_str, value);
-                }
-            }
+// Rows with tags of their UNION values
+type Fetched = (Vec<Vec<Value>>, Vec<Vec<Vec<String>>>);
 
-            // Check if this is a DateTime or Date struct
-            if let Some(struct_term) = map_data.get("__struct__") {
-                if let Ok(module_str) = struct_term.atom_to_string() {
-                    // Handle Date struct
-                    // Note: DuckDB Rust library doesn't support Date32 for parameter binding,
-                    // so we convert to ISO 8601 date string format (YYYY-MM-DD)
-                    if module_str == "Elixir.Date" {
-                        let year = map_data.get("year").and_then(|t| t.decode::<i32>().ok());
-                        let month = map_data.get("month").and_then(|t| t.decode::<u32>().ok());
-                        let day = map_data.get("day").and_then(|t| t.decode::<u32>().ok());
-
-                        if let (Some(year_val), Some(month_val), Some(day_val)) = (year, month, day) {
-                            // Format as ISO 8601 date string
-                            let date_string = format!("{:04}-{:02}-{:02}", year_val, month_val, day_val);
-                            return Ok(Value::Text(date_string));
-                        }
-                    }
-                    // Handle DateTime struct
-                    else if module_str == "Elixir.DateTime" {
-                        // Extract DateTime fields
-                        let year = map_data.get("year").and_then(|t| t.decode::<i32>().ok());
-                        let month = map_data.get("month").and_then(|t| t.decode::<u32>().ok());
-                        let day = map_data.get("day").and_then(|t| t.decode::<u32>().ok());
-                        let hour = map_data.get("hour").and_then(|t| t.decode::<u32>().ok());
-                        let minute = map_data.get("minute").and_then(|t| t.decode::<u32>().ok());
-                        let second = map_data.get("second").and_then(|t| t.decode::<u32>().ok());
-                        let microsecond = map_data.get("microsecond").and_then(|t| {
-                            // microsecond is a tuple {value, precision}
-                            t.decode::<(i64, u32)>().ok().map(|(v, _)| v)
-                        });
-
-                        if let (Some(year_val), Some(month_val), Some(day_val), Some(hour_val), Some(minute_val), Some(second_val), Some(microsecond_val)) =
-                            (year, month, day, hour, minute, second, microsecond) {
-                            // Calculate Unix timestamp in microseconds
-                            let mut days = 0i64;
-
-                            // Add days for years
-                            for y in 1970..year_val {
-                                if (y % 4 == 0 && y % 100 != 0) || (y % 400 == 0) {
-                                    days += 366;
-                                } else {
-                                    days += 365;
-                                }
-                            }
-
-                            // Add days for months in current year
-                            let is_leap = (year_val % 4 == 0 && year_val % 100 != 0) || (year_val % 400 == 0);
-                            let days_in_month = [31, if is_leap { 29 } else { 28 }, 31, 30, 31, 30, 31, 31, 30, 31, 30, 31];
-                            for m in 1..month_val {
-                                days += days_in_month[(m - 1) as usize] as i64;
-                            }
-
-                            // Add days in current month
-                            days += (day_val - 1) as i64;
-
-                            // Convert to microseconds
-                            let total_microseconds = days * 86400 * 1_000_000
-                                + (hour_val as i64) * 3600 * 1_000_000
-                                + (minute_val as i64) * 60 * 1_000_000
-                                + (second_val as i64) * 1_000_000
-                                + microsecond_val;
-
-                            return Ok(Value::Timestamp(duckdb::types::TimeUnit::Microsecond, total_microseconds));
-                        }
-                    }
-                }
-            }
-        }
-    }
+// Rows are materialized as DuckDB values, so they can be encoded into terms
+// after the connection is released
+fn fetch_rows(
+    stmt: &mut duckdb::Statement,
+    params: &[Value],
+    opts: &ExecuteOpts,
+    progress: &progress::Progress,
+) -> Result<Fetched, error::Error> {
+    let limits = opts.limits;
 
-    if term.is_number() {
-        if let Ok(i) = term.decode::<i64>() {
-            return Ok(Value::BigInt(i));
+    let mut rows = stmt
+        .query(params_from_iter(params.iter()))
+        .map_err(|e| format!("SQL execution error: {}", e))?;
+
+    let mut fetched = vec![];
+    let mut tags = vec![];
+    let mut bytes = 0;
+
+    while let Some(row) = rows
+        .next()
+        .map_err(|e| format!("SQL row processing error: {}", e))?
+    {
+        progress.row();
+
+        if limits.counts_bytes() {
+            bytes += (0..)
+                .map_while(|i| row.get_ref(i).ok())
+                .map(|value| limit::size(&value))
+                .sum::<usize>();
         }
-        if let Ok(f) = term.decode::<f64>() {
-            return Ok(Value::Double(f));
+
+        limits.check(fetched.len() + 1, bytes)?;
+
+        let values: Vec<ValueRef> = (0..).map_while(|i| row.get_ref(i).ok()).collect();
+        let row_tags: Vec<Vec<String>> = values.iter().map(union::tags).collect();
+
+        // Most rows have no unions, no need to keep anything for them
+        if row_tags.iter().all(Vec::is_empty) {
+            tags.push(vec![]);
+        } else {
+            tags.push(row_tags);
         }
-    }
 
-    if let Ok(s) = term.decode::<String>() {
-        return Ok(Value::Text(s));
+        fetched.push(values.iter().map(ValueRef::to_owned).collect());
     }
 
-    if let Ok(b) = term.decode::<bool>() {
-        return Ok(Value::Boolean(b));
-    }
+    Ok((fetched, tags))
+}
 
-    if term.is_atom() {
-        if let Ok(atom_str) = term.atom_to_string() {
-            if atom_str == "nil" {
-                return Ok(Value::Null);
+fn push_row<'a>(
+    data: &mut Vec<Vec<Term<'a>>>,
+    values: impl Iterator<Item = Term<'a>>,
+    layout: Layout,
+) {
+    match layout {
+        Layout::Rows => data.push(values.collect()),
+        Layout::Columnar => {
+            for (idx, value) in values.enumerate() {
+                if idx == data.len() {
+                    data.push(vec![]);
+                }
+
+                data[idx].push(value);
             }
         }
     }
+}
 
-    // Provide detailed type information in error message
-    let type_info = if term.is_number() {
-        "number (but failed to decode as i64 or f64)"
-    } else if term.is_map() {
-        "map (unsupported structure)"
-    } else if term.is_list() {
-        "list (not yet supported)"
-    } else if term.is_atom() {
-        "atom (not nil)"
-    } else if term.is_binary() {
-        "binary (failed to decode as string)"
-    } else if term.is_tuple() {
-        "tuple (not supported)"
-    } else {
-        "unknown type"
-    };
+#[rustler::nif]
+fn result_fetch<'a>(
+    env: Env<'a>,
+    result: ResourceArc<result::ResultResource>,
+    count: usize,
+) -> Result<(Vec<Vec<Term<'a>>>, bool), error::Error> {
+    panic::guard(|| result.fetch(env, count))
+}
 
-    Err(format!("Unsupported parameter type: {}", type_info))
+#[rustler::nif]
+fn result_close(result: ResourceArc<result::ResultResource>) -> Result<String, error::Error> {
+    result.close()?;
+
+    Ok("ok".to_string())
+}
+
+#[rustler::nif(schedule = "DirtyIo")]
+fn query_arrow<'a>(
+    env: Env<'a>,
+    resource: ResourceArc<DuckDBResource>,
+    query: String,
+    params: Vec<Term<'a>>,
+) -> Result<Vec<Binary<'a>>, error::Error> {
+    let params_vec: Vec<Value> = params
+        .into_iter()
+        .map(|term| term_to_duckdb_value(term))
+        .collect::<Result<Vec<_>, _>>()?;
+
+    let batches = resource.with_conn(move |conn| {
+        let mut stmt = conn
+            .prepare(&query)
+            .map_err(|e| format!("SQL preparation error: {}", e))?;
+
+        let batches = stmt
+            .query_arrow(params_from_iter(params_vec.iter()))
+            .map_err(|e| format!("SQL execution error: {}", e))?;
+
+        // Each record batch is serialized separately as an Arrow IPC stream
+        batches
+            .map(|batch| {
+                ipc::encode_batch(&batch)
+                    .map_err(|e| format!("Arrow serialization error: {}", e).into())
+            })
+            .collect::<Result<Vec<_>, error::Error>>()
+    })?;
+
+    batches
+        .iter()
+        .map(|bytes| Ok(bytes_to_binary(env, bytes)?))
+        .collect()
+}
+
+#[rustler::nif(schedule = "DirtyIo")]
+fn appender_open(
+    resource: ResourceArc<DuckDBResource>,
+    table: String,
+    opts: options::Options,
+) -> Result<ResourceArc<appender::AppenderResource>, error::Error> {
+    let appender = appender::AppenderResource::new(resource, table, &opts)?;
+
+    Ok(ResourceArc::new(appender))
+}
+
+#[rustler::nif(schedule = "DirtyIo")]
+fn appender_append_chunk<'a>(
+    appender: ResourceArc<appender::AppenderResource>,
+    rows: Vec<Vec<Term<'a>>>,
+) -> Result<u64, error::Error> {
+    let rows = panic::guard(|| {
+        rows.into_iter()
+            .map(|row| row.into_iter().map(term_to_duckdb_value).collect())
+            .collect::<Result<Vec<Vec<Value>>, _>>()
+    })?;
+
+    appender.append(rows)
+}
+
+#[rustler::nif(schedule = "DirtyIo")]
+fn appender_close(appender: ResourceArc<appender::AppenderResource>) -> Result<u64, error::Error> {
+    appender.close()
+}
+
+#[rustler::nif(schedule = "DirtyIo")]
+fn blob_open<'a>(
+    resource: ResourceArc<DuckDBResource>,
+    query: String,
+    params: Vec<Term<'a>>,
+) -> Result<blob::Opened, error::Error> {
+    let params = params::Bound::decode(params)?;
+
+    resource.with_conn(move |conn| blob::open(conn, &query, &params))
+}
+
+#[rustler::nif]
+fn blob_read<'a>(
+    env: Env<'a>,
+    blob: ResourceArc<blob::BlobResource>,
+    offset: u64,
+    length: u64,
+) -> Result<Binary<'a>, error::Error> {
+    Ok(bytes_to_binary(env, blob.read(offset, length))?)
+}
+
+#[rustler::nif]
+fn ingest_open(
+    resource: ResourceArc<DuckDBResource>,
+    table: String,
+    format: copy::CopyFormat,
+    opts: options::Options,
+) -> Result<ResourceArc<ingest::IngestResource>, error::Error> {
+    let ingest = ingest::IngestResource::new(resource, table, format, &opts)?;
+
+    Ok(ResourceArc::new(ingest))
+}
+
+#[rustler::nif]
+fn ingest_write(
+    ingest: ResourceArc<ingest::IngestResource>,
+    data: Binary,
+) -> Result<String, error::Error> {
+    ingest.write(data.as_slice())?;
+
+    Ok("ok".to_string())
+}
+
+#[rustler::nif(schedule = "DirtyIo")]
+fn ingest_finish(ingest: ResourceArc<ingest::IngestResource>) -> Result<u64, error::Error> {
+    ingest.finish()
+}
+
+#[rustler::nif(schedule = "DirtyIo")]
+fn register_rows<'a>(
+    resource: ResourceArc<DuckDBResource>,
+    name: String,
+    columns: Vec<(String, String)>,
+    rows: Vec<Term<'a>>,
+) -> Result<usize, error::Error> {
+    let rows = register::rows(rows, &columns)?;
+
+    resource.with_conn(move |conn| register::register(conn, &name, &columns, &rows))
+}
+
+#[rustler::nif(schedule = "DirtyIo")]
+fn copy_to(
+    resource: ResourceArc<DuckDBResource>,
+    query: String,
+    path: String,
+    format: copy::CopyFormat,
+    opts: options::Options,
+) -> Result<usize, error::Error> {
+    let sql = copy::copy_to_sql(&query, &path, format, &opts)?;
+
+    resource.with_conn(move |conn| {
+        let rows = conn
+            .execute(&sql, [])
+            .map_err(|e| format!("SQL execution error: {}", e))?;
+
+        Ok(rows)
+    })
+}
+
+#[rustler::nif(schedule = "DirtyIo")]
+fn execute_to_file<'a>(
+    resource: ResourceArc<DuckDBResource>,
+    query: String,
+    params: Vec<Term<'a>>,
+    path: String,
+    format: spool::Format,
+    opts: options::Options,
+) -> Result<spool::Spooled, error::Error> {
+    let params = params::Bound::decode(params)?;
+    let writer = spool::Writer::new(&query, &path, format, &opts)?;
+
+    resource.with_conn(move |conn| spool::write(conn, &query, &params, &path, &writer))
+}
+
+#[rustler::nif(schedule = "DirtyIo")]
+fn query_arrow_stream<'a>(
+    resource: ResourceArc<DuckDBResource>,
+    query: String,
+    params: Vec<Term<'a>>,
+) -> Result<ResourceArc<stream::ArrowStreamResource>, error::Error> {
+    let params_vec: Vec<Value> = params
+        .into_iter()
+        .map(|term| term_to_duckdb_value(term))
+        .collect::<Result<Vec<_>, _>>()?;
+
+    let (schema, batches) =
+        resource.with_conn(move |conn| query_batches(conn, &query, &params_vec))?;
+
+    Ok(ResourceArc::new(stream::ArrowStreamResource::new(schema, batches)))
+}
+
+#[rustler::nif(schedule = "DirtyIo")]
+fn query_tensors<'a>(
+    resource: ResourceArc<DuckDBResource>,
+    query: String,
+    params: Vec<Term<'a>>,
+) -> Result<Vec<tensor::Column>, error::Error> {
+    let params_vec: Vec<Value> = params
+        .into_iter()
+        .map(|term| term_to_duckdb_value(term))
+        .collect::<Result<Vec<_>, _>>()?;
+
+    let (schema, batches) =
+        resource.with_conn(move |conn| query_batches(conn, &query, &params_vec))?;
+
+    tensor::columns(&schema, &batches)
+}
+
+// Batches need to be collected, as they outlive the statement
+fn query_batches(
+    conn: &Connection,
+    query: &str,
+    params: &[Value],
+) -> Result<(SchemaRef, Vec<RecordBatch>), error::Error> {
+    let mut stmt = conn
+        .prepare(query)
+        .map_err(|e| format!("SQL preparation error: {}", e))?;
+
+    let arrow = stmt
+        .query_arrow(params_from_iter(params.iter()))
+        .map_err(|e| format!("SQL execution error: {}", e))?;
+
+    Ok((arrow.get_schema(), arrow.collect()))
+}
+
+#[rustler::nif]
+fn arrow_stream_address(
+    stream: ResourceArc<stream::ArrowStreamResource>,
+) -> Result<u64, error::Error> {
+    stream.address()
+}
+
+#[rustler::nif(schedule = "DirtyIo")]
+fn export_parquet<'a>(
+    env: Env<'a>,
+    resource: ResourceArc<DuckDBResource>,
+    query: String,
+    opts: options::Options,
+) -> Result<Binary<'a>, error::Error> {
+    // DuckDB can only write Parquet to files, so go through temporary one
+    let file = temp::TempFile::new("parquet")
+        .map_err(|e| format!("Failed to create temporary file: {}", e))?;
+    let sql = copy::copy_to_sql(&query, &file.path_str(), copy::CopyFormat::Parquet, &opts)?;
+
+    resource.with_conn(move |conn| {
+        conn.execute(&sql, [])
+            .map_err(|e| format!("SQL execution error: {}", e))?;
+
+        Ok(())
+    })?;
+
+    let bytes = std::fs::read(file.path())
+        .map_err(|e| format!("Failed to read exported Parquet file: {}", e))?;
+
+    Ok(bytes_to_binary(env, &bytes)?)
+}
+
+#[rustler::nif(schedule = "DirtyIo")]
+fn load_csv_binary(
+    resource: ResourceArc<DuckDBResource>,
+    table: String,
+    data: Binary,
+    opts: options::Options,
+) -> Result<usize, error::Error> {
+    // DuckDB reads CSV only from files, so go through temporary one
+    let file = temp::TempFile::new("csv")
+        .map_err(|e| format!("Failed to create temporary file: {}", e))?;
+    let sql = csv::load_sql(&table, &file.path_str(), &opts)?;
+
+    file.write_all(data.as_slice())
+        .map_err(|e| format!("Failed to write CSV data to temporary file: {}", e))?;
+
+    resource.with_conn(move |conn| {
+        let rows = conn
+            .execute(&sql, [])
+            .map_err(|e| format!("SQL execution error: {}", e))?;
+
+        Ok(rows)
+    })
+}
+
+#[rustler::nif(schedule = "DirtyIo")]
+fn load_ndjson_binary(
+    resource: ResourceArc<DuckDBResource>,
+    table: String,
+    data: Binary,
+    opts: options::Options,
+) -> Result<ndjson::Loaded, error::Error> {
+    // DuckDB reads JSON only from files, so go through temporary one
+    let file = temp::TempFile::new("ndjson")
+        .map_err(|e| format!("Failed to create temporary file: {}", e))?;
+
+    file.write_all(data.as_slice())
+        .map_err(|e| format!("Failed to write JSON data to temporary file: {}", e))?;
+
+    let sql = ndjson::load_sql(&table, &file.path_str(), &opts)?;
+
+    resource.with_conn(move |conn| ndjson::load(conn, &table, &sql))
+}
+
+#[rustler::nif(schedule = "DirtyIo")]
+fn sniff_csv(
+    resource: ResourceArc<DuckDBResource>,
+    path: String,
+) -> Result<csv::Sniffed, error::Error> {
+    resource.with_conn(move |conn| csv::sniff(conn, &path))
+}
+
+#[rustler::nif(schedule = "DirtyIo")]
+fn export_database(
+    resource: ResourceArc<DuckDBResource>,
+    dir: String,
+    format: copy::CopyFormat,
+    opts: options::Options,
+) -> Result<String, error::Error> {
+    let sql = copy::export_database_sql(&dir, format, &opts)?;
+    let owner = resource.clone();
+
+    resource.with_conn(move |conn| {
+        let _progress = owner.progress.start(conn, &sql);
+
+        conn.execute_batch(&sql)
+            .map_err(|e| format!("Failed to export database to '{}': {}", dir, e))?;
+
+        Ok("ok".to_string())
+    })
+}
+
+#[rustler::nif(schedule = "DirtyIo")]
+fn import_database(resource: ResourceArc<DuckDBResource>, dir: String) -> Result<String, error::Error> {
+    let sql = copy::import_database_sql(&dir);
+    let owner = resource.clone();
+
+    resource.with_conn(move |conn| {
+        let _progress = owner.progress.start(conn, &sql);
+
+        conn.execute_batch(&sql)
+            .map_err(|e| format!("Failed to import database from '{}': {}", dir, e))?;
+
+        Ok("ok".to_string())
+    })
+}
+
+#[rustler::nif(schedule = "DirtyIo")]
+fn create_macro(
+    resource: ResourceArc<DuckDBResource>,
+    name: String,
+    params: Vec<String>,
+    body: String,
+    opts: options::Options,
+) -> Result<String, error::Error> {
+    let sql = macros::create_sql(&name, &params, &body, &opts)?;
+
+    resource.with_conn(move |conn| {
+        conn.execute_batch(&sql)
+            .map_err(|e| format!("Failed to create macro '{}': {}", name, e))?;
+
+        Ok("ok".to_string())
+    })
+}
+
+#[rustler::nif(schedule = "DirtyIo")]
+fn drop_macro(resource: ResourceArc<DuckDBResource>, name: String) -> Result<String, error::Error> {
+    resource.with_conn(move |conn| {
+        conn.execute_batch(&macros::drop_sql(&name))
+            .map_err(|e| format!("Failed to drop macro '{}': {}", name, e))?;
+
+        Ok("ok".to_string())
+    })
+}
+
+#[rustler::nif(schedule = "DirtyIo")]
+fn create_fts_index(
+    resource: ResourceArc<DuckDBResource>,
+    table: String,
+    id: String,
+    columns: Vec<String>,
+    opts: options::Options,
+) -> Result<String, error::Error> {
+    let sql = fts::create_sql(&table, &id, &columns, &opts)?;
+
+    resource.with_conn(move |conn| {
+        conn.execute_batch(&sql)
+            .map_err(|e| format!("Failed to create FTS index on '{}': {}", table, e))?;
+
+        Ok("ok".to_string())
+    })
+}
+
+#[rustler::nif(schedule = "DirtyIo")]
+fn drop_fts_index(resource: ResourceArc<DuckDBResource>, table: String) -> Result<String, error::Error> {
+    resource.with_conn(move |conn| {
+        conn.execute_batch(&fts::drop_sql(&table))
+            .map_err(|e| format!("Failed to drop FTS index on '{}': {}", table, e))?;
+
+        Ok("ok".to_string())
+    })
+}
+
+#[rustler::nif(schedule = "DirtyIo")]
+fn install_extension(
+    resource: ResourceArc<DuckDBResource>,
+    name: String,
+    opts: options::Options,
+) -> Result<String, error::Error> {
+    let sql = extension::install_sql(&name, &opts)?;
+
+    resource.with_conn(move |conn| {
+        conn.execute_batch(&sql).map_err(|e| {
+            let message = e.to_string();
+            (
+                extension::error_kind(&message),
+                format!("Failed to install extension '{}': {}", name, message),
+            )
+        })?;
+
+        Ok("ok".to_string())
+    })
+}
+
+#[rustler::nif(schedule = "DirtyIo")]
+fn load_extension(resource: ResourceArc<DuckDBResource>, name: String) -> Result<String, error::Error> {
+    resource.with_conn(move |conn| {
+        conn.execute_batch(&extension::load_sql(&name)).map_err(|e| {
+            let message = e.to_string();
+            (
+                extension::error_kind(&message),
+                format!("Failed to load extension '{}': {}", name, message),
+            )
+        })?;
+
+        if name == "spatial" {
+            geometry::register(conn);
+        }
+
+        Ok("ok".to_string())
+    })
+}
+
+#[rustler::nif(schedule = "DirtyIo")]
+fn create_s3_secret(
+    resource: ResourceArc<DuckDBResource>,
+    name: String,
+    opts: options::Options,
+) -> Result<String, error::Error> {
+    let statement = secret::s3(&name, &opts)?;
+
+    resource.with_conn(move |conn| {
+        conn.execute(&statement.sql, params_from_iter(statement.params.iter()))
+            .map_err(|e| format!("Failed to create secret '{}': {}", name, e))?;
+
+        Ok("ok".to_string())
+    })
+}
+
+#[rustler::nif(schedule = "DirtyIo")]
+fn set_http_options(
+    resource: ResourceArc<DuckDBResource>,
+    opts: options::Options,
+) -> Result<String, error::Error> {
+    let statements = http::set_sql(&opts)?;
+
+    resource.with_conn(move |conn| {
+        http::configure(conn, &statements)?;
+
+        Ok("ok".to_string())
+    })
+}
+
+#[rustler::nif(schedule = "DirtyIo")]
+fn create_azure_secret(
+    resource: ResourceArc<DuckDBResource>,
+    name: String,
+    opts: options::Options,
+) -> Result<String, error::Error> {
+    let statement = secret::azure(&name, &opts)?;
+
+    resource.with_conn(move |conn| {
+        conn.execute(&statement.sql, params_from_iter(statement.params.iter()))
+            .map_err(|e| format!("Failed to create secret '{}': {}", name, e))?;
+
+        Ok("ok".to_string())
+    })
+}
+
+#[rustler::nif(schedule = "DirtyIo")]
+fn create_gcs_secret(
+    resource: ResourceArc<DuckDBResource>,
+    name: String,
+    opts: options::Options,
+) -> Result<String, error::Error> {
+    let statement = secret::gcs(&name, &opts)?;
+
+    resource.with_conn(move |conn| {
+        conn.execute(&statement.sql, params_from_iter(statement.params.iter()))
+            .map_err(|e| format!("Failed to create secret '{}': {}", name, e))?;
+
+        Ok("ok".to_string())
+    })
+}
+
+#[rustler::nif(schedule = "DirtyIo")]
+fn set_setting<'a>(
+    resource: ResourceArc<DuckDBResource>,
+    name: String,
+    value: Term<'a>,
+) -> Result<String, error::Error> {
+    let sql = setting::set_sql(&name, value)?;
+
+    resource.with_conn(move |conn| {
+        conn.execute_batch(&sql)
+            .map_err(|e| format!("Failed to set '{}': {}", name, e))?;
+
+        Ok("ok".to_string())
+    })
+}
+
+#[rustler::nif(schedule = "DirtyIo")]
+fn set_timezone(
+    resource: ResourceArc<DuckDBResource>,
+    timezone: String,
+) -> Result<String, error::Error> {
+    resource.with_conn(move |conn| {
+        timezone::set(conn, &timezone)?;
+
+        Ok("ok".to_string())
+    })
+}
+
+#[rustler::nif(schedule = "DirtyIo")]
+fn get_setting(
+    resource: ResourceArc<DuckDBResource>,
+    name: String,
+) -> Result<setting::SettingValue, error::Error> {
+    resource.with_conn(move |conn| setting::get(conn, &name))
+}
+
+#[rustler::nif(schedule = "DirtyIo")]
+fn enable_profiling(resource: ResourceArc<DuckDBResource>, enabled: bool) -> Result<String, error::Error> {
+    let mut profile = resource.profile.lock().map_err(|e| e.to_string())?;
+
+    if enabled {
+        let file = temp::TempFile::new("json")
+            .map_err(|e| format!("Failed to create temporary file: {}", e))?;
+        let sql = format!(
+            "SET enable_profiling = 'json'; SET profiling_output = {};",
+            sql::quote_literal(&file.path_str())
+        );
+
+        resource.with_conn(move |conn| {
+            conn.execute_batch(&sql)
+                .map_err(|e| format!("Failed to enable profiling: {}", e))?;
+
+            Ok(())
+        })?;
+
+        *profile = Some(file);
+    } else {
+        resource.with_conn(|conn| {
+            conn.execute_batch("SET disable_profiling;")
+                .map_err(|e| format!("Failed to disable profiling: {}", e))?;
+
+            Ok(())
+        })?;
+
+        *profile = None;
+    }
+
+    Ok("ok".to_string())
+}
+
+#[rustler::nif(schedule = "DirtyIo")]
+fn last_profile(resource: ResourceArc<DuckDBResource>) -> Result<Option<String>, error::Error> {
+    let profile = resource.profile.lock().map_err(|e| e.to_string())?;
+
+    let path = profile
+        .as_ref()
+        .ok_or_else(|| "Profiling is not enabled".to_string())?
+        .path()
+        .to_path_buf();
+
+    // Read on the worker, so the file is not read while a query writes it
+    resource.with_conn(move |_conn| match std::fs::read_to_string(&path) {
+        Ok(json) => Ok(Some(json)),
+        // Nothing was executed since profiling was enabled
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(None),
+        Err(e) => Err(format!("Failed to read query profile: {}", e).into()),
+    })
+}
+
+#[rustler::nif(schedule = "DirtyIo")]
+fn explain<'a>(
+    env: Env<'a>,
+    resource: ResourceArc<DuckDBResource>,
+    statement: ResourceArc<statement::StatementResource>,
+    opts: options::Options<'a>,
+) -> Result<Term<'a>, error::Error> {
+    let stmt_id = statement.id_in(&resource.queries)?;
+    let analyze = opts.get::<bool>("analyze")?.unwrap_or(false);
+    let params = opts.get::<Vec<Term<'a>>>("params")?.unwrap_or_default();
+
+    let params_vec: Vec<Value> = params
+        .into_iter()
+        .map(|term| term_to_duckdb_value(term))
+        .collect::<Result<Vec<_>, _>>()?;
+
+    let query = resource.query(stmt_id)?;
+
+    let explain = if analyze {
+        "EXPLAIN (ANALYZE, FORMAT json)"
+    } else {
+        "EXPLAIN (FORMAT json)"
+    };
+
+    let row: Vec<Value> = resource.with_conn(move |conn| {
+        let mut stmt = conn
+            .prepare(&format!("{} {}", explain, query))
+            .map_err(|e| format!("SQL preparation error: {}", e))?;
+
+        let row = stmt
+            .query_row(params_from_iter(params_vec.iter()), |row| {
+                Ok((0..).map_while(|i| row.get::<_, Value>(i).ok()).collect())
+            })
+            .map_err(|e| format!("SQL execution error: {}", e))?;
+
+        Ok(row)
+    })?;
+
+    // Plan is in the last column, first one is the plan kind
+    let plan = match row.into_iter().last() {
+        Some(Value::Text(plan)) => plan,
+        _ => return Err("Unexpected EXPLAIN output".to_string().into()),
+    };
+
+    let plan: serde_json::Value =
+        serde_json::from_str(&plan).map_err(|e| format!("Failed to parse query plan: {}", e))?;
+
+    Ok(json::to_term(env, &plan))
+}
+
+#[rustler::nif(schedule = "DirtyIo")]
+fn describe(
+    resource: ResourceArc<DuckDBResource>,
+    query: String,
+) -> Result<Vec<describe::Column>, error::Error> {
+    resource.with_conn(move |conn| describe::columns(conn, &query))
+}
+
+#[rustler::nif(schedule = "DirtyIo")]
+fn statement_params(
+    resource: ResourceArc<DuckDBResource>,
+    statement: ResourceArc<statement::StatementResource>,
+) -> Result<params::StatementParams, error::Error> {
+    let stmt_id = statement.id_in(&resource.queries)?;
+    let query = resource.query(stmt_id)?;
+
+    resource.with_conn(move |conn| params::describe(conn, &query))
+}
+
+#[rustler::nif(schedule = "DirtyIo")]
+fn temp_usage(resource: ResourceArc<DuckDBResource>) -> Result<spill::Usage, error::Error> {
+    resource.with_conn(|conn| spill::usage(conn))
+}
+
+#[rustler::nif(schedule = "DirtyIo")]
+fn memory_stats(resource: ResourceArc<DuckDBResource>) -> Result<memory::Stats, error::Error> {
+    let owner = resource.clone();
+
+    resource.with_conn(move |conn| memory::stats(conn, &owner.peak_memory))
+}
+
+#[rustler::nif]
+fn cache_stats(resource: ResourceArc<DuckDBResource>) -> Result<cache::Stats, error::Error> {
+    Ok(resource.lock_queries()?.stats())
+}
+
+#[rustler::nif]
+fn cache_clear(resource: ResourceArc<DuckDBResource>) -> Result<String, error::Error> {
+    resource.lock_queries()?.clear();
+    Ok("ok".to_string())
+}
+
+#[rustler::nif]
+fn cache_resize(
+    resource: ResourceArc<DuckDBResource>,
+    capacity: usize,
+) -> Result<String, error::Error> {
+    resource.lock_queries()?.resize(capacity);
+    Ok("ok".to_string())
+}
+
+#[rustler::nif]
+fn progress(resource: ResourceArc<DuckDBResource>) -> progress::ProgressInfo {
+    resource.progress.info()
+}
+
+#[rustler::nif]
+fn close(
+    resource: ResourceArc<DuckDBResource>,
+    statement: ResourceArc<statement::StatementResource>,
+) -> Result<String, error::Error> {
+    let stmt_id = statement.id_in(&resource.queries)?;
+    let mut queries = resource.lock_queries()?;
+    queries.remove(stmt_id);
+    Ok("ok".to_string())
+}
+
+// DuckDB has no savepoints, so transactions cannot be nested
+#[rustler::nif(schedule = "DirtyIo")]
+fn begin(
+    resource: ResourceArc<DuckDBResource>,
+    opts: options::Options,
+) -> Result<String, error::Error> {
+    let read_only = opts.get::<bool>("read_only")?.unwrap_or(false);
+    let owner = resource.clone();
+
+    resource.with_conn(move |conn| {
+        if owner.in_transaction() {
+            return Err(error::Error::new(
+                error::ErrorKind::NotImplemented,
+                "Nested transactions are not supported, DuckDB has no savepoints",
+            ));
+        }
+
+        // DuckDB itself rejects writes in read-only transaction
+        let sql = if read_only {
+            "BEGIN TRANSACTION READ ONLY"
+        } else {
+            "BEGIN"
+        };
+
+        let mut stmt = conn
+            .prepare(sql)
+            .map_err(|e| format!("SQL preparation error: {}", e))?;
+
+        stmt.execute([])
+            .map_err(|e| format!("SQL execution error: {}", e))?;
+
+        owner.set_transaction(true);
+
+        Ok("ok".to_string())
+    })
+}
+
+#[rustler::nif(schedule = "DirtyIo")]
+fn commit(resource: ResourceArc<DuckDBResource>) -> Result<String, error::Error> {
+    let owner = resource.clone();
+
+    resource.with_conn(move |conn| {
+        let mut stmt = conn
+            .prepare("COMMIT")
+            .map_err(|e| format!("SQL preparation error: {}", e))?;
+
+        let result = stmt.execute([]);
+
+        // Transaction is over even when it failed to commit
+        owner.set_transaction(false);
+
+        result.map_err(|e| format!("SQL execution error: {}", e))?;
+
+        Ok("ok".to_string())
+    })
+}
+
+#[rustler::nif(schedule = "DirtyIo")]
+fn rollback(resource: ResourceArc<DuckDBResource>) -> Result<String, error::Error> {
+    let owner = resource.clone();
+
+    resource.with_conn(move |conn| {
+        let mut stmt = conn
+            .prepare("ROLLBACK")
+            .map_err(|e| format!("SQL preparation error: {}", e))?;
+
+        let result = stmt.execute([]);
+
+        // Transaction is over even when it failed to rollback
+        owner.set_transaction(false);
+
+        result.map_err(|e| format!("SQL execution error: {}", e))?;
+
+        Ok("ok".to_string())
+    })
+}
+
+#[rustler::nif]
+fn transaction_status(
+    resource: ResourceArc<DuckDBResource>,
+) -> Result<transaction::Status, error::Error> {
+    if resource.in_transaction() {
+        Ok(transaction::Status::Transaction)
+    } else {
+        Ok(transaction::Status::Idle)
+    }
+}
+
+#[rustler::nif(schedule = "DirtyIo")]
+fn execute_batch(resource: ResourceArc<DuckDBResource>, sql: String) -> Result<String, error::Error> {
+    let owner = resource.clone();
+
+    resource.with_conn(move |conn| {
+        // Already open transaction covers the script, DuckDB does not nest
+        // them
+        if owner.in_transaction() {
+            conn.execute_batch(&sql)
+                .map_err(|e| format!("SQL execution error: {}", e))?;
+
+            return Ok("ok".to_string());
+        }
+
+        // Run the whole script inside a single transaction so it either
+        // applies completely or not at all
+        let tx = conn
+            .transaction()
+            .map_err(|e| format!("SQL execution error: {}", e))?;
+
+        tx.execute_batch(&sql)
+            .map_err(|e| format!("SQL execution error: {}", e))?;
+
+        tx.commit()
+            .map_err(|e| format!("SQL execution error: {}", e))?;
+
+        Ok("ok".to_string())
+    })
+}
+
+#[rustler::nif(schedule = "DirtyIo")]
+fn attach(
+    resource: ResourceArc<DuckDBResource>,
+    path: String,
+    opts: options::Options,
+) -> Result<database::DatabaseInfo, error::Error> {
+    let sql = database::attach_sql(&path, &opts)?;
+
+    resource.with_conn(move |conn| {
+        let before = database::list(conn)?;
+
+        conn.execute_batch(&sql)
+            .map_err(|e| format!("Failed to attach database '{}': {}", path, e))?;
+
+        database::attached(conn, &before, &path)
+    })
+}
+
+#[rustler::nif(schedule = "DirtyIo")]
+fn attach_iceberg(
+    resource: ResourceArc<DuckDBResource>,
+    warehouse: String,
+    opts: options::Options,
+    credentials: options::Options,
+) -> Result<database::DatabaseInfo, error::Error> {
+    let alias = opts.get_string("as")?.unwrap_or_else(|| warehouse.clone());
+    let secret_name = format!("duckex_iceberg_{}", alias);
+
+    let secret = match credentials.keys().next() {
+        Some(_) => Some(secret::iceberg(&secret_name, &credentials)?),
+        None => None,
+    };
+
+    let sql = lakehouse::iceberg_sql(
+        &warehouse,
+        secret.is_some().then_some(secret_name.as_str()),
+        &opts,
+    )?;
+
+    resource.with_conn(move |conn| {
+        lakehouse::ensure_extension(conn, "iceberg")?;
+
+        if let Some(secret) = secret {
+            conn.execute(&secret.sql, params_from_iter(secret.params.iter()))
+                .map_err(|e| format!("Failed to create secret '{}': {}", secret_name, e))?;
+        }
+
+        let before = database::list(conn)?;
+
+        conn.execute_batch(&sql).map_err(|e| {
+            lakehouse::error(format!("Failed to attach catalog '{}': {}", warehouse, e))
+        })?;
+
+        database::attached(conn, &before, &warehouse)
+    })
+}
+
+#[rustler::nif(schedule = "DirtyIo")]
+fn attach_delta(
+    resource: ResourceArc<DuckDBResource>,
+    path: String,
+    opts: options::Options,
+) -> Result<database::DatabaseInfo, error::Error> {
+    let sql = lakehouse::delta_sql(&path, &opts)?;
+
+    resource.with_conn(move |conn| {
+        lakehouse::ensure_extension(conn, "delta")?;
+
+        let before = database::list(conn)?;
+
+        conn.execute_batch(&sql).map_err(|e| {
+            lakehouse::error(format!("Failed to attach Delta table '{}': {}", path, e))
+        })?;
+
+        database::attached(conn, &before, &path)
+    })
+}
+
+#[rustler::nif(schedule = "DirtyIo")]
+fn detach(resource: ResourceArc<DuckDBResource>, name: String) -> Result<String, error::Error> {
+    resource.with_conn(move |conn| {
+        conn.execute_batch(&database::detach_sql(&name))
+            .map_err(|e| format!("Failed to detach database '{}': {}", name, e))?;
+
+        Ok("ok".to_string())
+    })
+}
+
+#[rustler::nif(schedule = "DirtyIo")]
+fn list_databases(
+    resource: ResourceArc<DuckDBResource>,
+) -> Result<Vec<database::DatabaseInfo>, error::Error> {
+    resource.with_conn(|conn| database::list(conn))
+}
+
+#[rustler::nif(schedule = "DirtyIo")]
+fn database_size(
+    resource: ResourceArc<DuckDBResource>,
+) -> Result<Vec<database::Size>, error::Error> {
+    resource.with_conn(|conn| database::size(conn))
+}
+
+#[rustler::nif(schedule = "DirtyIo")]
+fn checkpoint(resource: ResourceArc<DuckDBResource>, force: bool) -> Result<String, error::Error> {
+    // FORCE CHECKPOINT aborts other running transactions instead of failing
+    let sql = if force { "FORCE CHECKPOINT" } else { "CHECKPOINT" };
+
+    resource.with_conn(move |conn| {
+        conn.execute_batch(sql)
+            .map_err(|e| format!("Failed to checkpoint: {}", e))?;
+
+        Ok("ok".to_string())
+    })
+}
+
+/// Checkpoint and close every open connection, called when the VM shuts down
+#[rustler::nif(schedule = "DirtyIo")]
+fn close_all() -> usize {
+    shutdown::close_all()
+}
+
+#[rustler::nif(schedule = "DirtyIo")]
+fn disconnect(resource: ResourceArc<DuckDBResource>) -> Result<String, error::Error> {
+    resource.ensure_not_poisoned()?;
+
+    // Closing releases the database file lock, once clones are closed as well
+    resource.readers.close();
+
+    let closed = resource.worker.run(resource.lock_timeout, |conn| {
+        let Some(open) = conn.take() else {
+            return Ok(false);
+        };
+
+        // Fails when there is no open transaction, which is fine
+        let _ = open.execute_batch("ROLLBACK");
+
+        if let Err((open, e)) = open.close() {
+            *conn = Some(open);
+            return Err(format!("Failed to close connection: {}", e).into());
+        }
+
+        Ok(true)
+    });
+
+    match closed.inspect_err(|e| resource.mark_poisoned(e)) {
+        Ok(true) => {
+            resource.set_transaction(false);
+            resource.lock_queries()?.clear();
+        }
+        // Already closed
+        Ok(false) => {}
+        Err(e) => {
+            resource.readers.reopen();
+            return Err(e);
+        }
+    }
+
+    Ok("ok".to_string())
+}
+
+#[rustler::nif(schedule = "DirtyIo")]
+fn status(resource: ResourceArc<DuckDBResource>) -> Result<status::Status, error::Error> {
+    let mut status = status::Status {
+        state: status::State::Idle,
+        database: resource.database.clone(),
+        access_mode: None,
+        error: None,
+        queued: resource.worker.queued(),
+    };
+
+    let access_mode = resource.ensure_not_poisoned().and_then(|_| {
+        resource.worker.run(Some(status::LOCK_TIMEOUT), |conn| {
+            let conn = conn.as_ref().ok_or_else(error::Error::closed)?;
+
+            // Broken connection is reported in the status, not as an error
+            Ok(conn
+                .query_row("SELECT current_setting('access_mode')", [], |row| row.get(0))
+                .map_err(|e| e.to_string()))
+        })
+    });
+
+    let access_mode = match access_mode.inspect_err(|e| resource.mark_poisoned(e)) {
+        Ok(access_mode) => access_mode,
+        Err(e) if e.kind == error::ErrorKind::Busy => {
+            status.state = status::State::Busy;
+            return Ok(status);
+        }
+        Err(e) if e.kind == error::ErrorKind::Closed => {
+            status.state = status::State::Closed;
+            return Ok(status);
+        }
+        Err(e) => {
+            status.state = status::State::Broken;
+            status.error = Some(e.message);
+            return Ok(status);
+        }
+    };
+
+    match access_mode {
+        Ok(access_mode) => status.access_mode = Some(access_mode),
+        Err(e) => {
+            status.state = status::State::Broken;
+            status.error = Some(e);
+        }
+    }
+
+    Ok(status)
+}
+
+// Helper function to convert Elixir terms to DuckDB values
+fn term_to_duckdb_value(term: Term) -> Result<Value, error::Error> {
+    // Check for Date, Time and DateTime structs first
+    if let Some((module, fields)) = datetime::struct_fields(term) {
+        if let Some(value) = datetime::decode(&module, &fields)? {
+            return Ok(value);
+        }
+    }
+
+    if term.is_number() {
+        if let Ok(i) = term.decode::<i64>() {
+            return Ok(Value::BigInt(i));
+        }
+        // Larger integers are cast to VARINT parameters by DuckDB, text form is
+        // used only for those which do not fit HUGEINT either
+        if let Ok(i) = term.decode::<num_bigint::BigInt>() {
+            return Ok(match i128::try_from(&i) {
+                Ok(i) => Value::HugeInt(i),
+                Err(_) => Value::Text(i.to_string()),
+            });
+        }
+        if let Ok(f) = term.decode::<f64>() {
+            return Ok(Value::Double(f));
+        }
+    }
+
+    if let Ok(s) = term.decode::<String>() {
+        return Ok(Value::Text(s));
+    }
+
+    // Binaries which are not valid UTF-8 cannot be text, so they are bound as
+    // blobs, unless the connection has `strict_text` set
+    if let Ok(b) = term.decode::<Binary>() {
+        return Ok(Value::Blob(b.as_slice().to_vec()));
+    }
+
+    if let Ok(b) = term.decode::<bool>() {
+        return Ok(Value::Boolean(b));
+    }
+
+    if term.is_atom() {
+        if let Ok(atom_str) = term.atom_to_string() {
+            if atom_str == "nil" {
+                return Ok(Value::Null);
+            }
+        }
+
+        if let Some(f) = float::decode(term) {
+            return Ok(Value::Double(f));
+        }
+
+        // Other atoms are bound as text, e.g. as members of ENUM types
+        if let Ok(name) = term.atom_to_string() {
+            return Ok(Value::Text(name));
+        }
+    }
+
+    // `{:array, values}` is fixed-size ARRAY
+    if let Ok((tag, items)) = term.decode::<(Term, Vec<Term>)>() {
+        if tag.atom_to_string().is_ok_and(|tag| tag == "array") {
+            return term_to_array(items);
+        }
+    }
+
+    // `{%Time{}, utc_offset}` is TIME WITH TIME ZONE
+    if let Ok((time, offset)) = term.decode::<(Term, i32)>() {
+        if let Some(value) = datetime::time_tz(time, offset)? {
+            return Ok(value);
+        }
+    }
+
+    // Provide detailed type information in error message
+    let type_info = if term.is_number() {
+        "number (but failed to decode as i64 or f64)"
+    } else if term.is_map() {
+        "map (unsupported structure)"
+    } else if term.is_list() {
+        "list (not yet supported)"
+    } else if term.is_tuple() {
+        "tuple (not supported)"
+    } else {
+        "unknown type"
+    };
+
+    Err(format!("Unsupported parameter type: {}", type_info).into())
+}
+
+// Nested lists are arrays as well, e.g. `{:array, [[1, 2], [3, 4]]}`
+fn term_to_array(items: Vec<Term>) -> Result<Value, error::Error> {
+    items
+        .into_iter()
+        .map(|item| match item.decode::<Vec<Term>>() {
+            Ok(nested) => term_to_array(nested),
+            Err(_) => term_to_duckdb_value(item),
+        })
+        .collect::<Result<_, _>>()
+        .map(Value::Array)
 }
 
 fn on_load(env: Env, _info: Term) -> bool {
     rustler::resource!(DuckDBResource, env)
+        && rustler::resource!(appender::AppenderResource, env)
+        && rustler::resource!(ingest::IngestResource, env)
+        && rustler::resource!(blob::BlobResource, env)
+        && rustler::resource!(result::ResultResource, env)
+        && rustler::resource!(stream::ArrowStreamResource, env)
+        && rustler::resource!(statement::StatementResource, env)
+        && env.register::<caller::CallerResource>().is_ok()
 }
 
 rustler::init!("Elixir.Duckex.Native", load = on_load);
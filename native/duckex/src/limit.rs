@@ -0,0 +1,63 @@
+// SPDX-FileCopyrightText: 2025 Stas Muzhyk <sts@abc3.dev>
+// SPDX-FileCopyrightText: 2025 Łukasz Niemier <~@hauleth.dev>
+//
+// SPDX-License-Identifier: Apache-2.0
+
+use duckdb::types::ValueRef;
+
+use crate::error::{Error, ErrorKind};
+use crate::options::Options;
+
+/// Bounds of the result materialized by single query. Query exceeding them
+/// fails, instead of exhausting the memory of the whole VM.
+#[derive(Clone, Copy, Default)]
+pub(crate) struct Limits {
+    max_rows: Option<usize>,
+    max_bytes: Option<usize>,
+}
+
+impl Limits {
+    pub(crate) fn new(opts: &Options) -> Result<Self, String> {
+        Ok(Limits {
+            max_rows: opts.get::<usize>("max_rows")?,
+            max_bytes: opts.get::<usize>("max_result_bytes")?,
+        })
+    }
+
+    /// Limits set here, falling back to `defaults` for the ones that are not
+    pub(crate) fn or(self, defaults: Limits) -> Limits {
+        Limits {
+            max_rows: self.max_rows.or(defaults.max_rows),
+            max_bytes: self.max_bytes.or(defaults.max_bytes),
+        }
+    }
+
+    pub(crate) fn counts_bytes(&self) -> bool {
+        self.max_bytes.is_some()
+    }
+
+    pub(crate) fn check(&self, rows: usize, bytes: usize) -> Result<(), Error> {
+        if let Some(max) = self.max_rows.filter(|&max| rows > max) {
+            return Err(too_large(format!("Result exceeds max_rows of {}", max)));
+        }
+
+        if let Some(max) = self.max_bytes.filter(|&max| bytes > max) {
+            return Err(too_large(format!("Result exceeds max_result_bytes of {}", max)));
+        }
+
+        Ok(())
+    }
+}
+
+fn too_large(message: String) -> Error {
+    Error::new(ErrorKind::ResultTooLarge, message)
+}
+
+/// Approximate size of the value once materialized, nested values are
+/// counted by their fixed part only
+pub(crate) fn size(value: &ValueRef) -> usize {
+    match value {
+        ValueRef::Text(bytes) | ValueRef::Blob(bytes) => bytes.len(),
+        _ => std::mem::size_of::<u64>(),
+    }
+}
@@ -0,0 +1,44 @@
+// SPDX-FileCopyrightText: 2025 Stas Muzhyk <sts@abc3.dev>
+// SPDX-FileCopyrightText: 2025 Łukasz Niemier <~@hauleth.dev>
+//
+// SPDX-License-Identifier: Apache-2.0
+
+use std::sync::{Mutex, MutexGuard, TryLockError};
+use std::time::{Duration, Instant};
+
+use crate::error::Error;
+
+// How long to sleep between attempts to take the lock
+const RETRY_INTERVAL: Duration = Duration::from_millis(1);
+
+/// Lock the mutex, giving up with busy error when it cannot be acquired
+/// within `timeout`. Without timeout it blocks until the lock is available.
+///
+/// When the mutex was poisoned by a panic, the poison is cleared (so the lock
+/// is usable again once the owner resets its state) and poisoned error is
+/// returned.
+pub(crate) fn acquire<T>(
+    mutex: &Mutex<T>,
+    timeout: Option<Duration>,
+) -> Result<MutexGuard<'_, T>, Error> {
+    let Some(timeout) = timeout else {
+        return mutex.lock().map_err(|_| poisoned(mutex));
+    };
+
+    let deadline = Instant::now() + timeout;
+
+    loop {
+        match mutex.try_lock() {
+            Ok(guard) => return Ok(guard),
+            Err(TryLockError::Poisoned(_)) => return Err(poisoned(mutex)),
+            Err(TryLockError::WouldBlock) if Instant::now() >= deadline => return Err(Error::busy()),
+            Err(TryLockError::WouldBlock) => std::thread::sleep(RETRY_INTERVAL),
+        }
+    }
+}
+
+fn poisoned<T>(mutex: &Mutex<T>) -> Error {
+    mutex.clear_poison();
+
+    Error::poisoned()
+}
@@ -0,0 +1,37 @@
+// SPDX-FileCopyrightText: 2025 Stas Muzhyk <sts@abc3.dev>
+// SPDX-FileCopyrightText: 2025 Łukasz Niemier <~@hauleth.dev>
+//
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::options::Options;
+use crate::sql::quote_identifier;
+
+/// Scalar macro defined by SQL expression over its parameters, e.g.
+/// `normalize(s) AS lower(trim(s))`. DuckDB expands it into the queries calling
+/// it, so it cannot run Elixir code.
+pub(crate) fn create_sql(
+    name: &str,
+    params: &[String],
+    body: &str,
+    opts: &Options,
+) -> Result<String, String> {
+    let temporary = if opts.get::<bool>("temporary")?.unwrap_or(true) {
+        "TEMP "
+    } else {
+        ""
+    };
+
+    let params: Vec<_> = params.iter().map(|p| quote_identifier(p)).collect();
+
+    Ok(format!(
+        "CREATE OR REPLACE {}MACRO {}({}) AS ({})",
+        temporary,
+        quote_identifier(name),
+        params.join(", "),
+        body
+    ))
+}
+
+pub(crate) fn drop_sql(name: &str) -> String {
+    format!("DROP MACRO IF EXISTS {}", quote_identifier(name))
+}
@@ -0,0 +1,58 @@
+// SPDX-FileCopyrightText: 2025 Stas Muzhyk <sts@abc3.dev>
+// SPDX-FileCopyrightText: 2025 Łukasz Niemier <~@hauleth.dev>
+//
+// SPDX-License-Identifier: Apache-2.0
+
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicU64, Ordering};
+
+use duckdb::Connection;
+use rustler::NifMap;
+
+use crate::error::Error;
+
+/// Buffer manager usage of the database
+#[derive(NifMap)]
+pub(crate) struct Stats {
+    // Bytes currently held in memory
+    memory_usage: u64,
+    // Highest `memory_usage` reported for this connection so far, DuckDB does
+    // not track it on its own
+    peak_memory_usage: u64,
+    // As reported by DuckDB, e.g. "12.4 GiB"
+    memory_limit: String,
+    // Bytes offloaded to temporary storage
+    temporary_storage: u64,
+    // Bytes in memory per component, e.g. "BASE_TABLE" or "HASH_TABLE"
+    by_tag: HashMap<String, u64>,
+}
+
+pub(crate) fn stats(conn: &Connection, peak: &AtomicU64) -> Result<Stats, Error> {
+    let memory_limit: String = conn
+        .query_row("SELECT current_setting('memory_limit')", [], |row| row.get(0))
+        .map_err(|e| format!("Failed to read memory limit: {}", e))?;
+
+    let mut stmt = conn
+        .prepare("SELECT tag, memory_usage_bytes, temporary_storage_bytes FROM duckdb_memory()")
+        .map_err(|e| format!("Failed to read memory usage: {}", e))?;
+
+    let tags = stmt
+        .query_map([], |row| Ok((row.get::<_, String>(0)?, row.get(1)?, row.get(2)?)))
+        .and_then(|rows| rows.collect::<Result<Vec<(String, u64, u64)>, _>>())
+        .map_err(|e| format!("Failed to read memory usage: {}", e))?;
+
+    let memory_usage = tags.iter().map(|(_, memory, _)| memory).sum();
+    let temporary_storage = tags.iter().map(|(_, _, temporary)| temporary).sum();
+    let peak_memory_usage = peak.fetch_max(memory_usage, Ordering::Relaxed).max(memory_usage);
+
+    Ok(Stats {
+        memory_usage,
+        peak_memory_usage,
+        memory_limit,
+        temporary_storage,
+        by_tag: tags
+            .into_iter()
+            .map(|(tag, memory, _)| (tag, memory))
+            .collect(),
+    })
+}
@@ -0,0 +1,49 @@
+// SPDX-FileCopyrightText: 2025 Stas Muzhyk <sts@abc3.dev>
+// SPDX-FileCopyrightText: 2025 Łukasz Niemier <~@hauleth.dev>
+//
+// SPDX-License-Identifier: Apache-2.0
+
+// MotherDuck databases are opened with `md:` (or `motherduck:`) paths, DuckDB
+// loads the `motherduck` extension for them on its own
+
+use duckdb::Config;
+
+use crate::error::{Error, ErrorKind};
+use crate::options::Options;
+
+const PREFIXES: &[&str] = &["md:", "motherduck:"];
+
+// Read by the extension itself as well, it is passed explicitly so the token
+// given in options takes precedence
+const TOKEN_ENV: &[&str] = &["motherduck_token", "MOTHERDUCK_TOKEN"];
+
+pub(crate) fn is_motherduck(path: &str) -> bool {
+    PREFIXES.iter().any(|prefix| path.starts_with(prefix))
+}
+
+/// Set MotherDuck token from `:motherduck_token` option, or environment
+pub(crate) fn configure(config: Config, opts: &Options) -> Result<Config, Error> {
+    let token = match opts.get_string("motherduck_token")? {
+        Some(token) => Some(token),
+        None => TOKEN_ENV.iter().find_map(|name| std::env::var(name).ok()),
+    };
+
+    let Some(token) = token else {
+        return Ok(config);
+    };
+
+    let config = config
+        .with("motherduck_token", &token)
+        .map_err(|e| (ErrorKind::Motherduck, format!("Invalid MotherDuck token: {}", e)))?;
+
+    Ok(config)
+}
+
+/// Opening MotherDuck database fails for reasons local databases do not, like
+/// missing or expired token, so these are reported with their own kind
+pub(crate) fn error(path: &str, error: duckdb::Error) -> Error {
+    Error::new(
+        ErrorKind::Motherduck,
+        format!("Failed to connect to MotherDuck database '{}': {}", path, error),
+    )
+}
@@ -0,0 +1,36 @@
+// SPDX-FileCopyrightText: 2025 Stas Muzhyk <sts@abc3.dev>
+// SPDX-FileCopyrightText: 2025 Łukasz Niemier <~@hauleth.dev>
+//
+// SPDX-License-Identifier: Apache-2.0
+
+use duckdb::Connection;
+use rustler::NifMap;
+
+use crate::describe::{self, Column};
+use crate::error::Error;
+use crate::options::Options;
+use crate::sql::{create_table_as, quote_identifier, quote_literal};
+
+/// Number of loaded rows and schema inferred by DuckDB JSON reader
+#[derive(NifMap)]
+pub(crate) struct Loaded {
+    rows: usize,
+    columns: Vec<Column>,
+}
+
+/// Statement loading newline-delimited JSON file at `path` into new table
+/// `table`
+pub(crate) fn load_sql(table: &str, path: &str, opts: &Options) -> Result<String, String> {
+    create_table_as(table, &format!("read_ndjson({})", quote_literal(path)), opts)
+}
+
+/// Run statement built with `load_sql`
+pub(crate) fn load(conn: &Connection, table: &str, sql: &str) -> Result<Loaded, Error> {
+    let rows = conn
+        .execute(sql, [])
+        .map_err(|e| format!("SQL execution error: {}", e))?;
+
+    let columns = describe::columns(conn, &format!("SELECT * FROM {}", quote_identifier(table)))?;
+
+    Ok(Loaded { rows, columns })
+}
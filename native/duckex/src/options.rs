@@ -0,0 +1,76 @@
+// SPDX-FileCopyrightText: 2025 Stas Muzhyk <sts@abc3.dev>
+// SPDX-FileCopyrightText: 2025 Łukasz Niemier <~@hauleth.dev>
+//
+// SPDX-License-Identifier: Apache-2.0
+
+use rustler::types::map::MapIterator;
+use rustler::{Decoder, Error, NifResult, Term};
+
+/// Options passed from Elixir either as a keyword list or as a map with atom
+/// keys
+pub(crate) struct Options<'a> {
+    entries: Vec<(String, Term<'a>)>,
+}
+
+impl<'a> Decoder<'a> for Options<'a> {
+    fn decode(term: Term<'a>) -> NifResult<Self> {
+        let pairs: Vec<(Term<'a>, Term<'a>)> = if term.is_map() {
+            MapIterator::new(term).ok_or(Error::BadArg)?.collect()
+        } else {
+            term.decode()?
+        };
+
+        let entries = pairs
+            .into_iter()
+            .map(|(key, value)| Ok((key.atom_to_string()?, value)))
+            .collect::<NifResult<Vec<_>>>()?;
+
+        Ok(Options { entries })
+    }
+}
+
+impl<'a> Options<'a> {
+    /// Names of all passed options, in the order they were given
+    pub(crate) fn keys(&self) -> impl Iterator<Item = &str> + use<'_, 'a> {
+        self.entries.iter().map(|(key, _)| key.as_str())
+    }
+
+    /// Raw value of the option, last one wins when key is duplicated
+    pub(crate) fn get_term(&self, key: &str) -> Option<Term<'a>> {
+        self.entries
+            .iter()
+            .rev()
+            .find(|(k, _)| k == key)
+            .map(|(_, value)| *value)
+    }
+
+    /// Decoded value of the option, `nil` is treated as if the option was not
+    /// set at all
+    pub(crate) fn get<T: Decoder<'a>>(&self, key: &str) -> Result<Option<T>, String> {
+        match self.get_term(key) {
+            None => Ok(None),
+            Some(term) if is_nil(term) => Ok(None),
+            Some(term) => term
+                .decode::<T>()
+                .map(Some)
+                .map_err(|_| format!("Invalid value for option :{}", key)),
+        }
+    }
+
+    /// Value of the option that can be given either as an atom or a string
+    pub(crate) fn get_string(&self, key: &str) -> Result<Option<String>, String> {
+        match self.get_term(key) {
+            None => Ok(None),
+            Some(term) if is_nil(term) => Ok(None),
+            Some(term) if term.is_atom() => term
+                .atom_to_string()
+                .map(Some)
+                .map_err(|_| format!("Invalid value for option :{}", key)),
+            Some(_) => self.get::<String>(key),
+        }
+    }
+}
+
+fn is_nil(term: Term) -> bool {
+    term.atom_to_string().map(|atom| atom == "nil").unwrap_or(false)
+}
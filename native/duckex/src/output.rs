@@ -0,0 +1,308 @@
+// SPDX-FileCopyrightText: 2025 Stas Muzhyk <sts@abc3.dev>
+// SPDX-FileCopyrightText: 2025 Łukasz Niemier <~@hauleth.dev>
+//
+// SPDX-License-Identifier: Apache-2.0
+
+// Results serialized straight into a binary, for callers which only pass them
+// on, e.g. as HTTP response body, and have no use for terms
+
+use arrow::datatypes::DataType;
+use base64::{engine::general_purpose, Engine as _};
+use chrono::{DateTime, NaiveTime};
+use duckdb::types::{TimeUnit, Value};
+use rustler::NifUnitEnum;
+use serde::ser::{SerializeMap, SerializeSeq};
+use serde::{Serialize, Serializer};
+use serde_json::Value as Json;
+
+use crate::duckdb_value_to_string;
+use crate::options::Options;
+use crate::union::{self, Tags};
+
+/// How rows of the result are returned
+#[derive(NifUnitEnum, Clone, Copy, PartialEq, Eq, Default)]
+pub(crate) enum Format {
+    // List of rows (or columns) of terms
+    #[default]
+    Terms,
+    // JSON array of objects, one per row
+    Json,
+    // RFC 4180 CSV with header row
+    Csv,
+}
+
+/// Characters used by CSV output
+#[derive(Clone, Copy)]
+pub(crate) struct Csv {
+    delimiter: char,
+    quote: char,
+}
+
+impl Csv {
+    pub(crate) fn new(opts: &Options) -> Result<Self, String> {
+        Ok(Csv {
+            delimiter: char_option(opts, "delimiter")?.unwrap_or(','),
+            quote: char_option(opts, "quote")?.unwrap_or('"'),
+        })
+    }
+
+    /// Serialize rows with header row of column names. Values are written the
+    /// same way as with JSON output, nested ones as JSON text and `NULL` as
+    /// an empty field.
+    pub(crate) fn write(
+        &self,
+        columns: &[(String, DataType)],
+        rows: &[Vec<Value>],
+        tags: &[Vec<Vec<String>>],
+    ) -> Result<Vec<u8>, String> {
+        let mut out = String::new();
+
+        self.write_row(&mut out, columns.iter().map(|(name, _)| name.clone()));
+
+        for (values, tags) in rows.iter().zip(tags) {
+            let fields = columns
+                .iter()
+                .zip(values)
+                .enumerate()
+                .map(|(idx, ((_, data_type), value))| {
+                    field(value, data_type, &union::column(tags, idx))
+                })
+                .collect::<Result<Vec<_>, _>>()?;
+
+            self.write_row(&mut out, fields.into_iter());
+        }
+
+        Ok(out.into_bytes())
+    }
+
+    fn write_row(&self, out: &mut String, fields: impl Iterator<Item = String>) {
+        for (idx, field) in fields.enumerate() {
+            if idx > 0 {
+                out.push(self.delimiter);
+            }
+
+            let special = |c: char| {
+                c == self.delimiter || c == self.quote || c == '\r' || c == '\n'
+            };
+
+            if field.contains(special) {
+                let quote = self.quote.to_string();
+
+                out.push(self.quote);
+                out.push_str(&field.replace(self.quote, &quote.repeat(2)));
+                out.push(self.quote);
+            } else {
+                out.push_str(&field);
+            }
+        }
+
+        out.push_str("\r\n");
+    }
+}
+
+fn char_option(opts: &Options, key: &str) -> Result<Option<char>, String> {
+    match opts.get::<String>(key)? {
+        None => Ok(None),
+        Some(value) => {
+            let mut chars = value.chars();
+
+            match (chars.next(), chars.next()) {
+                (Some(c), None) if c != '\r' && c != '\n' => Ok(Some(c)),
+                _ => Err(format!("Invalid value for option :{}", key)),
+            }
+        }
+    }
+}
+
+fn field(value: &Value, data_type: &DataType, tags: &Tags) -> Result<String, String> {
+    let json = serde_json::to_value(Cell::new(value, Some(data_type), tags))
+        .map_err(|e| format!("Failed to serialize result to CSV: {}", e))?;
+
+    Ok(match json {
+        Json::Null => String::new(),
+        Json::String(s) => s,
+        other => other.to_string(),
+    })
+}
+
+/// Serialize rows into JSON array of objects keyed by column names, in the
+/// order of columns
+pub(crate) fn json(
+    columns: &[(String, DataType)],
+    rows: &[Vec<Value>],
+    tags: &[Vec<Vec<String>>],
+) -> Result<Vec<u8>, String> {
+    let rows: Vec<_> = rows
+        .iter()
+        .zip(tags)
+        .map(|(values, tags)| Row { columns, values, tags })
+        .collect();
+
+    serde_json::to_vec(&rows).map_err(|e| format!("Failed to serialize result to JSON: {}", e))
+}
+
+struct Row<'a> {
+    columns: &'a [(String, DataType)],
+    values: &'a [Value],
+    tags: &'a [Vec<String>],
+}
+
+impl Serialize for Row<'_> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let mut map = serializer.serialize_map(Some(self.values.len()))?;
+
+        for (idx, ((name, data_type), value)) in self.columns.iter().zip(self.values).enumerate() {
+            let tags = union::column(self.tags, idx);
+            map.serialize_entry(name, &Cell::new(value, Some(data_type), &tags))?;
+        }
+
+        map.end()
+    }
+}
+
+// Column type is needed to tell `TIMESTAMPTZ` from `TIMESTAMP`, nested values
+// carry type of their own field. They share tags of UNION values with the
+// column value, taken as the unions are serialized.
+struct Cell<'a> {
+    value: &'a Value,
+    data_type: Option<&'a DataType>,
+    tags: &'a Tags<'a>,
+}
+
+impl<'a> Cell<'a> {
+    fn new(value: &'a Value, data_type: Option<&'a DataType>, tags: &'a Tags<'a>) -> Self {
+        Cell {
+            value,
+            data_type,
+            tags,
+        }
+    }
+
+    fn child(&self, idx: usize) -> Option<&'a DataType> {
+        match self.data_type? {
+            DataType::List(field)
+            | DataType::LargeList(field)
+            | DataType::FixedSizeList(field, _) => Some(field.data_type()),
+            DataType::Struct(fields) => fields.get(idx).map(|field| field.data_type()),
+            _ => None,
+        }
+    }
+}
+
+impl Serialize for Cell<'_> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        match self.value {
+            Value::Null => serializer.serialize_unit(),
+            Value::Boolean(b) => serializer.serialize_bool(*b),
+            Value::TinyInt(i) => serializer.serialize_i8(*i),
+            Value::SmallInt(i) => serializer.serialize_i16(*i),
+            Value::Int(i) => serializer.serialize_i32(*i),
+            Value::BigInt(i) => serializer.serialize_i64(*i),
+            // JSON parsers commonly read numbers as doubles, so values outside
+            // of 64-bit range and decimals are strings to keep their precision
+            Value::HugeInt(i) => match i64::try_from(*i) {
+                Ok(i) => serializer.serialize_i64(i),
+                Err(_) => serializer.collect_str(i),
+            },
+            Value::UTinyInt(i) => serializer.serialize_u8(*i),
+            Value::USmallInt(i) => serializer.serialize_u16(*i),
+            Value::UInt(i) => serializer.serialize_u32(*i),
+            Value::UBigInt(i) => serializer.serialize_u64(*i),
+            Value::Decimal(d) => serializer.collect_str(d),
+            // NaN and infinities become `null`
+            Value::Float(f) => serializer.serialize_f32(*f),
+            Value::Double(f) => serializer.serialize_f64(*f),
+            Value::Timestamp(unit, value) => {
+                let utc = matches!(self.data_type, Some(DataType::Timestamp(_, Some(_))));
+
+                match timestamp(*unit, *value, utc) {
+                    Some(timestamp) => serializer.serialize_str(&timestamp),
+                    None => serializer.serialize_i64(*value),
+                }
+            }
+            Value::Date32(days) => match DateTime::from_timestamp(*days as i64 * 86_400, 0) {
+                Some(date) => serializer.collect_str(&date.date_naive()),
+                None => serializer.serialize_i32(*days),
+            },
+            Value::Time64(unit, value) => {
+                let micros = unit.to_micros(*value);
+                let time = NaiveTime::from_num_seconds_from_midnight_opt(
+                    (micros / 1_000_000) as u32,
+                    (micros % 1_000_000) as u32 * 1000,
+                );
+
+                match time {
+                    Some(time) => serializer.collect_str(&time),
+                    None => serializer.serialize_i64(micros),
+                }
+            }
+            Value::Interval {
+                months,
+                days,
+                nanos,
+            } => {
+                let mut map = serializer.serialize_map(Some(3))?;
+                map.serialize_entry("months", months)?;
+                map.serialize_entry("days", days)?;
+                map.serialize_entry("nanos", nanos)?;
+                map.end()
+            }
+            Value::Text(s) | Value::Enum(s) => serializer.serialize_str(s),
+            Value::Blob(b) => serializer.serialize_str(&general_purpose::STANDARD.encode(b)),
+            Value::List(items) | Value::Array(items) => {
+                let mut seq = serializer.serialize_seq(Some(items.len()))?;
+
+                for item in items {
+                    seq.serialize_element(&Cell::new(item, self.child(0), self.tags))?;
+                }
+
+                seq.end()
+            }
+            Value::Struct(fields) => {
+                let mut map = serializer.serialize_map(Some(fields.iter().count()))?;
+
+                for (idx, (name, value)) in fields.iter().enumerate() {
+                    map.serialize_entry(name, &Cell::new(value, self.child(idx), self.tags))?;
+                }
+
+                map.end()
+            }
+            // JSON object keys have to be strings
+            Value::Map(entries) => {
+                let mut map = serializer.serialize_map(None)?;
+
+                for (key, value) in entries.iter() {
+                    let key = duckdb_value_to_string(key.clone());
+                    map.serialize_entry(&key, &Cell::new(value, None, self.tags))?;
+                }
+
+                map.end()
+            }
+            // Objects with the tag as the only key, when it is known
+            Value::Union(value) => {
+                let member = Cell::new(value, None, self.tags);
+
+                match self.tags.next() {
+                    Some(tag) => {
+                        let mut map = serializer.serialize_map(Some(1))?;
+                        map.serialize_entry(tag, &member)?;
+                        map.end()
+                    }
+                    None => member.serialize(serializer),
+                }
+            }
+        }
+    }
+}
+
+// ISO 8601, with `Z` suffix for `TIMESTAMPTZ` values, which are in UTC
+fn timestamp(unit: TimeUnit, value: i64, utc: bool) -> Option<String> {
+    let datetime = match unit {
+        TimeUnit::Nanosecond => DateTime::from_timestamp_nanos(value),
+        _ => DateTime::from_timestamp_micros(unit.to_micros(value))?,
+    };
+
+    let suffix = if utc { "Z" } else { "" };
+
+    Some(format!("{}{}", datetime.naive_utc().format("%Y-%m-%dT%H:%M:%S%.f"), suffix))
+}
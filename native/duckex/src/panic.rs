@@ -0,0 +1,37 @@
+// SPDX-FileCopyrightText: 2025 Stas Muzhyk <sts@abc3.dev>
+// SPDX-FileCopyrightText: 2025 Łukasz Niemier <~@hauleth.dev>
+//
+// SPDX-License-Identifier: Apache-2.0
+
+use std::any::Any;
+use std::panic::{self, AssertUnwindSafe};
+
+use crate::error::{Error, ErrorKind};
+
+/// Run body of the NIF, turning panic into internal error with the panic
+/// message, where rustler would raise bare `nif_panicked` error.
+///
+/// Jobs panicking on the connection worker thread are reported the same way,
+/// and the connection is reported as poisoned on its next use, as its state
+/// cannot be trusted.
+pub(crate) fn guard<T>(body: impl FnOnce() -> Result<T, Error>) -> Result<T, Error> {
+    panic::catch_unwind(AssertUnwindSafe(body)).unwrap_or_else(|payload| Err(error(&*payload)))
+}
+
+/// Internal error with message of the caught panic
+pub(crate) fn error(payload: &(dyn Any + Send)) -> Error {
+    Error::new(
+        ErrorKind::Internal,
+        format!("NIF panicked: {}", message(payload)),
+    )
+}
+
+fn message(payload: &(dyn Any + Send)) -> &str {
+    if let Some(message) = payload.downcast_ref::<&str>() {
+        message
+    } else if let Some(message) = payload.downcast_ref::<String>() {
+        message
+    } else {
+        "unknown reason"
+    }
+}
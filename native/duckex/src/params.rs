@@ -0,0 +1,271 @@
+// SPDX-FileCopyrightText: 2025 Stas Muzhyk <sts@abc3.dev>
+// SPDX-FileCopyrightText: 2025 Łukasz Niemier <~@hauleth.dev>
+//
+// SPDX-License-Identifier: Apache-2.0
+
+use chrono::DateTime;
+use duckdb::types::{TimeUnit, Value};
+use duckdb::{Connection, Statement};
+use rustler::{NifMap, Term};
+
+use crate::capi;
+use crate::error::{Error, ErrorKind};
+use crate::term_to_duckdb_value;
+
+// Name of the SQL-level prepared statement used to look up parameter types
+const PREPARED_NAME: &str = "__duckex_params";
+
+/// Values bound to the statement, by position or, when passed as keyword list,
+/// by name
+pub(crate) enum Bound {
+    Positional(Vec<Value>),
+    Named(Vec<(String, Value)>),
+}
+
+impl Bound {
+    pub(crate) fn decode(terms: Vec<Term>) -> Result<Self, Error> {
+        let pairs: Option<Vec<(String, Term)>> = terms
+            .iter()
+            .map(|term| {
+                let (key, value) = term.decode::<(Term, Term)>().ok()?;
+
+                Some((key.atom_to_string().ok()?, value))
+            })
+            .collect();
+
+        match pairs {
+            Some(pairs) if !pairs.is_empty() => pairs
+                .into_iter()
+                .map(|(name, value)| Ok((name, term_to_duckdb_value(value)?)))
+                .collect::<Result<_, Error>>()
+                .map(Bound::Named),
+            _ => terms
+                .into_iter()
+                .map(term_to_duckdb_value)
+                .collect::<Result<_, _>>()
+                .map(Bound::Positional),
+        }
+    }
+
+    /// Values in the order of statement parameters. Named ones are matched to
+    /// the names DuckDB gave the parameters, as it numbers them in the order it
+    /// binds the statement, e.g. `FROM` before `SET` of `UPDATE`, not in the
+    /// order of their use in `sql`.
+    pub(crate) fn resolve(&self, stmt: &Statement, sql: &str) -> Result<Vec<Value>, Error> {
+        let named = match self {
+            Bound::Positional(values) => return Ok(values.clone()),
+            Bound::Named(named) => named,
+        };
+
+        let names: Vec<Option<String>> = (1..=stmt.parameter_count())
+            .map(|idx| capi::parameter_name(stmt, idx))
+            .collect();
+
+        let mut values = vec![None; stmt.parameter_count()];
+
+        for (name, value) in named {
+            let slot = names
+                .iter()
+                .position(|known| {
+                    known
+                        .as_deref()
+                        .is_some_and(|known| known.eq_ignore_ascii_case(name))
+                })
+                .and_then(|idx| values.get_mut(idx))
+                .ok_or_else(|| {
+                    Error::new(ErrorKind::InvalidInput, format!("Unknown parameter ${}", name))
+                })?;
+
+            *slot = Some(value.clone());
+        }
+
+        values
+            .into_iter()
+            .collect::<Option<_>>()
+            .ok_or_else(|| Error::arity(stmt.parameter_count(), named.len(), sql))
+    }
+}
+
+/// Fail on blobs, which parameters become when they are not valid UTF-8, for
+/// connections opened with `strict_text`
+pub(crate) fn ensure_text(values: &[Value]) -> Result<(), Error> {
+    match values.iter().position(|value| matches!(value, Value::Blob(_))) {
+        Some(idx) => Err(Error::new(
+            ErrorKind::InvalidInput,
+            format!("Parameter ${} is not valid UTF-8 text", idx + 1),
+        )),
+        None => Ok(()),
+    }
+}
+
+/// Fixed-size arrays are bound in their text form, which DuckDB casts to the
+/// `ARRAY` type of the parameter. Their size is checked with `validate_arrays`
+/// beforehand.
+pub(crate) fn bind_arrays(values: &[Value]) -> Result<Vec<Value>, Error> {
+    values
+        .iter()
+        .map(|value| match value {
+            Value::Array(items) => Ok(Value::Text(array_literal(items)?)),
+            value => Ok(value.clone()),
+        })
+        .collect()
+}
+
+/// Fail on arrays bound to `ARRAY` parameter of other size, before the
+/// statement runs. DuckDB reports it only as failed cast of the text form.
+pub(crate) fn validate_arrays(stmt: &Statement, values: &[Value]) -> Result<(), Error> {
+    for (idx, value) in values.iter().enumerate() {
+        let Value::Array(items) = value else {
+            continue;
+        };
+
+        let Some(ty) = capi::parameter_type(stmt, idx + 1) else {
+            continue;
+        };
+
+        if let Some((size, got)) = array_mismatch(&ty, items) {
+            return Err(Error::new(
+                ErrorKind::InvalidInput,
+                format!(
+                    "Parameter ${} is ARRAY of {} elements, got {}",
+                    idx + 1,
+                    size,
+                    got
+                ),
+            ));
+        }
+    }
+
+    Ok(())
+}
+
+// Expected and actual size of the first array, outer or nested, not matching
+// the size of its type
+fn array_mismatch(ty: &capi::LogicalType, items: &[Value]) -> Option<(usize, usize)> {
+    let (size, child) = ty.array()?;
+
+    if items.len() != size {
+        return Some((size, items.len()));
+    }
+
+    items.iter().find_map(|item| match item {
+        Value::Array(items) => array_mismatch(&child, items),
+        _ => None,
+    })
+}
+
+// Text elements are quoted, with quotes and backslashes in them escaped, so
+// dates, decimals and other values given as text are cast to the element type
+fn array_literal(items: &[Value]) -> Result<String, Error> {
+    let items = items
+        .iter()
+        .map(|item| match item {
+            Value::Null => Ok("NULL".to_string()),
+            Value::Boolean(b) => Ok(b.to_string()),
+            Value::BigInt(i) => Ok(i.to_string()),
+            Value::HugeInt(i) => Ok(i.to_string()),
+            Value::Double(f) => Ok(f.to_string()),
+            Value::Text(text) => Ok(quote_element(text)),
+            Value::Timestamp(TimeUnit::Microsecond, micros) => timestamp_element(*micros),
+            Value::Array(items) => array_literal(items),
+            _ => Err(Error::new(
+                ErrorKind::InvalidInput,
+                "ARRAY parameters cannot hold blobs",
+            )),
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+
+    Ok(format!("[{}]", items.join(", ")))
+}
+
+// UTC timestamp, which `DateTime` parameters are, in its text form
+fn timestamp_element(micros: i64) -> Result<String, Error> {
+    let at = DateTime::from_timestamp_micros(micros).ok_or_else(|| {
+        Error::new(
+            ErrorKind::InvalidInput,
+            "Timestamp in ARRAY is out of range",
+        )
+    })?;
+
+    Ok(quote_element(&at.format("%Y-%m-%d %H:%M:%S%.6f+00").to_string()))
+}
+
+fn quote_element(text: &str) -> String {
+    format!("'{}'", text.replace('\\', "\\\\").replace('\'', "\\'"))
+}
+
+/// Fail on text bound to `ENUM` parameter which is not one of its members,
+/// before the statement runs. DuckDB reports it only as failed conversion to
+/// the enum index type, which does not tell what went wrong.
+pub(crate) fn validate_enums(stmt: &Statement, values: &[Value]) -> Result<(), Error> {
+    for (idx, value) in values.iter().enumerate() {
+        let Value::Text(text) = value else {
+            continue;
+        };
+
+        let Some(allowed) = capi::parameter_type(stmt, idx + 1).and_then(|ty| ty.enum_members())
+        else {
+            continue;
+        };
+
+        if !allowed.contains(text) {
+            return Err(Error::invalid_enum(idx + 1, text.clone(), allowed));
+        }
+    }
+
+    Ok(())
+}
+
+#[derive(NifMap)]
+pub(crate) struct StatementParams {
+    count: usize,
+    types: Vec<Option<String>>,
+}
+
+pub(crate) fn describe(conn: &Connection, query: &str) -> Result<StatementParams, Error> {
+    let stmt = conn
+        .prepare(query)
+        .map_err(|e| format!("SQL preparation error: {}", e))?;
+
+    let count = stmt.parameter_count();
+
+    // Types inferred by the binder are only exposed by
+    // `duckdb_prepared_statements()`, which lists statements prepared with SQL
+    // `PREPARE`. Not every statement can be prepared this way, so types are
+    // simply unknown then.
+    let types = inferred_types(conn, query).unwrap_or_default();
+
+    let types = (0..count)
+        .map(|idx| types.get(idx).cloned().flatten())
+        .collect();
+
+    Ok(StatementParams { count, types })
+}
+
+fn inferred_types(conn: &Connection, query: &str) -> Option<Vec<Option<String>>> {
+    let query = query.trim_end().trim_end_matches(';');
+
+    conn.execute_batch(&format!("PREPARE {} AS {}", PREPARED_NAME, query))
+        .ok()?;
+
+    let types = conn.query_row(
+        "SELECT parameter_types FROM duckdb_prepared_statements() WHERE name = ?",
+        [PREPARED_NAME],
+        |row| row.get::<_, Value>(0),
+    );
+
+    let _ = conn.execute_batch(&format!("DEALLOCATE {}", PREPARED_NAME));
+
+    match types.ok()? {
+        Value::List(types) => Some(
+            types
+                .into_iter()
+                .map(|ty| match ty {
+                    Value::Text(ty) if ty != "UNKNOWN" => Some(ty),
+                    _ => None,
+                })
+                .collect(),
+        ),
+        _ => None,
+    }
+}
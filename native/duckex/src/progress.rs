@@ -0,0 +1,129 @@
+// SPDX-FileCopyrightText: 2025 Stas Muzhyk <sts@abc3.dev>
+// SPDX-FileCopyrightText: 2025 Łukasz Niemier <~@hauleth.dev>
+//
+// SPDX-License-Identifier: Apache-2.0
+
+use std::marker::PhantomData;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Mutex;
+use std::time::Instant;
+
+use duckdb::{ffi, Connection};
+use rustler::NifMap;
+
+use crate::capi;
+
+/// Progress of the query currently running on the connection. It is updated
+/// from the worker thread running the query, so it can be polled while the
+/// query runs. Percentage and rows processed come from DuckDB progress bar,
+/// which has to be enabled on the connection.
+#[derive(Default)]
+pub(crate) struct Progress {
+    rows: AtomicU64,
+    current: Mutex<Option<Running>>,
+}
+
+struct Running {
+    query: String,
+    started: Instant,
+    conn: Handle,
+}
+
+// Raw connection handle, only used while the connection runs the query
+struct Handle(ffi::duckdb_connection);
+
+// Safety: DuckDB allows polling query progress from other threads, the handle
+// is removed before the query finishes
+unsafe impl Send for Handle {}
+
+#[derive(NifMap)]
+pub(crate) struct ProgressInfo {
+    running: bool,
+    query: Option<String>,
+    percentage: Option<f64>,
+    rows_processed: u64,
+    total_rows: u64,
+    rows_fetched: u64,
+    elapsed_us: u64,
+}
+
+impl Progress {
+    /// Mark start of the query running on `conn`, progress is reset when
+    /// returned guard is dropped
+    pub(crate) fn start<'a>(&'a self, conn: &'a Connection, query: &str) -> ProgressGuard<'a> {
+        self.rows.store(0, Ordering::Relaxed);
+
+        // Safety: the guard borrows the connection, so the handle is removed
+        // before the connection can be closed
+        let conn = Handle(unsafe { conn.raw_connection() });
+
+        if let Ok(mut current) = self.current.lock() {
+            *current = Some(Running {
+                query: query.to_string(),
+                started: Instant::now(),
+                conn,
+            });
+        }
+
+        ProgressGuard {
+            progress: self,
+            _conn: PhantomData,
+        }
+    }
+
+    pub(crate) fn row(&self) {
+        self.rows.fetch_add(1, Ordering::Relaxed);
+    }
+
+    pub(crate) fn info(&self) -> ProgressInfo {
+        // Lock is held while polling, so the query cannot finish meanwhile
+        let Ok(current) = self.current.lock() else {
+            return ProgressInfo::idle();
+        };
+
+        match current.as_ref() {
+            Some(running) => {
+                // Safety: the handle is valid while it is stored
+                let progress = unsafe { capi::query_progress(running.conn.0) };
+
+                ProgressInfo {
+                    running: true,
+                    query: Some(running.query.clone()),
+                    percentage: progress.percentage,
+                    rows_processed: progress.rows_processed,
+                    total_rows: progress.total_rows,
+                    rows_fetched: self.rows.load(Ordering::Relaxed),
+                    elapsed_us: running.started.elapsed().as_micros() as u64,
+                }
+            }
+            None => ProgressInfo::idle(),
+        }
+    }
+}
+
+impl ProgressInfo {
+    fn idle() -> Self {
+        ProgressInfo {
+            running: false,
+            query: None,
+            percentage: None,
+            rows_processed: 0,
+            total_rows: 0,
+            rows_fetched: 0,
+            elapsed_us: 0,
+        }
+    }
+}
+
+pub(crate) struct ProgressGuard<'a> {
+    progress: &'a Progress,
+    _conn: PhantomData<&'a Connection>,
+}
+
+impl Drop for ProgressGuard<'_> {
+    fn drop(&mut self) {
+        if let Ok(mut current) = self.progress.current.lock() {
+            *current = None;
+        }
+    }
+}
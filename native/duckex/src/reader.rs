@@ -0,0 +1,109 @@
+// SPDX-FileCopyrightText: 2025 Stas Muzhyk <sts@abc3.dev>
+// SPDX-FileCopyrightText: 2025 Łukasz Niemier <~@hauleth.dev>
+//
+// SPDX-License-Identifier: Apache-2.0
+
+use std::ops::Deref;
+use std::sync::Mutex;
+
+use duckdb::Connection;
+
+use crate::error::Error;
+use crate::lock;
+
+/// Pool of connection clones running read queries concurrently with each
+/// other and with the connection itself. Clones share the database with the
+/// connection, but not its transaction, so they see only committed data.
+pub(crate) struct Readers {
+    state: Mutex<State>,
+    // Maximum number of idle clones kept around
+    size: usize,
+}
+
+struct State {
+    idle: Vec<Connection>,
+    // Set once the connection is closed, so clones do not keep the database
+    // open
+    closed: bool,
+}
+
+impl Readers {
+    pub(crate) fn new(size: usize) -> Self {
+        Readers {
+            state: Mutex::new(State {
+                idle: Vec::new(),
+                closed: false,
+            }),
+            size,
+        }
+    }
+
+    /// Take idle clone, or create new one with `clone` when there is none
+    pub(crate) fn checkout(
+        &self,
+        clone: impl FnOnce() -> Result<Connection, Error>,
+    ) -> Result<Reader<'_>, Error> {
+        let idle = {
+            let mut state = lock::acquire(&self.state, None)?;
+
+            if state.closed {
+                return Err(Error::closed());
+            }
+
+            state.idle.pop()
+        };
+
+        let conn = match idle {
+            Some(conn) => conn,
+            None => clone()?,
+        };
+
+        Ok(Reader {
+            readers: self,
+            conn: Some(conn),
+        })
+    }
+
+    /// Drop idle clones and stop handing out new ones
+    pub(crate) fn close(&self) {
+        let mut state = self.state.lock().unwrap_or_else(|e| e.into_inner());
+
+        state.closed = true;
+        state.idle.clear();
+    }
+
+    /// Hand out clones again, after closing the connection failed
+    pub(crate) fn reopen(&self) {
+        let mut state = self.state.lock().unwrap_or_else(|e| e.into_inner());
+
+        state.closed = false;
+    }
+}
+
+/// Checked out clone, returned to the pool when dropped
+pub(crate) struct Reader<'a> {
+    readers: &'a Readers,
+    conn: Option<Connection>,
+}
+
+impl Deref for Reader<'_> {
+    type Target = Connection;
+
+    fn deref(&self) -> &Connection {
+        self.conn.as_ref().expect("reader is checked out")
+    }
+}
+
+impl Drop for Reader<'_> {
+    fn drop(&mut self) {
+        let Some(conn) = self.conn.take() else {
+            return;
+        };
+
+        if let Ok(mut state) = self.readers.state.lock() {
+            if !state.closed && state.idle.len() < self.readers.size {
+                state.idle.push(conn);
+            }
+        }
+    }
+}
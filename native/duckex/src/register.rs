@@ -0,0 +1,130 @@
+// SPDX-FileCopyrightText: 2025 Stas Muzhyk <sts@abc3.dev>
+// SPDX-FileCopyrightText: 2025 Łukasz Niemier <~@hauleth.dev>
+//
+// SPDX-License-Identifier: Apache-2.0
+
+use std::collections::HashMap;
+
+use duckdb::types::Value;
+use duckdb::{appender_params_from_iter, Connection};
+use rustler::types::map::MapIterator;
+use rustler::types::tuple::get_tuple;
+use rustler::Term;
+
+use crate::error::{Error, ErrorKind};
+use crate::sql::quote_identifier;
+use crate::term_to_duckdb_value;
+
+/// Values of the rows in column order. Rows are maps keyed by column names, or
+/// tuples and lists with values in column order.
+pub(crate) fn rows(
+    rows: Vec<Term>,
+    columns: &[(String, String)],
+) -> Result<Vec<Vec<Value>>, Error> {
+    rows.into_iter()
+        .map(|row| row_values(row, columns))
+        .collect()
+}
+
+/// Create table `name` with given `(column, type)` columns and load rows into
+/// it. Returns number of loaded rows.
+///
+/// The table is not temporary, as those are visible only to the connection
+/// which created them, not to the other connections of the pool.
+pub(crate) fn register(
+    conn: &Connection,
+    name: &str,
+    columns: &[(String, String)],
+    rows: &[Vec<Value>],
+) -> Result<usize, Error> {
+    conn.execute_batch(&create_sql(name, columns)?)
+        .map_err(|e| format!("Failed to create table '{}': {}", name, e))?;
+
+    let mut appender = conn
+        .appender(name)
+        .map_err(|e| format!("Failed to create appender for '{}': {}", name, e))?;
+
+    for row in rows {
+        appender
+            .append_row(appender_params_from_iter(row))
+            .map_err(|e| format!("Failed to append row: {}", e))?;
+    }
+
+    appender
+        .flush()
+        .map_err(|e| format!("Failed to flush appender: {}", e))?;
+
+    Ok(rows.len())
+}
+
+fn create_sql(name: &str, columns: &[(String, String)]) -> Result<String, Error> {
+    if columns.is_empty() {
+        return Err(invalid("At least one column is required".to_string()));
+    }
+
+    let columns = columns
+        .iter()
+        .map(|(column, ty)| {
+            // Types are interpolated into SQL, so only allow what type names
+            // can consist of, e.g. `DECIMAL(10, 2)` or `INTEGER[]`
+            let valid = ty
+                .chars()
+                .all(|c| c.is_ascii_alphanumeric() || " _(),[]".contains(c));
+
+            if valid && !ty.trim().is_empty() {
+                Ok(format!("{} {}", quote_identifier(column), ty))
+            } else {
+                Err(invalid(format!("Invalid type '{}' of column '{}'", ty, column)))
+            }
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+
+    Ok(format!(
+        "CREATE OR REPLACE TABLE {} ({})",
+        quote_identifier(name),
+        columns.join(", ")
+    ))
+}
+
+fn row_values(row: Term, columns: &[(String, String)]) -> Result<Vec<Value>, Error> {
+    let values: Vec<Term> = if row.is_map() {
+        let map: HashMap<String, Term> = MapIterator::new(row)
+            .ok_or_else(|| invalid("Invalid row".to_string()))?
+            .filter_map(|(key, value)| {
+                key.atom_to_string()
+                    .ok()
+                    .or_else(|| key.decode::<String>().ok())
+                    .map(|key| (key, value))
+            })
+            .collect();
+
+        // Missing keys are NULLs
+        columns
+            .iter()
+            .map(|(column, _)| map.get(column).copied().unwrap_or_else(|| nil(row)))
+            .collect()
+    } else if row.is_tuple() {
+        get_tuple(row).map_err(|_| invalid("Invalid row".to_string()))?
+    } else {
+        row.decode()
+            .map_err(|_| invalid("Row has to be a map, tuple or list".to_string()))?
+    };
+
+    if values.len() != columns.len() {
+        return Err(invalid(format!(
+            "Row has {} values, but there are {} columns",
+            values.len(),
+            columns.len()
+        )));
+    }
+
+    values.into_iter().map(term_to_duckdb_value).collect()
+}
+
+fn nil(term: Term) -> Term {
+    rustler::types::atom::nil().to_term(term.get_env())
+}
+
+fn invalid(message: String) -> Error {
+    Error::new(ErrorKind::InvalidInput, message)
+}
@@ -0,0 +1,120 @@
+// SPDX-FileCopyrightText: 2025 Stas Muzhyk <sts@abc3.dev>
+// SPDX-FileCopyrightText: 2025 Łukasz Niemier <~@hauleth.dev>
+//
+// SPDX-License-Identifier: Apache-2.0
+
+use std::sync::Mutex;
+use std::vec::IntoIter;
+
+use duckdb::types::Value;
+use rustler::{Encoder, Env, Term};
+
+use crate::blob;
+use crate::error::Error;
+use crate::geometry;
+use crate::timeslice::Timeslice;
+use crate::union;
+use crate::varint;
+use crate::{duckdb_value_to_term, lock, push_row, tagged_value_to_term, Layout};
+
+// Values of the row with tags of their UNION values, see `union::tags`
+type Row = (Vec<Value>, Vec<Vec<String>>);
+
+/// Rows of the result that were not encoded into terms yet. They are kept as
+/// DuckDB values, so they can be encoded in chunks by separate NIF calls, each
+/// of bounded duration.
+pub struct ResultResource {
+    layout: Layout,
+    rows: Mutex<IntoIter<Row>>,
+    // Columns holding GEOMETRY values and the format they are returned in
+    geometry: Vec<bool>,
+    geometry_format: geometry::Format,
+    // Columns holding VARINT values, encoded as blobs
+    varint: Vec<bool>,
+    blob_format: blob::Format,
+}
+
+impl ResultResource {
+    pub(crate) fn new(
+        rows: Vec<Vec<Value>>,
+        tags: Vec<Vec<Vec<String>>>,
+        layout: Layout,
+        geometry: Vec<bool>,
+        geometry_format: geometry::Format,
+        varint: Vec<bool>,
+        blob_format: blob::Format,
+    ) -> Self {
+        ResultResource {
+            layout,
+            rows: Mutex::new(rows.into_iter().zip(tags).collect::<Vec<_>>().into_iter()),
+            geometry,
+            geometry_format,
+            varint,
+            blob_format,
+        }
+    }
+
+    /// Encode up to `count` rows, fewer when the timeslice of the NIF runs out.
+    /// Returns them with flag telling whether all rows were fetched
+    pub(crate) fn fetch<'a>(
+        &self,
+        env: Env<'a>,
+        count: usize,
+    ) -> Result<(Vec<Vec<Term<'a>>>, bool), Error> {
+        self.encode(env, count, Some(Timeslice::new()))
+    }
+
+    /// Encode all rows at once, for results encoded off the schedulers
+    pub(crate) fn fetch_all<'a>(&self, env: Env<'a>) -> Result<(Vec<Vec<Term<'a>>>, bool), Error> {
+        self.encode(env, usize::MAX, None)
+    }
+
+    fn encode<'a>(
+        &self,
+        env: Env<'a>,
+        count: usize,
+        mut timeslice: Option<Timeslice>,
+    ) -> Result<(Vec<Vec<Term<'a>>>, bool), Error> {
+        let mut rows = lock::acquire(&self.rows, None)?;
+        let mut data = vec![];
+
+        for (row, tags) in rows.by_ref().take(count) {
+            let values = row.into_iter().enumerate().map(|(idx, value)| {
+                if self.geometry.get(idx).copied().unwrap_or(false) {
+                    geometry::encode(env, value, self.geometry_format, self.blob_format)
+                } else if self.varint.get(idx).copied().unwrap_or(false) {
+                    self.encode_varint(env, value)
+                } else {
+                    tagged_value_to_term(env, value, self.blob_format, &union::column(&tags, idx))
+                }
+            });
+
+            push_row(&mut data, values, self.layout);
+
+            // Yield to the scheduler, rest is fetched by the next call
+            if timeslice.as_mut().is_some_and(|slice| slice.row(env)) {
+                break;
+            }
+        }
+
+        Ok((data, rows.len() == 0))
+    }
+
+    fn encode_varint<'a>(&self, env: Env<'a>, value: Value) -> Term<'a> {
+        if let Value::Blob(bytes) = &value {
+            if let Some(integer) = varint::decode(bytes) {
+                return integer.encode(env);
+            }
+        }
+
+        duckdb_value_to_term(env, value, self.blob_format)
+    }
+
+    /// Drop rows that were not fetched yet
+    pub(crate) fn close(&self) -> Result<(), Error> {
+        let mut rows = lock::acquire(&self.rows, None)?;
+        *rows = Vec::new().into_iter();
+
+        Ok(())
+    }
+}
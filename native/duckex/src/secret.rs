@@ -0,0 +1,181 @@
+// SPDX-FileCopyrightText: 2025 Stas Muzhyk <sts@abc3.dev>
+// SPDX-FileCopyrightText: 2025 Łukasz Niemier <~@hauleth.dev>
+//
+// SPDX-License-Identifier: Apache-2.0
+
+use duckdb::types::Value;
+
+use crate::options::Options;
+use crate::sql::{quote_identifier, quote_literal};
+
+/// `CREATE SECRET` statement with credentials passed as bind parameters, so
+/// they never end up in the SQL text itself
+pub(crate) struct SecretStatement {
+    pub(crate) sql: String,
+    pub(crate) params: Vec<Value>,
+}
+
+/// Option name and the corresponding secret parameter
+const S3_STRING_OPTIONS: &[(&str, &str)] = &[
+    ("key_id", "KEY_ID"),
+    ("secret", "SECRET"),
+    ("session_token", "SESSION_TOKEN"),
+    ("region", "REGION"),
+    ("endpoint", "ENDPOINT"),
+    ("url_style", "URL_STYLE"),
+];
+
+pub(crate) fn s3(name: &str, opts: &Options) -> Result<SecretStatement, String> {
+    let mut fields = vec!["TYPE s3".to_string()];
+    let mut params = vec![];
+
+    for key in opts.keys() {
+        if let Some((_, field)) = S3_STRING_OPTIONS.iter().find(|(k, _)| *k == key) {
+            if let Some(value) = opts.get_string(key)? {
+                fields.push(format!("{} ?", field));
+                params.push(Value::Text(value));
+            }
+        } else if key == "use_ssl" {
+            if let Some(value) = opts.get::<bool>(key)? {
+                fields.push("USE_SSL ?".to_string());
+                params.push(Value::Boolean(value));
+            }
+        } else if !matches!(key, "scope" | "persistent") {
+            return Err(format!("Unsupported S3 secret option :{}", key));
+        }
+    }
+
+    build(name, fields, params, opts)
+}
+
+/// Option name and the corresponding secret parameter of Iceberg REST catalog
+const ICEBERG_STRING_OPTIONS: &[(&str, &str)] = &[
+    ("client_id", "CLIENT_ID"),
+    ("client_secret", "CLIENT_SECRET"),
+    ("oauth2_server_uri", "OAUTH2_SERVER_URI"),
+    ("oauth2_scope", "OAUTH2_SCOPE"),
+    ("token", "TOKEN"),
+];
+
+pub(crate) fn iceberg(name: &str, opts: &Options) -> Result<SecretStatement, String> {
+    let mut fields = vec!["TYPE iceberg".to_string()];
+    let mut params = vec![];
+
+    for key in opts.keys() {
+        if let Some((_, field)) = ICEBERG_STRING_OPTIONS.iter().find(|(k, _)| *k == key) {
+            if let Some(value) = opts.get_string(key)? {
+                fields.push(format!("{} ?", field));
+                params.push(Value::Text(value));
+            }
+        } else {
+            return Err(format!("Unsupported Iceberg secret option :{}", key));
+        }
+    }
+
+    build(name, fields, params, opts)
+}
+
+/// Option name and the corresponding secret parameter of Azure Blob Storage
+const AZURE_STRING_OPTIONS: &[(&str, &str)] = &[
+    ("connection_string", "CONNECTION_STRING"),
+    ("account_name", "ACCOUNT_NAME"),
+    ("tenant_id", "TENANT_ID"),
+    ("client_id", "CLIENT_ID"),
+    ("client_secret", "CLIENT_SECRET"),
+    ("client_certificate_path", "CLIENT_CERTIFICATE_PATH"),
+    ("chain", "CHAIN"),
+];
+
+pub(crate) fn azure(name: &str, opts: &Options) -> Result<SecretStatement, String> {
+    let mut fields = vec!["TYPE azure".to_string()];
+    let mut params = vec![];
+
+    // Service principal credentials are ignored unless the provider is set
+    // explicitly, so pick it when they are given
+    let provider = match provider(opts)? {
+        None if opts.get_term("tenant_id").is_some() => Some("service_principal".to_string()),
+        provider => provider,
+    };
+
+    if let Some(provider) = provider {
+        fields.push(format!("PROVIDER {}", provider));
+    }
+
+    for key in opts.keys() {
+        if let Some((_, field)) = AZURE_STRING_OPTIONS.iter().find(|(k, _)| *k == key) {
+            if let Some(value) = opts.get_string(key)? {
+                fields.push(format!("{} ?", field));
+                params.push(Value::Text(value));
+            }
+        } else if !matches!(key, "provider" | "scope" | "persistent") {
+            return Err(format!("Unsupported Azure secret option :{}", key));
+        }
+    }
+
+    build(name, fields, params, opts)
+}
+
+/// Option name and the corresponding secret parameter of Google Cloud Storage
+const GCS_STRING_OPTIONS: &[(&str, &str)] = &[
+    ("key_id", "KEY_ID"),
+    ("secret", "SECRET"),
+    ("endpoint", "ENDPOINT"),
+];
+
+pub(crate) fn gcs(name: &str, opts: &Options) -> Result<SecretStatement, String> {
+    let mut fields = vec!["TYPE gcs".to_string()];
+    let mut params = vec![];
+
+    if let Some(provider) = provider(opts)? {
+        fields.push(format!("PROVIDER {}", provider));
+    }
+
+    for key in opts.keys() {
+        if let Some((_, field)) = GCS_STRING_OPTIONS.iter().find(|(k, _)| *k == key) {
+            if let Some(value) = opts.get_string(key)? {
+                fields.push(format!("{} ?", field));
+                params.push(Value::Text(value));
+            }
+        } else if !matches!(key, "provider" | "scope" | "persistent") {
+            return Err(format!("Unsupported GCS secret option :{}", key));
+        }
+    }
+
+    build(name, fields, params, opts)
+}
+
+// Provider is a keyword in `CREATE SECRET`, so it cannot be bound
+fn provider(opts: &Options) -> Result<Option<String>, String> {
+    match opts.get_string("provider")? {
+        Some(provider) if !provider.chars().all(|c| c.is_ascii_alphanumeric() || c == '_') => {
+            Err("Invalid value for option :provider".to_string())
+        }
+        provider => Ok(provider),
+    }
+}
+
+fn build(
+    name: &str,
+    mut fields: Vec<String>,
+    params: Vec<Value>,
+    opts: &Options,
+) -> Result<SecretStatement, String> {
+    let persistent = if opts.get::<bool>("persistent")?.unwrap_or(false) {
+        "PERSISTENT "
+    } else {
+        ""
+    };
+
+    if let Some(scope) = opts.get::<String>("scope")? {
+        fields.push(format!("SCOPE {}", quote_literal(&scope)));
+    }
+
+    let sql = format!(
+        "CREATE OR REPLACE {}SECRET {} ({})",
+        persistent,
+        quote_identifier(name),
+        fields.join(", ")
+    );
+
+    Ok(SecretStatement { sql, params })
+}
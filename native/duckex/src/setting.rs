@@ -0,0 +1,97 @@
+// SPDX-FileCopyrightText: 2025 Stas Muzhyk <sts@abc3.dev>
+// SPDX-FileCopyrightText: 2025 Łukasz Niemier <~@hauleth.dev>
+//
+// SPDX-License-Identifier: Apache-2.0
+
+use duckdb::{Connection, OptionalExt};
+use rustler::{Encoder, Env, Term};
+
+use crate::error::{Error, ErrorKind};
+use crate::sql::quote_literal;
+
+/// Setting value decoded according to the setting's declared input type
+pub(crate) enum SettingValue {
+    Null,
+    Boolean(bool),
+    Integer(i64),
+    Double(f64),
+    Text(String),
+}
+
+impl Encoder for SettingValue {
+    fn encode<'a>(&self, env: Env<'a>) -> Term<'a> {
+        match self {
+            SettingValue::Null => rustler::types::atom::nil().encode(env),
+            SettingValue::Boolean(b) => b.encode(env),
+            SettingValue::Integer(i) => i.encode(env),
+            SettingValue::Double(f) => f.encode(env),
+            SettingValue::Text(s) => s.encode(env),
+        }
+    }
+}
+
+// Settings names are interpolated into `SET`, which does not take parameters
+fn validate_name(name: &str) -> Result<(), Error> {
+    if !name.is_empty() && name.chars().all(|c| c.is_ascii_alphanumeric() || c == '_') {
+        Ok(())
+    } else {
+        Err(Error::new(
+            ErrorKind::InvalidInput,
+            format!("Invalid setting name '{}'", name),
+        ))
+    }
+}
+
+pub(crate) fn set_sql(name: &str, value: Term) -> Result<String, Error> {
+    validate_name(name)?;
+
+    let value = if let Ok(b) = value.decode::<bool>() {
+        b.to_string()
+    } else if let Ok(i) = value.decode::<i64>() {
+        i.to_string()
+    } else if let Ok(f) = value.decode::<f64>() {
+        f.to_string()
+    } else if let Ok(s) = value.decode::<String>() {
+        quote_literal(&s)
+    } else if value.is_atom() {
+        quote_literal(&value.atom_to_string().unwrap_or_default())
+    } else {
+        return Err(Error::new(
+            ErrorKind::InvalidInput,
+            format!("Unsupported value for setting '{}'", name),
+        ));
+    };
+
+    Ok(format!("SET {} = {}", name, value))
+}
+
+pub(crate) fn get(conn: &Connection, name: &str) -> Result<SettingValue, Error> {
+    validate_name(name)?;
+
+    let setting: Option<(Option<String>, String)> = conn
+        .query_row(
+            "SELECT value, input_type FROM duckdb_settings() WHERE name = ?",
+            [name],
+            |row| Ok((row.get(0)?, row.get(1)?)),
+        )
+        .optional()
+        .map_err(|e| format!("Failed to read setting '{}': {}", name, e))?;
+
+    let (value, input_type) = setting.ok_or_else(|| {
+        Error::new(ErrorKind::NotFound, format!("Unknown setting '{}'", name))
+    })?;
+
+    let Some(value) = value else {
+        return Ok(SettingValue::Null);
+    };
+
+    let parsed = match input_type.as_str() {
+        "BOOLEAN" => value.parse().ok().map(SettingValue::Boolean),
+        "TINYINT" | "SMALLINT" | "INTEGER" | "BIGINT" | "UTINYINT" | "USMALLINT"
+        | "UINTEGER" | "UBIGINT" => value.parse().ok().map(SettingValue::Integer),
+        "FLOAT" | "DOUBLE" => value.parse().ok().map(SettingValue::Double),
+        _ => None,
+    };
+
+    Ok(parsed.unwrap_or(SettingValue::Text(value)))
+}
@@ -0,0 +1,64 @@
+// SPDX-FileCopyrightText: 2025 Stas Muzhyk <sts@abc3.dev>
+// SPDX-FileCopyrightText: 2025 Łukasz Niemier <~@hauleth.dev>
+//
+// SPDX-License-Identifier: Apache-2.0
+
+// Every `:memory:` database opened through the C API is a separate instance,
+// so connections to the named ones are cloned from the first connection
+// instead, the way DuckDB instance cache of other clients does
+
+use std::sync::{Arc, Mutex, Weak};
+
+use duckdb::{Config, Connection};
+
+use crate::error::Error;
+use crate::lock;
+
+// Named databases in use, entries of the dropped ones are removed lazily
+static DATABASES: Mutex<Vec<(String, Weak<Database>)>> = Mutex::new(Vec::new());
+
+/// Named in-memory database, kept alive as long as any connection uses it
+pub(crate) struct Database {
+    // Connection the others are cloned from
+    origin: Mutex<Connection>,
+}
+
+/// Name of the database for `:memory:name` paths
+pub(crate) fn name(path: &str) -> Option<&str> {
+    path.strip_prefix(":memory:").filter(|name| !name.is_empty())
+}
+
+/// Connect to named in-memory database, creating it with `config` when it
+/// does not exist yet. Configuration of existing database is not changed.
+pub(crate) fn connect(name: &str, config: Config) -> Result<(Connection, Arc<Database>), Error> {
+    let mut databases = lock::acquire(&DATABASES, None)?;
+
+    databases.retain(|(_, database)| database.strong_count() > 0);
+
+    let existing = databases
+        .iter()
+        .filter(|(existing, _)| existing == name)
+        .find_map(|(_, database)| database.upgrade());
+
+    let database = match existing {
+        Some(database) => database,
+        None => {
+            let origin = Connection::open_in_memory_with_flags(config)
+                .map_err(|e| format!("Failed to create in-memory DuckDB connection: {}", e))?;
+
+            let database = Arc::new(Database {
+                origin: Mutex::new(origin),
+            });
+
+            databases.push((name.to_string(), Arc::downgrade(&database)));
+
+            database
+        }
+    };
+
+    let conn = lock::acquire(&database.origin, None)?
+        .try_clone()
+        .map_err(|e| format!("Failed to connect to in-memory database '{}': {}", name, e))?;
+
+    Ok((conn, database))
+}
@@ -0,0 +1,68 @@
+// SPDX-FileCopyrightText: 2025 Stas Muzhyk <sts@abc3.dev>
+// SPDX-FileCopyrightText: 2025 Łukasz Niemier <~@hauleth.dev>
+//
+// SPDX-License-Identifier: Apache-2.0
+
+// Resources are not garbage collected when the VM halts, so connections still
+// open by then are closed explicitly, otherwise the next open of the database
+// has to replay whole WAL
+
+use std::sync::{Arc, Mutex, Weak};
+use std::time::Duration;
+
+use crate::lock;
+use crate::worker::Worker;
+
+// How long to wait for the jobs queued on the connection to finish
+const LOCK_TIMEOUT: Duration = Duration::from_secs(5);
+
+// Workers of open connections with their database paths, entries of the
+// dropped ones are removed lazily
+static OPEN: Mutex<Vec<(String, Weak<Worker>)>> = Mutex::new(Vec::new());
+
+/// Track connection, so `close_all` can close it
+pub(crate) fn register(database: &str, worker: &Arc<Worker>) {
+    if let Ok(mut open) = lock::acquire(&OPEN, None) {
+        open.retain(|(_, worker)| worker.strong_count() > 0);
+        open.push((database.to_string(), Arc::downgrade(worker)));
+    }
+}
+
+/// Checkpoint and close all open connections, returns how many were closed.
+/// Connections busy for longer than `LOCK_TIMEOUT` are left alone.
+pub(crate) fn close_all() -> usize {
+    let open = match lock::acquire(&OPEN, None) {
+        Ok(mut open) => std::mem::take(&mut *open),
+        Err(_) => return 0,
+    };
+
+    let mut closed = 0;
+
+    for (database, worker) in open {
+        let Some(worker) = worker.upgrade() else {
+            continue;
+        };
+
+        let checkpoint = !database.starts_with(":memory:");
+
+        let result = worker.run(Some(LOCK_TIMEOUT), move |conn| {
+            let Some(open) = conn.take() else {
+                return Ok(false);
+            };
+
+            if checkpoint {
+                let _ = open.execute_batch("CHECKPOINT");
+            }
+
+            let _ = open.close();
+
+            Ok(true)
+        });
+
+        if let Ok(true) = result {
+            closed += 1;
+        }
+    }
+
+    closed
+}
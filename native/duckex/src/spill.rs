@@ -0,0 +1,73 @@
+// SPDX-FileCopyrightText: 2025 Stas Muzhyk <sts@abc3.dev>
+// SPDX-FileCopyrightText: 2025 Łukasz Niemier <~@hauleth.dev>
+//
+// SPDX-License-Identifier: Apache-2.0
+
+// Out-of-core operators, like large joins or sorts, spill to temporary files
+// once they do not fit in memory
+
+use duckdb::{Config, Connection};
+use rustler::NifMap;
+
+use crate::error::Error;
+use crate::options::Options;
+
+/// Database configuration with `:temp_directory` and
+/// `:max_temp_directory_size` options applied. These have to be set when the
+/// database is opened, before anything is spilled to the default location.
+pub(crate) fn config(opts: &Options) -> Result<Config, Error> {
+    let mut config = Config::default();
+
+    if let Some(directory) = opts.get::<String>("temp_directory")? {
+        config = config
+            .with("temp_directory", &directory)
+            .map_err(|e| format!("Invalid value for option :temp_directory: {}", e))?;
+    }
+
+    // Size is either number of bytes or DuckDB size string, like "10GB"
+    let max_size = match opts.get::<u64>("max_temp_directory_size") {
+        Ok(bytes) => bytes.map(|bytes| format!("{}B", bytes)),
+        Err(_) => opts.get::<String>("max_temp_directory_size")?,
+    };
+
+    if let Some(max_size) = max_size {
+        config = config
+            .with("max_temp_directory_size", &max_size)
+            .map_err(|e| format!("Invalid value for option :max_temp_directory_size: {}", e))?;
+    }
+
+    Ok(config)
+}
+
+/// Current spill usage
+#[derive(NifMap)]
+pub(crate) struct Usage {
+    temp_directory: String,
+    // As reported by DuckDB, e.g. "90% of available disk space"
+    max_temp_directory_size: String,
+    // Number and total size in bytes of temporary files
+    files: u64,
+    size: u64,
+}
+
+pub(crate) fn usage(conn: &Connection) -> Result<Usage, Error> {
+    let usage = conn
+        .query_row(
+            "SELECT current_setting('temp_directory'), \
+             current_setting('max_temp_directory_size'), \
+             (SELECT count(*) FROM duckdb_temporary_files()), \
+             (SELECT coalesce(sum(size), 0) FROM duckdb_temporary_files())",
+            [],
+            |row| {
+                Ok(Usage {
+                    temp_directory: row.get(0)?,
+                    max_temp_directory_size: row.get(1)?,
+                    files: row.get(2)?,
+                    size: row.get(3)?,
+                })
+            },
+        )
+        .map_err(|e| format!("Failed to read temporary files: {}", e))?;
+
+    Ok(usage)
+}
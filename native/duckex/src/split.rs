@@ -0,0 +1,312 @@
+// SPDX-FileCopyrightText: 2025 Stas Muzhyk <sts@abc3.dev>
+// SPDX-FileCopyrightText: 2025 Łukasz Niemier <~@hauleth.dev>
+//
+// SPDX-License-Identifier: Apache-2.0
+
+/// Split SQL script into separate statements on semicolons which are not
+/// inside string literals, quoted identifiers, comments nor dollar-quoted
+/// strings. Statements are trimmed, empty ones are skipped.
+pub(crate) fn statements(sql: &str) -> Vec<String> {
+    let bytes = sql.as_bytes();
+    let mut statements = vec![];
+    let mut start = 0;
+    let mut pos = 0;
+
+    while pos < bytes.len() {
+        pos = match bytes[pos] {
+            b';' => {
+                push(&mut statements, &sql[start..pos]);
+                start = pos + 1;
+                pos + 1
+            }
+            _ => skip(bytes, pos).unwrap_or(pos + 1),
+        };
+    }
+
+    push(&mut statements, &sql[start..]);
+
+    statements
+}
+
+/// Piece of SQL which matters for telling what the statement does, anything
+/// else, including literals and comments, is left out
+#[derive(Debug, PartialEq, Eq)]
+pub(crate) enum Token<'a> {
+    Word(&'a str),
+    // Quoted identifier, without the quotes
+    Quoted(&'a str),
+    // `$name` parameter, without the dollar sign
+    Parameter(&'a str),
+    Open,
+    Close,
+    Comma,
+    Dot,
+}
+
+pub(crate) fn tokens(sql: &str) -> Vec<Token<'_>> {
+    let bytes = sql.as_bytes();
+    let mut tokens = vec![];
+    let mut pos = 0;
+
+    while pos < bytes.len() {
+        let ident_end = |start: usize| {
+            start + bytes[start..].iter().take_while(|&&b| is_ident(b)).count()
+        };
+
+        pos = match bytes[pos] {
+            b'(' => {
+                tokens.push(Token::Open);
+                pos + 1
+            }
+            b')' => {
+                tokens.push(Token::Close);
+                pos + 1
+            }
+            b',' => {
+                tokens.push(Token::Comma);
+                pos + 1
+            }
+            b'.' => {
+                tokens.push(Token::Dot);
+                pos + 1
+            }
+            b'"' => {
+                let end = quoted(bytes, pos, b'"', false);
+                let inner = &sql[pos + 1..end];
+                tokens.push(Token::Quoted(inner.strip_suffix('"').unwrap_or(inner)));
+                end
+            }
+            b'$' if dollar_quoted(bytes, pos).is_none() && !ident_before(bytes, pos) => {
+                let end = ident_end(pos + 1);
+
+                // Numbered parameters like `$1` are positional
+                if end > pos + 1 && !bytes[pos + 1].is_ascii_digit() {
+                    tokens.push(Token::Parameter(&sql[pos + 1..end]));
+                }
+
+                end
+            }
+            b if is_ident(b) && !b.is_ascii_digit() && !ident_before(bytes, pos) => {
+                match skip(bytes, pos) {
+                    Some(end) => end,
+                    None => {
+                        let end = ident_end(pos);
+                        tokens.push(Token::Word(&sql[pos..end]));
+                        end
+                    }
+                }
+            }
+            _ => skip(bytes, pos).unwrap_or(pos + 1),
+        };
+    }
+
+    tokens
+}
+
+// Keywords starting the statement which follows common table expressions
+const CTE_STATEMENTS: &[&str] = &[
+    "SELECT", "INSERT", "UPDATE", "DELETE", "MERGE", "VALUES", "FROM", "TABLE", "PIVOT",
+    "UNPIVOT",
+];
+
+/// Uppercased keyword telling what the statement does. It is the first one,
+/// unless the statement starts with common table expressions, then it is the
+/// one following them, e.g. `INSERT` of `WITH t AS (...) INSERT ...`.
+pub(crate) fn statement_keyword(sql: &str) -> Option<String> {
+    let tokens = tokens(sql);
+    let mut words = tokens.iter().enumerate().filter_map(|(idx, token)| match token {
+        Token::Word(word) => Some((idx, word.to_ascii_uppercase())),
+        _ => None,
+    });
+
+    let (start, first) = words.next()?;
+
+    if first != "WITH" {
+        return Some(first);
+    }
+
+    // Bodies of the expressions are in parentheses, the statement is the
+    // first keyword on the top level right after one of them
+    let mut depth = 0;
+    let mut closed = false;
+
+    for token in &tokens[start + 1..] {
+        match token {
+            Token::Open => depth += 1,
+            Token::Close => {
+                depth -= 1;
+                closed = depth == 0;
+                continue;
+            }
+            Token::Word(word) if depth == 0 && closed => {
+                let word = word.to_ascii_uppercase();
+
+                if CTE_STATEMENTS.contains(&word.as_str()) {
+                    return Some(word);
+                }
+            }
+            _ => {}
+        }
+
+        closed = false;
+    }
+
+    Some(first)
+}
+
+/// Name of the table changed by `INSERT`, `UPDATE` or `DELETE` statement,
+/// without its schema
+pub(crate) fn target_table(sql: &str) -> Option<String> {
+    let keyword = statement_keyword(sql)?;
+    let before = match keyword.as_str() {
+        "INSERT" => "INTO",
+        "UPDATE" => "UPDATE",
+        "DELETE" => "FROM",
+        _ => return None,
+    };
+
+    // The name follows the first `before` keyword on the top level, once the
+    // statement keyword was seen, e.g. `INTO` of `INSERT OR REPLACE INTO t`
+    let tokens = tokens(sql);
+    let mut depth = 0;
+    let mut started = false;
+
+    let start = tokens.iter().position(|token| {
+        match token {
+            Token::Open => depth += 1,
+            Token::Close => depth -= 1,
+            Token::Word(word) if depth == 0 => {
+                started |= word.eq_ignore_ascii_case(&keyword);
+
+                return started && word.eq_ignore_ascii_case(before);
+            }
+            _ => {}
+        }
+
+        false
+    })?;
+
+    let mut name = None;
+    let mut parts = tokens[start + 1..].iter();
+
+    loop {
+        name = match parts.next() {
+            Some(Token::Word(word)) => Some(word.to_string()),
+            Some(Token::Quoted(word)) => Some(word.replace("\"\"", "\"")),
+            _ => return name,
+        };
+
+        if parts.next() != Some(&Token::Dot) {
+            return name;
+        }
+    }
+}
+
+// Position after string literal, quoted identifier, comment or dollar-quoted
+// string starting at `pos`, `None` when there is none
+fn skip(bytes: &[u8], pos: usize) -> Option<usize> {
+    match bytes[pos] {
+        // `E'...'` strings allow backslash escapes
+        b'e' | b'E' if bytes.get(pos + 1) == Some(&b'\'') && !ident_before(bytes, pos) => {
+            Some(quoted(bytes, pos + 1, b'\'', true))
+        }
+        b'\'' => Some(quoted(bytes, pos, b'\'', false)),
+        b'"' => Some(quoted(bytes, pos, b'"', false)),
+        b'-' if bytes.get(pos + 1) == Some(&b'-') => Some(line_comment(bytes, pos)),
+        b'/' if bytes.get(pos + 1) == Some(&b'*') => Some(block_comment(bytes, pos)),
+        b'$' => dollar_quoted(bytes, pos),
+        _ => None,
+    }
+}
+
+fn push(statements: &mut Vec<String>, statement: &str) {
+    let statement = statement.trim();
+
+    if !statement.is_empty() {
+        statements.push(statement.to_string());
+    }
+}
+
+fn is_ident(byte: u8) -> bool {
+    byte.is_ascii_alphanumeric() || byte == b'_' || byte >= 0x80
+}
+
+fn ident_before(bytes: &[u8], pos: usize) -> bool {
+    pos > 0 && is_ident(bytes[pos - 1])
+}
+
+// Position after the closing quote, quote is escaped by doubling it
+fn quoted(bytes: &[u8], pos: usize, quote: u8, backslash: bool) -> usize {
+    let mut pos = pos + 1;
+
+    while pos < bytes.len() {
+        match bytes[pos] {
+            b'\\' if backslash => pos += 2,
+            b if b == quote && bytes.get(pos + 1) == Some(&quote) => pos += 2,
+            b if b == quote => return pos + 1,
+            _ => pos += 1,
+        }
+    }
+
+    bytes.len()
+}
+
+fn line_comment(bytes: &[u8], pos: usize) -> usize {
+    bytes[pos..]
+        .iter()
+        .position(|&b| b == b'\n')
+        .map_or(bytes.len(), |end| pos + end + 1)
+}
+
+// Block comments can be nested
+fn block_comment(bytes: &[u8], pos: usize) -> usize {
+    let mut depth = 0;
+    let mut pos = pos;
+
+    while pos < bytes.len() {
+        if bytes[pos..].starts_with(b"/*") {
+            depth += 1;
+            pos += 2;
+        } else if bytes[pos..].starts_with(b"*/") {
+            depth -= 1;
+            pos += 2;
+
+            if depth == 0 {
+                return pos;
+            }
+        } else {
+            pos += 1;
+        }
+    }
+
+    bytes.len()
+}
+
+// `$tag$ ... $tag$` string, tag can be empty. `None` for parameters like `$1`
+// or `$name`, which are regular text.
+fn dollar_quoted(bytes: &[u8], pos: usize) -> Option<usize> {
+    if ident_before(bytes, pos) {
+        return None;
+    }
+
+    let tag_len = bytes[pos + 1..]
+        .iter()
+        .take_while(|&&b| is_ident(b))
+        .count();
+
+    let starts_with_digit = bytes.get(pos + 1).is_some_and(u8::is_ascii_digit);
+
+    if starts_with_digit || bytes.get(pos + 1 + tag_len) != Some(&b'$') {
+        return None;
+    }
+
+    let tag = &bytes[pos..pos + tag_len + 2];
+    let body = pos + tag.len();
+
+    Some(
+        bytes[body..]
+            .windows(tag.len())
+            .position(|window| window == tag)
+            .map_or(bytes.len(), |end| body + end + tag.len()),
+    )
+}
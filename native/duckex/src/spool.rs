@@ -0,0 +1,165 @@
+// SPDX-FileCopyrightText: 2025 Stas Muzhyk <sts@abc3.dev>
+// SPDX-FileCopyrightText: 2025 Łukasz Niemier <~@hauleth.dev>
+//
+// SPDX-License-Identifier: Apache-2.0
+
+// Query results written straight to a file, without ever becoming terms
+
+use std::fs::File;
+use std::io::BufWriter;
+use std::path::Path;
+
+use arrow::ipc::writer::{FileWriter, IpcWriteOptions};
+use arrow::ipc::CompressionType;
+use duckdb::{params_from_iter, Connection};
+use rustler::{NifMap, NifUnitEnum};
+
+use crate::copy::{self, CopyFormat};
+use crate::error::{Error, ErrorKind};
+use crate::options::Options;
+use crate::params::Bound;
+
+#[derive(NifUnitEnum, Clone, Copy)]
+pub(crate) enum Format {
+    Csv,
+    Parquet,
+    Json,
+    // Arrow IPC file, also known as Feather v2
+    Arrow,
+}
+
+#[derive(NifMap)]
+pub(crate) struct Spooled {
+    path: String,
+    // Total size of written files, when `path` is a directory of partitions
+    size: u64,
+    rows: u64,
+}
+
+/// How the result is written, decided from the options before the query runs.
+/// DuckDB writes CSV, Parquet and JSON itself with `COPY`, Arrow is written
+/// from its record batches.
+pub(crate) enum Writer {
+    Copy(String),
+    Arrow(Option<CompressionType>),
+}
+
+impl Writer {
+    pub(crate) fn new(
+        query: &str,
+        path: &str,
+        format: Format,
+        opts: &Options,
+    ) -> Result<Self, Error> {
+        let copy_format = match format {
+            Format::Csv => CopyFormat::Csv,
+            Format::Parquet => CopyFormat::Parquet,
+            Format::Json => CopyFormat::Json,
+            Format::Arrow => return Ok(Writer::Arrow(arrow_compression(opts)?)),
+        };
+
+        Ok(Writer::Copy(copy::copy_to_sql(query, path, copy_format, opts)?))
+    }
+}
+
+/// Write result of `query` to file at `path`
+pub(crate) fn write(
+    conn: &Connection,
+    query: &str,
+    params: &Bound,
+    path: &str,
+    writer: &Writer,
+) -> Result<Spooled, Error> {
+    let rows = match writer {
+        Writer::Copy(sql) => {
+            let mut stmt = conn
+                .prepare(sql)
+                .map_err(|e| format!("SQL preparation error: {}", e))?;
+            let params = params.resolve(&stmt, query)?;
+
+            stmt.execute(params_from_iter(params.iter()))
+                .map_err(|e| format!("SQL execution error: {}", e))? as u64
+        }
+        Writer::Arrow(compression) => {
+            // Do not leave truncated file behind
+            write_arrow(conn, query, params, path, *compression).inspect_err(|_| {
+                let _ = std::fs::remove_file(path);
+            })?
+        }
+    };
+
+    Ok(Spooled {
+        path: path.to_string(),
+        size: size(Path::new(path)),
+        rows,
+    })
+}
+
+// Arrow IPC compresses each buffer of the batches separately
+fn arrow_compression(opts: &Options) -> Result<Option<CompressionType>, Error> {
+    if let Some(key) = opts.keys().find(|key| *key != "compression") {
+        return Err(format!("Option :{} is not supported for Arrow", key).into());
+    }
+
+    match opts.get_string("compression")?.as_deref() {
+        None | Some("uncompressed") => Ok(None),
+        Some("zstd") => Ok(Some(CompressionType::ZSTD)),
+        Some("lz4") => Ok(Some(CompressionType::LZ4_FRAME)),
+        Some(other) => Err(format!("Unsupported Arrow compression '{}'", other).into()),
+    }
+}
+
+fn write_arrow(
+    conn: &Connection,
+    query: &str,
+    params: &Bound,
+    path: &str,
+    compression: Option<CompressionType>,
+) -> Result<u64, Error> {
+    let mut stmt = conn
+        .prepare(query)
+        .map_err(|e| format!("SQL preparation error: {}", e))?;
+    let params = params.resolve(&stmt, query)?;
+
+    let batches = stmt
+        .query_arrow(params_from_iter(params.iter()))
+        .map_err(|e| format!("SQL execution error: {}", e))?;
+
+    let file = File::create(path).map_err(|e| {
+        Error::new(ErrorKind::Io, format!("Failed to create file '{}': {}", path, e))
+    })?;
+
+    let options = IpcWriteOptions::default()
+        .try_with_compression(compression)
+        .map_err(|e| format!("Arrow serialization error: {}", e))?;
+
+    let mut writer =
+        FileWriter::try_new_with_options(BufWriter::new(file), &batches.get_schema(), options)
+            .map_err(|e| format!("Arrow serialization error: {}", e))?;
+    let mut rows = 0;
+
+    for batch in batches {
+        rows += batch.num_rows() as u64;
+
+        writer
+            .write(&batch)
+            .map_err(|e| format!("Arrow serialization error: {}", e))?;
+    }
+
+    writer
+        .finish()
+        .map_err(|e| format!("Arrow serialization error: {}", e))?;
+
+    Ok(rows)
+}
+
+// Partitioned output is a directory tree of files
+fn size(path: &Path) -> u64 {
+    match std::fs::metadata(path) {
+        Ok(metadata) if metadata.is_dir() => std::fs::read_dir(path)
+            .map(|entries| entries.flatten().map(|entry| size(&entry.path())).sum())
+            .unwrap_or(0),
+        Ok(metadata) => metadata.len(),
+        Err(_) => 0,
+    }
+}
@@ -0,0 +1,33 @@
+// SPDX-FileCopyrightText: 2025 Stas Muzhyk <sts@abc3.dev>
+// SPDX-FileCopyrightText: 2025 Łukasz Niemier <~@hauleth.dev>
+//
+// SPDX-License-Identifier: Apache-2.0
+
+// Helpers for safely building SQL statements out of user supplied values
+
+use crate::options::Options;
+
+/// Quote string as SQL literal, doubling any single quotes inside
+pub(crate) fn quote_literal(value: &str) -> String {
+    format!("'{}'", value.replace('\'', "''"))
+}
+
+/// Quote identifier (table, column, etc.), doubling any double quotes inside
+pub(crate) fn quote_identifier(value: &str) -> String {
+    format!("\"{}\"", value.replace('"', "\"\""))
+}
+
+/// `CREATE TABLE ... AS SELECT * FROM source`, temporary unless `:temporary`
+/// option is `false`, replacing existing one with `:replace` option
+pub(crate) fn create_table_as(table: &str, source: &str, opts: &Options) -> Result<String, String> {
+    let temporary = opts.get::<bool>("temporary")?.unwrap_or(true);
+    let replace = opts.get::<bool>("replace")?.unwrap_or(false);
+
+    Ok(format!(
+        "CREATE {}{}TABLE {} AS SELECT * FROM {}",
+        if replace { "OR REPLACE " } else { "" },
+        if temporary { "TEMP " } else { "" },
+        quote_identifier(table),
+        source
+    ))
+}
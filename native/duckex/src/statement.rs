@@ -0,0 +1,58 @@
+// SPDX-FileCopyrightText: 2025 Stas Muzhyk <sts@abc3.dev>
+// SPDX-FileCopyrightText: 2025 Łukasz Niemier <~@hauleth.dev>
+//
+// SPDX-License-Identifier: Apache-2.0
+
+use std::sync::{Arc, Mutex, Weak};
+
+use crate::cache::Cache;
+use crate::error::{Error, ErrorKind};
+
+/// Handle of the prepared statement returned to Elixir instead of bare cache
+/// id, so it cannot be forged nor used with connection other than the one that
+/// prepared it.
+///
+/// Statement cache is referenced weakly, so handles outliving the connection
+/// do not keep it alive.
+pub struct StatementResource {
+    id: u64,
+    queries: Weak<Mutex<Cache<String>>>,
+}
+
+impl StatementResource {
+    pub(crate) fn new(id: u64, queries: &Arc<Mutex<Cache<String>>>) -> Self {
+        StatementResource {
+            id,
+            queries: Arc::downgrade(queries),
+        }
+    }
+
+    /// Id of the statement in the `queries` cache, fails when the statement
+    /// was prepared on another connection
+    pub(crate) fn id_in(&self, queries: &Arc<Mutex<Cache<String>>>) -> Result<u64, Error> {
+        if std::ptr::eq(self.queries.as_ptr(), Arc::as_ptr(queries)) {
+            Ok(self.id)
+        } else {
+            Err(Error::new(
+                ErrorKind::InvalidInput,
+                "Statement was prepared on another connection",
+            ))
+        }
+    }
+}
+
+// Runs when the handle is garbage collected, e.g. after the process holding
+// it died without closing the statement. Removal is skipped when the cache is
+// locked by running query, not to block the scheduler doing GC, such entry
+// is eventually evicted as the least recently used.
+impl Drop for StatementResource {
+    fn drop(&mut self) {
+        let Some(queries) = self.queries.upgrade() else {
+            return;
+        };
+
+        if let Ok(mut queries) = queries.try_lock() {
+            queries.remove(self.id);
+        };
+    }
+}
@@ -0,0 +1,35 @@
+// SPDX-FileCopyrightText: 2025 Stas Muzhyk <sts@abc3.dev>
+// SPDX-FileCopyrightText: 2025 Łukasz Niemier <~@hauleth.dev>
+//
+// SPDX-License-Identifier: Apache-2.0
+
+use std::time::Duration;
+
+use rustler::{NifMap, NifUnitEnum};
+
+/// How long the health check waits for the connection before reporting it busy
+pub(crate) const LOCK_TIMEOUT: Duration = Duration::from_millis(100);
+
+#[derive(NifUnitEnum, Clone, Copy, PartialEq, Eq, Debug)]
+pub(crate) enum State {
+    // Connection answered the check query
+    Idle,
+    // Connection worker is running other jobs
+    Busy,
+    // Connection needs to be reopened
+    Broken,
+    // Connection was closed with `disconnect`
+    Closed,
+}
+
+#[derive(NifMap)]
+pub(crate) struct Status {
+    pub(crate) state: State,
+    pub(crate) database: String,
+    // Value of DuckDB `access_mode` setting, `nil` when it could not be read
+    pub(crate) access_mode: Option<String>,
+    // Reason the connection is broken
+    pub(crate) error: Option<String>,
+    // Number of calls waiting for or running on the connection worker thread
+    pub(crate) queued: usize,
+}
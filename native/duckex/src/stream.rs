@@ -0,0 +1,45 @@
+// SPDX-FileCopyrightText: 2025 Stas Muzhyk <sts@abc3.dev>
+// SPDX-FileCopyrightText: 2025 Łukasz Niemier <~@hauleth.dev>
+//
+// SPDX-License-Identifier: Apache-2.0
+
+use std::sync::Mutex;
+
+use arrow::datatypes::SchemaRef;
+use arrow::ffi_stream::FFI_ArrowArrayStream;
+use arrow::record_batch::{RecordBatch, RecordBatchIterator};
+
+use crate::error::Error;
+use crate::lock;
+
+// Stream only holds record batches, which are safe to send between threads
+struct Stream(Box<FFI_ArrowArrayStream>);
+
+unsafe impl Send for Stream {}
+
+/// Query result exposed through Arrow C stream interface.
+///
+/// Other NIFs loaded in the same VM (e.g. Explorer) can import the stream from
+/// its address without copying the data. Importing moves the stream out and
+/// leaves released one behind, so the resource can be dropped safely either way.
+pub struct ArrowStreamResource {
+    stream: Mutex<Stream>,
+}
+
+impl ArrowStreamResource {
+    pub(crate) fn new(schema: SchemaRef, batches: Vec<RecordBatch>) -> Self {
+        let reader = RecordBatchIterator::new(batches.into_iter().map(Ok), schema);
+        let stream = FFI_ArrowArrayStream::new(Box::new(reader));
+
+        ArrowStreamResource {
+            stream: Mutex::new(Stream(Box::new(stream))),
+        }
+    }
+
+    /// Address of the `ArrowArrayStream` struct, stable for the resource's life
+    pub(crate) fn address(&self) -> Result<u64, Error> {
+        let mut stream = lock::acquire(&self.stream, None)?;
+
+        Ok(&mut *stream.0 as *mut FFI_ArrowArrayStream as u64)
+    }
+}
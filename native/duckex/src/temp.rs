@@ -0,0 +1,71 @@
+// SPDX-FileCopyrightText: 2025 Stas Muzhyk <sts@abc3.dev>
+// SPDX-FileCopyrightText: 2025 Łukasz Niemier <~@hauleth.dev>
+//
+// SPDX-License-Identifier: Apache-2.0
+
+use std::fs::{File, OpenOptions};
+use std::hash::{BuildHasher, RandomState};
+use std::io::{self, ErrorKind, Write};
+use std::path::{Path, PathBuf};
+
+// Attempts to find a name not taken yet
+const ATTEMPTS: usize = 16;
+
+/// File in the system temporary directory that is removed when dropped. It
+/// is created with random name and fails when the name is taken, so nothing
+/// planted in the shared directory upfront, e.g. a symlink, is written to.
+pub(crate) struct TempFile {
+    path: PathBuf,
+    file: File,
+}
+
+impl TempFile {
+    pub(crate) fn new(extension: &str) -> io::Result<Self> {
+        let mut attempt = 0;
+
+        loop {
+            // Hasher keys are random, so are the hashes
+            let random = RandomState::new().hash_one(std::process::id());
+            let name = format!("duckex-{:016x}.{}", random, extension);
+            let path = std::env::temp_dir().join(name);
+
+            match create(&path) {
+                Ok(file) => return Ok(TempFile { path, file }),
+                Err(e) if e.kind() == ErrorKind::AlreadyExists && attempt < ATTEMPTS => {
+                    attempt += 1;
+                }
+                Err(e) => return Err(e),
+            }
+        }
+    }
+
+    pub(crate) fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Path as a string suitable for embedding in SQL
+    pub(crate) fn path_str(&self) -> String {
+        self.path.to_string_lossy().into_owned()
+    }
+
+    pub(crate) fn write_all(&self, data: &[u8]) -> io::Result<()> {
+        (&self.file).write_all(data)
+    }
+}
+
+// Readable only by the owner, as the data may be sensitive
+fn create(path: &Path) -> io::Result<File> {
+    let mut options = OpenOptions::new();
+    options.write(true).create_new(true);
+
+    #[cfg(unix)]
+    std::os::unix::fs::OpenOptionsExt::mode(&mut options, 0o600);
+
+    options.open(path)
+}
+
+impl Drop for TempFile {
+    fn drop(&mut self) {
+        let _ = std::fs::remove_file(&self.path);
+    }
+}
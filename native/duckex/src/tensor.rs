@@ -0,0 +1,142 @@
+// SPDX-FileCopyrightText: 2025 Stas Muzhyk <sts@abc3.dev>
+// SPDX-FileCopyrightText: 2025 Łukasz Niemier <~@hauleth.dev>
+//
+// SPDX-License-Identifier: Apache-2.0
+
+use arrow::array::{Array, AsArray};
+use arrow::datatypes::{
+    DataType, Float32Type, Float64Type, Int16Type, Int32Type, Int64Type, Int8Type, SchemaRef,
+    UInt16Type, UInt32Type, UInt64Type, UInt8Type,
+};
+use arrow::record_batch::RecordBatch;
+use rustler::{Atom, Binary, Encoder, Env, NewBinary, Term};
+
+use crate::error::{Error, ErrorKind};
+
+mod atoms {
+    rustler::atoms! {
+        name,
+        r#type = "type",
+        shape,
+        data,
+        s,
+        u,
+        f,
+    }
+}
+
+/// Numeric column as contiguous binary of native endian values, encoded as
+/// `%{name: String.t(), type: {:s | :u | :f, bits}, shape: {rows}, data: binary}`
+/// matching arguments of `Nx.from_binary/3`
+pub(crate) struct Column {
+    name: String,
+    r#type: (Atom, usize),
+    rows: usize,
+    data: Vec<u8>,
+}
+
+impl Encoder for Column {
+    fn encode<'a>(&self, env: Env<'a>) -> Term<'a> {
+        let mut data = NewBinary::new(env, self.data.len());
+        data.as_mut_slice().copy_from_slice(&self.data);
+
+        let keys = [
+            atoms::name().encode(env),
+            atoms::r#type().encode(env),
+            atoms::shape().encode(env),
+            atoms::data().encode(env),
+        ];
+        let values = [
+            self.name.encode(env),
+            self.r#type.encode(env),
+            (self.rows,).encode(env),
+            Binary::from(data).encode(env),
+        ];
+
+        Term::map_from_term_arrays(env, &keys, &values)
+            .unwrap_or_else(|_| rustler::types::atom::nil().encode(env))
+    }
+}
+
+/// Pack each column of the result into contiguous binary. Fails on columns
+/// that are not numeric or that hold `NULL`s, as tensors have no notion of them
+pub(crate) fn columns(schema: &SchemaRef, batches: &[RecordBatch]) -> Result<Vec<Column>, Error> {
+    schema
+        .fields()
+        .iter()
+        .enumerate()
+        .map(|(idx, field)| {
+            let r#type = match field.data_type() {
+                DataType::Int8 => (atoms::s(), 8),
+                DataType::Int16 => (atoms::s(), 16),
+                DataType::Int32 => (atoms::s(), 32),
+                DataType::Int64 => (atoms::s(), 64),
+                DataType::UInt8 | DataType::Boolean => (atoms::u(), 8),
+                DataType::UInt16 => (atoms::u(), 16),
+                DataType::UInt32 => (atoms::u(), 32),
+                DataType::UInt64 => (atoms::u(), 64),
+                DataType::Float32 => (atoms::f(), 32),
+                DataType::Float64 => (atoms::f(), 64),
+                other => {
+                    return Err(invalid(format!(
+                        "Column {} of type {} cannot be converted to tensor",
+                        field.name(),
+                        other
+                    )))
+                }
+            };
+
+            let mut data = vec![];
+            let mut rows = 0;
+
+            for batch in batches {
+                let array = batch.column(idx);
+
+                if array.null_count() > 0 {
+                    return Err(invalid(format!(
+                        "Column {} holds NULL values, which cannot be converted to tensor",
+                        field.name()
+                    )));
+                }
+
+                append(&mut data, array.as_ref());
+                rows += array.len();
+            }
+
+            Ok(Column {
+                name: field.name().clone(),
+                r#type,
+                rows,
+                data,
+            })
+        })
+        .collect()
+}
+
+// Buffers of primitive arrays already hold values in native endianness
+fn append(data: &mut Vec<u8>, array: &dyn Array) {
+    let bytes = match array.data_type() {
+        DataType::Int8 => array.as_primitive::<Int8Type>().values().inner().as_slice(),
+        DataType::Int16 => array.as_primitive::<Int16Type>().values().inner().as_slice(),
+        DataType::Int32 => array.as_primitive::<Int32Type>().values().inner().as_slice(),
+        DataType::Int64 => array.as_primitive::<Int64Type>().values().inner().as_slice(),
+        DataType::UInt8 => array.as_primitive::<UInt8Type>().values().inner().as_slice(),
+        DataType::UInt16 => array.as_primitive::<UInt16Type>().values().inner().as_slice(),
+        DataType::UInt32 => array.as_primitive::<UInt32Type>().values().inner().as_slice(),
+        DataType::UInt64 => array.as_primitive::<UInt64Type>().values().inner().as_slice(),
+        DataType::Float32 => array.as_primitive::<Float32Type>().values().inner().as_slice(),
+        DataType::Float64 => array.as_primitive::<Float64Type>().values().inner().as_slice(),
+        // Booleans are bit-packed, so unpack them into bytes
+        DataType::Boolean => {
+            data.extend(array.as_boolean().values().iter().map(u8::from));
+            return;
+        }
+        _ => return,
+    };
+
+    data.extend_from_slice(bytes);
+}
+
+fn invalid(message: String) -> Error {
+    Error::new(ErrorKind::InvalidInput, message)
+}
@@ -0,0 +1,31 @@
+// SPDX-FileCopyrightText: 2025 Stas Muzhyk <sts@abc3.dev>
+// SPDX-FileCopyrightText: 2025 Łukasz Niemier <~@hauleth.dev>
+//
+// SPDX-License-Identifier: Apache-2.0
+
+use rustler::schedule::consume_timeslice;
+use rustler::Env;
+
+// Rows encoded between reports to the scheduler
+const SLICE_ROWS: usize = 500;
+// Percentage of the timeslice reported for each slice of rows
+const SLICE_PERCENT: i32 = 5;
+
+/// Tracks how much of its timeslice the NIF used while encoding rows, so it can
+/// return early and let the scheduler run other processes before next chunk.
+pub struct Timeslice {
+    rows: usize,
+}
+
+impl Timeslice {
+    pub fn new() -> Self {
+        Timeslice { rows: 0 }
+    }
+
+    /// Count encoded row, returns `true` once the timeslice is used up
+    pub fn row(&mut self, env: Env) -> bool {
+        self.rows += 1;
+
+        self.rows.is_multiple_of(SLICE_ROWS) && consume_timeslice(env, SLICE_PERCENT)
+    }
+}
@@ -0,0 +1,48 @@
+// SPDX-FileCopyrightText: 2025 Stas Muzhyk <sts@abc3.dev>
+// SPDX-FileCopyrightText: 2025 Łukasz Niemier <~@hauleth.dev>
+//
+// SPDX-License-Identifier: Apache-2.0
+
+// Without ICU extension DuckDB knows only UTC, so `TIMESTAMPTZ` arithmetic and
+// casts ignore the time zone and `TimeZone` setting cannot be changed
+
+use duckdb::Connection;
+
+use crate::error::Error;
+use crate::extension;
+use crate::options::Options;
+use crate::sql::quote_literal;
+
+/// Load ICU and set time zone given with `:icu` and `:timezone` options
+pub(crate) fn setup(conn: &Connection, opts: &Options) -> Result<(), Error> {
+    if let Some(timezone) = opts.get_string("timezone")? {
+        set(conn, &timezone)
+    } else if opts.get::<bool>("icu")?.unwrap_or(false) {
+        load_icu(conn)
+    } else {
+        Ok(())
+    }
+}
+
+/// Set session time zone, e.g. "Europe/Warsaw", loading ICU first
+pub(crate) fn set(conn: &Connection, timezone: &str) -> Result<(), Error> {
+    load_icu(conn)?;
+
+    conn.execute_batch(&format!("SET TimeZone = {}", quote_literal(timezone)))
+        .map_err(|e| format!("Failed to set time zone '{}': {}", timezone, e))?;
+
+    Ok(())
+}
+
+// Both are no-op once the extension is installed and loaded
+fn load_icu(conn: &Connection) -> Result<(), Error> {
+    conn.execute_batch("INSTALL icu; LOAD icu").map_err(|e| {
+        let message = e.to_string();
+        (
+            extension::error_kind(&message),
+            format!("Failed to load extension 'icu': {}", message),
+        )
+    })?;
+
+    Ok(())
+}
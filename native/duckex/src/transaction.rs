@@ -0,0 +1,34 @@
+// SPDX-FileCopyrightText: 2025 Stas Muzhyk <sts@abc3.dev>
+// SPDX-FileCopyrightText: 2025 Łukasz Niemier <~@hauleth.dev>
+//
+// SPDX-License-Identifier: Apache-2.0
+
+use rustler::NifUnitEnum;
+
+use crate::split;
+
+#[derive(NifUnitEnum, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum Status {
+    Idle,
+    Transaction,
+}
+
+/// Whether transaction is open after successfully running `sql`, `None` when
+/// the statement neither starts nor ends one
+pub(crate) fn active_after(sql: &str) -> Option<bool> {
+    let mut words = words(sql);
+
+    match words.next()?.as_str() {
+        "BEGIN" | "START" => Some(true),
+        "COMMIT" | "END" | "ROLLBACK" | "ABORT" => Some(false),
+        _ => None,
+    }
+}
+
+// Keywords of the statement, without those in comments or literals
+fn words(sql: &str) -> impl Iterator<Item = String> + '_ {
+    split::tokens(sql).into_iter().filter_map(|token| match token {
+        split::Token::Word(word) => Some(word.to_ascii_uppercase()),
+        _ => None,
+    })
+}
@@ -0,0 +1,122 @@
+// SPDX-FileCopyrightText: 2025 Stas Muzhyk <sts@abc3.dev>
+// SPDX-FileCopyrightText: 2025 Łukasz Niemier <~@hauleth.dev>
+//
+// SPDX-License-Identifier: Apache-2.0
+
+// DuckDB bindings unwrap UNION values to the value of their member, losing
+// which member it is. Tags of the members are read from the Arrow arrays of
+// the result instead, and kept next to the values.
+
+use std::cell::Cell;
+
+use arrow::array::{Array, AsArray};
+use arrow::datatypes::DataType;
+use duckdb::types::{ListType, ValueRef};
+
+/// Member tags of UNION values within one value, in the order the values are
+/// visited when it is encoded: depth first, struct fields and list items in
+/// order, values of map entries only, as their keys are encoded as strings.
+pub(crate) fn tags(value: &ValueRef) -> Vec<String> {
+    let mut tags = vec![];
+
+    match *value {
+        ValueRef::Union(array, row) => collect(array.as_ref(), row, &mut tags),
+        ValueRef::List(ListType::Regular(array), row) => collect(array, row, &mut tags),
+        ValueRef::List(ListType::Large(array), row) => collect(array, row, &mut tags),
+        ValueRef::Struct(array, row) => collect(array, row, &mut tags),
+        ValueRef::Array(array, row) => collect(array, row, &mut tags),
+        ValueRef::Map(array, row) => collect(array, row, &mut tags),
+        _ => {}
+    }
+
+    tags
+}
+
+// Mirrors how the bindings turn Arrow arrays into values, including that
+// `NULL` values are not looked into
+fn collect(array: &dyn Array, row: usize, tags: &mut Vec<String>) {
+    if array.is_null(row) {
+        return;
+    }
+
+    match array.data_type() {
+        DataType::Union(fields, _) => {
+            let union = array.as_union();
+            let type_id = union.type_id(row);
+
+            if let Some((_, field)) = fields.iter().find(|(id, _)| *id == type_id) {
+                tags.push(field.name().clone());
+            }
+
+            collect(union.child(type_id).as_ref(), row, tags);
+        }
+        DataType::List(_) => {
+            let list = array.as_list::<i32>();
+            let offsets = list.value_offsets();
+
+            for idx in offsets[row] as usize..offsets[row + 1] as usize {
+                collect(list.values().as_ref(), idx, tags);
+            }
+        }
+        DataType::LargeList(_) => {
+            let list = array.as_list::<i64>();
+            let offsets = list.value_offsets();
+
+            for idx in offsets[row] as usize..offsets[row + 1] as usize {
+                collect(list.values().as_ref(), idx, tags);
+            }
+        }
+        DataType::FixedSizeList(_, size) => {
+            let size = *size as usize;
+            let list = array.as_fixed_size_list();
+
+            for idx in row * size..(row + 1) * size {
+                collect(list.values().as_ref(), idx, tags);
+            }
+        }
+        DataType::Struct(_) => {
+            for column in array.as_struct().columns() {
+                collect(column.as_ref(), row, tags);
+            }
+        }
+        DataType::Map(..) => {
+            let map = array.as_map();
+            let offsets = map.value_offsets();
+
+            for idx in offsets[row] as usize..offsets[row + 1] as usize {
+                collect(map.values().as_ref(), idx, tags);
+            }
+        }
+        _ => {}
+    }
+}
+
+/// Tags of the value in column `idx` of the row, with `row` holding tags of
+/// its values, or nothing when there are no unions in the row
+pub(crate) fn column(row: &[Vec<String>], idx: usize) -> Tags<'_> {
+    Tags::new(row.get(idx).map_or(&[][..], Vec::as_slice))
+}
+
+/// Tags of one value, taken in the order `tags` listed them, as its unions
+/// are encoded
+pub(crate) struct Tags<'a> {
+    tags: &'a [String],
+    next: Cell<usize>,
+}
+
+impl<'a> Tags<'a> {
+    pub(crate) fn new(tags: &'a [String]) -> Self {
+        Tags {
+            tags,
+            next: Cell::new(0),
+        }
+    }
+
+    /// Tag of the next union, `None` when it is not known
+    pub(crate) fn next(&self) -> Option<&'a str> {
+        let idx = self.next.get();
+        self.next.set(idx + 1);
+
+        self.tags.get(idx).map(String::as_str)
+    }
+}
@@ -0,0 +1,99 @@
+// SPDX-FileCopyrightText: 2025 Stas Muzhyk <sts@abc3.dev>
+// SPDX-FileCopyrightText: 2025 Łukasz Niemier <~@hauleth.dev>
+//
+// SPDX-License-Identifier: Apache-2.0
+
+use duckdb::Config;
+
+use crate::error::{Error, ErrorKind};
+
+const SCHEME: &str = "duckdb://";
+
+/// Database given as URI, e.g.
+/// `duckdb:///path/to/db.duckdb?threads=4&access_mode=read_only`, where query
+/// string holds DuckDB settings to open the database with
+pub(crate) struct Uri {
+    pub(crate) path: String,
+    pub(crate) settings: Vec<(String, String)>,
+}
+
+/// Parse `database`, plain paths are returned as they are, without settings.
+/// Path of `duckdb://` or `duckdb://:memory:` is `:memory:`.
+pub(crate) fn parse(database: &str) -> Result<Uri, Error> {
+    let Some(rest) = database.strip_prefix(SCHEME) else {
+        return Ok(Uri {
+            path: database.to_string(),
+            settings: vec![],
+        });
+    };
+
+    let (path, query) = rest.split_once('?').unwrap_or((rest, ""));
+
+    let path = match decode(path, false)? {
+        path if path.is_empty() || path == "/:memory:" => ":memory:".to_string(),
+        path => path,
+    };
+
+    let settings = query
+        .split('&')
+        .filter(|pair| !pair.is_empty())
+        .map(|pair| {
+            let (key, value) = pair.split_once('=').ok_or_else(|| {
+                Error::new(
+                    ErrorKind::InvalidInput,
+                    format!("Setting '{}' in database URI has no value", pair),
+                )
+            })?;
+
+            Ok((decode(key, true)?, decode(value, true)?))
+        })
+        .collect::<Result<_, Error>>()?;
+
+    Ok(Uri { path, settings })
+}
+
+impl Uri {
+    /// Apply settings from the query string on top of `config`
+    pub(crate) fn configure(&self, config: Config) -> Result<Config, Error> {
+        self.settings.iter().try_fold(config, |config, (key, value)| {
+            config
+                .with(key, value)
+                .map_err(|e| format!("Invalid setting '{}' in database URI: {}", key, e).into())
+        })
+    }
+}
+
+// Percent-decoding. In query string `+` stands for space as in HTML forms, in
+// path it is a plain character of the file name.
+fn decode(text: &str, query: bool) -> Result<String, Error> {
+    let invalid = || {
+        Error::new(
+            ErrorKind::InvalidInput,
+            format!("Invalid percent-encoding in database URI: {}", text),
+        )
+    };
+
+    let bytes = text.as_bytes();
+    let mut decoded = Vec::with_capacity(bytes.len());
+    let mut pos = 0;
+
+    while pos < bytes.len() {
+        match bytes[pos] {
+            b'%' => {
+                let hex = text.get(pos + 1..pos + 3).ok_or_else(invalid)?;
+                decoded.push(u8::from_str_radix(hex, 16).map_err(|_| invalid())?);
+                pos += 3;
+            }
+            b'+' if query => {
+                decoded.push(b' ');
+                pos += 1;
+            }
+            byte => {
+                decoded.push(byte);
+                pos += 1;
+            }
+        }
+    }
+
+    String::from_utf8(decoded).map_err(|_| invalid())
+}
@@ -0,0 +1,90 @@
+// SPDX-FileCopyrightText: 2025 Stas Muzhyk <sts@abc3.dev>
+// SPDX-FileCopyrightText: 2025 Łukasz Niemier <~@hauleth.dev>
+//
+// SPDX-License-Identifier: Apache-2.0
+
+use std::sync::{Mutex, OnceLock};
+
+use duckdb::Connection;
+use rustler::NifMap;
+
+use crate::error::{Error, ErrorKind};
+use crate::{lock, split};
+
+// Empty database statements are prepared against. Preparing parses the whole
+// statement, while missing tables or columns are reported as catalog or
+// binder errors, which are ignored here.
+static VALIDATOR: OnceLock<Mutex<Connection>> = OnceLock::new();
+
+/// Syntax error found in one of the statements
+#[derive(NifMap)]
+pub(crate) struct Diagnostic {
+    // Index of the statement within the validated SQL
+    statement: usize,
+    message: String,
+    // Byte offset of the error within the statement, when known
+    position: Option<usize>,
+}
+
+pub(crate) fn validate(sql: &str, split: bool) -> Result<Vec<Diagnostic>, Error> {
+    let conn = match VALIDATOR.get() {
+        Some(conn) => conn,
+        None => {
+            let conn = Connection::open_in_memory()
+                .map_err(|e| format!("Failed to create in-memory DuckDB connection: {}", e))?;
+
+            VALIDATOR.get_or_init(|| Mutex::new(conn))
+        }
+    };
+
+    let conn = lock::acquire(conn, None)?;
+
+    let statements = if split {
+        split::statements(sql)
+    } else {
+        vec![sql.to_string()]
+    };
+
+    let diagnostics = statements
+        .iter()
+        .enumerate()
+        .filter_map(|(idx, statement)| {
+            let error = Error::from(conn.prepare(statement).err()?);
+
+            (error.kind == ErrorKind::Syntax).then(|| Diagnostic {
+                statement: idx,
+                position: position(statement, &error.message),
+                message: error.message,
+            })
+        })
+        .collect();
+
+    Ok(diagnostics)
+}
+
+// DuckDB points at the error with caret below the offending line:
+//
+//     LINE 2: SELEC 1
+//             ^
+//
+// Long lines are shortened with "...", position is unknown then.
+fn position(statement: &str, message: &str) -> Option<usize> {
+    let mut lines = message.lines().skip_while(|line| !line.starts_with("LINE "));
+
+    let line = lines.next()?;
+    let (number, text) = line.strip_prefix("LINE ")?.split_once(": ")?;
+    let column = lines.next()?.find('^')?.checked_sub(line.len() - text.len())?;
+
+    if text.starts_with("...") {
+        return None;
+    }
+
+    let number: usize = number.parse().ok()?;
+    let offset: usize = statement
+        .split_inclusive('\n')
+        .take(number.checked_sub(1)?)
+        .map(str::len)
+        .sum();
+
+    Some(offset + column)
+}
@@ -0,0 +1,62 @@
+// SPDX-FileCopyrightText: 2025 Stas Muzhyk <sts@abc3.dev>
+// SPDX-FileCopyrightText: 2025 Łukasz Niemier <~@hauleth.dev>
+//
+// SPDX-License-Identifier: Apache-2.0
+
+// Arbitrary precision `VARINT` (`BIGNUM` since DuckDB 1.4) integers. Arrow
+// has no such type, so DuckDB exports them as binaries in its own encoding,
+// tagged with the opaque extension type naming the DuckDB type.
+
+use arrow::datatypes::Schema;
+use num_bigint::{BigInt, Sign};
+use serde_json::Value as Json;
+
+const EXTENSION_KEY: &str = "ARROW:extension:name";
+const EXTENSION_NAME: &str = "arrow.opaque";
+const METADATA_KEY: &str = "ARROW:extension:metadata";
+// Name of the type in metadata, it was renamed in DuckDB 1.4
+const TYPE_NAMES: [&str; 2] = ["bignum", "varint"];
+
+// Header of 3 bytes holds sign and number of the data bytes
+const HEADER_SIZE: usize = 3;
+
+/// Which columns of the result hold `VARINT` values
+pub(crate) fn columns(schema: &Schema) -> Vec<bool> {
+    schema
+        .fields()
+        .iter()
+        .map(|field| {
+            let metadata = field.metadata();
+
+            metadata.get(EXTENSION_KEY).is_some_and(|name| name == EXTENSION_NAME)
+                && metadata.get(METADATA_KEY).is_some_and(|json| is_varint(json))
+        })
+        .collect()
+}
+
+// Metadata of opaque type is JSON object with `type_name` and `vendor_name`
+fn is_varint(metadata: &str) -> bool {
+    let Ok(metadata) = serde_json::from_str::<Json>(metadata) else {
+        return false;
+    };
+
+    metadata["type_name"]
+        .as_str()
+        .is_some_and(|name| TYPE_NAMES.iter().any(|ty| name.eq_ignore_ascii_case(ty)))
+}
+
+/// Decode integer from DuckDB `VARINT` encoding, big-endian magnitude after
+/// the header, where negative numbers have all bits inverted
+pub(crate) fn decode(bytes: &[u8]) -> Option<BigInt> {
+    let header = bytes.get(..HEADER_SIZE)?;
+    let data = &bytes[HEADER_SIZE..];
+    let negative = header[0] & 0x80 == 0;
+
+    if negative {
+        let magnitude: Vec<u8> = data.iter().map(|byte| !byte).collect();
+
+        Some(BigInt::from_bytes_be(Sign::Minus, &magnitude))
+    } else {
+        Some(BigInt::from_bytes_be(Sign::Plus, data))
+    }
+}
@@ -0,0 +1,40 @@
+// SPDX-FileCopyrightText: 2025 Stas Muzhyk <sts@abc3.dev>
+// SPDX-FileCopyrightText: 2025 Łukasz Niemier <~@hauleth.dev>
+//
+// SPDX-License-Identifier: Apache-2.0
+
+use std::sync::mpsc::{self, RecvTimeoutError, Sender};
+use std::sync::Arc;
+use std::thread::JoinHandle;
+use std::time::Duration;
+
+use duckdb::InterruptHandle;
+
+/// Thread that interrupts the running query when it does not finish in time
+pub(crate) struct Watchdog {
+    cancel: Sender<()>,
+    thread: JoinHandle<bool>,
+}
+
+impl Watchdog {
+    pub(crate) fn arm(handle: Arc<InterruptHandle>, timeout: Duration) -> Self {
+        let (cancel, cancelled) = mpsc::channel();
+
+        let thread = std::thread::spawn(move || match cancelled.recv_timeout(timeout) {
+            Err(RecvTimeoutError::Timeout) => {
+                handle.interrupt();
+                true
+            }
+            _ => false,
+        });
+
+        Watchdog { cancel, thread }
+    }
+
+    /// Stop the watchdog, returns whether the query was interrupted
+    pub(crate) fn disarm(self) -> bool {
+        let _ = self.cancel.send(());
+
+        self.thread.join().unwrap_or(false)
+    }
+}
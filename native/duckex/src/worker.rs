@@ -0,0 +1,161 @@
+// SPDX-FileCopyrightText: 2025 Stas Muzhyk <sts@abc3.dev>
+// SPDX-FileCopyrightText: 2025 Łukasz Niemier <~@hauleth.dev>
+//
+// SPDX-License-Identifier: Apache-2.0
+
+use std::panic::{catch_unwind, resume_unwind, AssertUnwindSafe};
+use std::sync::atomic::{AtomicBool, AtomicU8, AtomicUsize, Ordering};
+use std::sync::mpsc::{self, RecvTimeoutError, Sender};
+use std::sync::Arc;
+use std::time::Duration;
+
+use duckdb::Connection;
+
+use crate::error::Error;
+use crate::panic;
+
+type Job = Box<dyn FnOnce(&mut Option<Connection>) + Send + 'static>;
+
+// States of the job waited for with `run`
+const QUEUED: u8 = 0;
+const RUNNING: u8 = 1;
+const CANCELLED: u8 = 2;
+
+/// Dedicated thread owning the connection. Every call on the connection is
+/// sent to it as a job, jobs run one by one, in the order they were submitted,
+/// so the connection is never shared between threads nor locked.
+///
+/// NIFs waiting for their job with `run` run on dirty schedulers, results of
+/// `execute_async` queries are sent to Elixir processes as messages instead,
+/// so normal schedulers are never blocked waiting for the connection.
+///
+/// Thread stops once the connection is garbage collected, as then the channel
+/// is closed. The connection still open by then is checkpointed, so the next
+/// open does not have to replay the whole WAL, and closed.
+pub(crate) struct Worker {
+    sender: Sender<Job>,
+    // Jobs submitted and not finished yet
+    queued: Arc<AtomicUsize>,
+    // Set once a job panicked, the connection state cannot be trusted then
+    panicked: Arc<AtomicBool>,
+}
+
+impl Worker {
+    /// Start the thread, `checkpoint` tells whether the connection is
+    /// checkpointed before it is closed
+    pub(crate) fn spawn(conn: Connection, checkpoint: bool) -> Result<Self, Error> {
+        let (sender, receiver) = mpsc::channel::<Job>();
+        let queued = Arc::new(AtomicUsize::new(0));
+        let panicked = Arc::new(AtomicBool::new(false));
+
+        let worker = Worker {
+            sender,
+            queued: queued.clone(),
+            panicked: panicked.clone(),
+        };
+
+        std::thread::Builder::new()
+            .name("duckex-worker".to_string())
+            .spawn(move || {
+                let mut conn = Some(conn);
+
+                for job in receiver {
+                    // Panicking job must not take down jobs queued after it
+                    if catch_unwind(AssertUnwindSafe(|| job(&mut conn))).is_err() {
+                        panicked.store(true, Ordering::Release);
+                    }
+
+                    queued.fetch_sub(1, Ordering::AcqRel);
+                }
+
+                // Connection is not checkpointed after a panic, only closed
+                if let Some(conn) = conn {
+                    if checkpoint && !panicked.load(Ordering::Acquire) {
+                        let _ = conn.execute_batch("CHECKPOINT");
+                    }
+
+                    let _ = conn.close();
+                }
+            })
+            .map_err(|e| format!("Failed to start worker thread: {}", e))?;
+
+        Ok(worker)
+    }
+
+    /// Queue the job without waiting for it
+    pub(crate) fn submit(
+        &self,
+        job: impl FnOnce(&mut Option<Connection>) + Send + 'static,
+    ) -> Result<(), Error> {
+        self.queued.fetch_add(1, Ordering::AcqRel);
+
+        self.sender.send(Box::new(job)).map_err(|_| {
+            self.queued.fetch_sub(1, Ordering::AcqRel);
+            "Worker thread of the connection is gone".to_string().into()
+        })
+    }
+
+    /// Queue the job and wait for its result. When it does not start within
+    /// `timeout` it is cancelled and busy error returned, without timeout it
+    /// waits until the jobs queued before it are done. Job which started
+    /// already is always waited for.
+    pub(crate) fn run<T: Send + 'static>(
+        &self,
+        timeout: Option<Duration>,
+        job: impl FnOnce(&mut Option<Connection>) -> Result<T, Error> + Send + 'static,
+    ) -> Result<T, Error> {
+        let (reply, result) = mpsc::sync_channel(1);
+        let state = Arc::new(AtomicU8::new(QUEUED));
+        let job_state = state.clone();
+
+        self.submit(move |conn| {
+            let started = job_state
+                .compare_exchange(QUEUED, RUNNING, Ordering::AcqRel, Ordering::Acquire)
+                .is_ok();
+
+            if !started {
+                return;
+            }
+
+            match catch_unwind(AssertUnwindSafe(|| job(conn))) {
+                Ok(result) => {
+                    let _ = reply.send(result);
+                }
+                // Caller gets the panic message, the worker marks the
+                // connection as poisoned
+                Err(payload) => {
+                    let _ = reply.send(Err(panic::error(payload.as_ref())));
+                    resume_unwind(payload);
+                }
+            }
+        })?;
+
+        let Some(timeout) = timeout else {
+            return result.recv().unwrap_or_else(|_| Err(Error::poisoned()));
+        };
+
+        match result.recv_timeout(timeout) {
+            Ok(result) => result,
+            Err(RecvTimeoutError::Timeout) => {
+                let cancelled = state
+                    .compare_exchange(QUEUED, CANCELLED, Ordering::AcqRel, Ordering::Acquire)
+                    .is_ok();
+
+                if cancelled {
+                    Err(Error::busy())
+                } else {
+                    result.recv().unwrap_or_else(|_| Err(Error::poisoned()))
+                }
+            }
+            Err(RecvTimeoutError::Disconnected) => Err(Error::poisoned()),
+        }
+    }
+
+    pub(crate) fn queued(&self) -> usize {
+        self.queued.load(Ordering::Acquire)
+    }
+
+    pub(crate) fn panicked(&self) -> bool {
+        self.panicked.load(Ordering::Acquire)
+    }
+}
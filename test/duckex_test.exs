@@ -145,14 +145,17 @@ defmodule DuckexTest do
       assert {:ok, _, _} = @subject.execute(conn, query, [])
     end
 
-    test "prepared statements cache exhaustion", %{conn: conn} do
+    test "prepared statements cache evicts least recently used", %{conn: conn} do
       @subject.query!(conn, "CREATE TABLE person (name TEXT, data INTEGER)", [])
 
-      assert_raise Duckex.Error, "Exhausted prepared statements cache", fn ->
+      # Statements are kept referenced, so they are not removed on GC
+      queries =
         for _ <- 0..2000 do
           @subject.prepare!(conn, "SELECT name, data FROM person")
         end
-      end
+
+      assert {:ok, %{size: 1024}} = @subject.cache_stats(conn)
+      assert length(queries) == 2001
     end
 
     test "prepared query can be executed with other params", %{conn: conn} do
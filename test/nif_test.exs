@@ -29,6 +29,153 @@ defmodule DuckexNifTest do
       assert_in_delta val, 3.14159, 0.00001
     end
 
+    test "handles NaN and infinite floats", %{conn: conn} do
+      sql = "SELECT 'nan'::DOUBLE, 'inf'::FLOAT, '-inf'::DOUBLE"
+
+      assert {:ok, %{rows: [[:nan, :infinity, :neg_infinity]]}} = @subject.query(conn, sql)
+
+      assert {:ok, %{rows: [[true, true]]}} =
+               @subject.query(conn, "SELECT isnan(?::DOUBLE), ?::DOUBLE > 0", [:nan, :infinity])
+    end
+
+    test "rejects NaN and infinite floats in strict mode" do
+      conn = start_supervised!({@subject, strict_floats: true}, id: :strict)
+
+      assert {:error, %Duckex.Error{kind: :conversion}} =
+               @subject.query(conn, "SELECT [1.0, 'nan'::DOUBLE]")
+
+      assert {:ok, %{rows: [[1.5]]}} = @subject.query(conn, "SELECT 1.5::DOUBLE")
+    end
+
+    test "binds binaries which are not valid UTF-8 as blobs", %{conn: conn} do
+      assert {:ok, %{rows: [["BLOB", <<0xFF, 0>>]]}} =
+               @subject.query(conn, "SELECT typeof($1), $1", [<<0xFF, 0>>], blob: :raw)
+    end
+
+    test "rejects binaries which are not valid UTF-8 in strict text mode" do
+      conn = start_supervised!({@subject, strict_text: true}, id: :strict_text)
+
+      assert {:error, %Duckex.Error{kind: :invalid_input}} =
+               @subject.query(conn, "SELECT $1", [<<0xFF, 0>>])
+
+      assert {:ok, %{rows: [["ok"]]}} = @subject.query(conn, "SELECT $1", ["ok"])
+    end
+
+    test "binds atoms and strings to ENUM parameters", %{conn: conn} do
+      @subject.query!(conn, "CREATE TYPE mood AS ENUM ('sad', 'ok', 'happy')", [])
+      @subject.query!(conn, "CREATE TABLE moods (m mood)", [])
+
+      assert {:ok, _} = @subject.query(conn, "INSERT INTO moods VALUES ($1)", [:happy])
+      assert {:ok, _} = @subject.query(conn, "INSERT INTO moods VALUES ($1)", ["sad"])
+
+      assert {:error,
+              %Duckex.Error{
+                kind: :invalid_enum,
+                invalid_enum: %{parameter: 1, value: "angry", allowed: ["sad", "ok", "happy"]}
+              }} = @subject.query(conn, "INSERT INTO moods VALUES ($1)", [:angry])
+
+      assert {:ok, %{rows: [["happy"], ["sad"]]}} = @subject.query(conn, "SELECT m FROM moods")
+    end
+
+    test "binds fixed-size ARRAY parameters", %{conn: conn} do
+      @subject.query!(conn, "CREATE TABLE embeddings (v FLOAT[3])", [])
+
+      insert = "INSERT INTO embeddings VALUES ($1)"
+
+      assert {:ok, _} = @subject.query(conn, insert, [{:array, [1, 0.5, 2]}])
+
+      assert {:ok, %{rows: [[[1.0, 0.5, 2.0]]]}} =
+               @subject.query(conn, "SELECT v FROM embeddings")
+
+      assert {:error, %Duckex.Error{kind: :invalid_input, message: message}} =
+               @subject.query(conn, insert, [{:array, [1, 2]}])
+
+      assert message =~ "ARRAY of 3 elements, got 2"
+    end
+
+    test "binds ARRAY parameters of text, dates and decimals", %{conn: conn} do
+      query = "SELECT $1::TEXT[2], $2::DATE[2], $3::DECIMAL(4, 2)[1]::TEXT[1]"
+      text = {:array, ["it's", "back\\slash, [x]"]}
+      dates = {:array, [~D[2024-01-02], nil]}
+
+      assert {:ok, %{rows: [[["it's", "back\\slash, [x]"], [~D[2024-01-02], nil], ["1.25"]]]}} =
+               @subject.query(conn, query, [text, dates, {:array, ["1.25"]}])
+    end
+
+    test "checks size of nested ARRAY parameters", %{conn: conn} do
+      assert {:error, %Duckex.Error{kind: :invalid_input, message: message}} =
+               @subject.query(conn, "SELECT $1::INTEGER[2][2]", [{:array, [[1, 2], [3]]}])
+
+      assert message =~ "ARRAY of 2 elements, got 1"
+    end
+
+    test "returns UNION values tagged with their member", %{conn: conn} do
+      query = """
+      SELECT u FROM (
+        VALUES (union_value(num := 2)::UNION(num INTEGER, str VARCHAR)),
+               (union_value(str := 'two')::UNION(num INTEGER, str VARCHAR))
+      ) t(u)
+      """
+
+      assert {:ok, %{rows: [[{"num", 2}], [{"str", "two"}]]}} = @subject.query(conn, query)
+    end
+
+    test "tags UNION values nested in lists and structs", %{conn: conn} do
+      query = """
+      SELECT [union_value(num := 1)::UNION(num INTEGER, str VARCHAR),
+              union_value(str := 'b')::UNION(num INTEGER, str VARCHAR)] AS l,
+             {'u': union_value(str := 'c')::UNION(num INTEGER, str VARCHAR)} AS s
+      """
+
+      assert {:ok, %{rows: [[[{"num", 1}, {"str", "b"}], %{"u" => {"str", "c"}}]]}} =
+               @subject.query(conn, query)
+
+      assert {:ok, %{rows: json}} = @subject.query(conn, query, [], format: :json)
+      assert json == ~s([{"l":[{"num":1},{"str":"b"}],"s":{"u":{"str":"c"}}}])
+    end
+
+    test "returns STRUCT values as maps", %{conn: conn} do
+      query = "SELECT {'name': 'a', 'address': {'city': 'b', 'tags': [{'x': 1}]}} AS s"
+
+      assert {:ok, %{rows: [[value]]}} = @subject.query(conn, query)
+      assert value["address"]["city"] == "b"
+      assert %{"name" => "a", "address" => %{"tags" => [%{"x" => 1}]}} = value
+    end
+
+    test "handles VARINT values", %{conn: conn} do
+      big = 2 ** 200
+
+      assert {:ok, %{rows: [[^big, -1, 0]]}} =
+               @subject.query(conn, "SELECT $1::VARINT, -1::VARINT, 0::VARINT", [big])
+
+      assert {:ok, %{rows: [[-170_141_183_460_469_231_731_687_303_715_884_105_728]]}} =
+               @subject.query(conn, "SELECT $1::VARINT", [-(2 ** 127)])
+    end
+
+    test "binds TIME WITH TIME ZONE values with their offset", %{conn: conn} do
+      @subject.query!(conn, "CREATE TABLE shifts (starts TIMETZ)", [])
+      @subject.query!(conn, "INSERT INTO shifts VALUES (?)", [{~T[12:30:00], 7200}])
+      @subject.query!(conn, "INSERT INTO shifts VALUES (?)", [{~T[08:15:30.250000], -19800}])
+
+      assert {:ok, %{rows: rows}} =
+               @subject.query(conn, "SELECT starts::VARCHAR FROM shifts ORDER BY starts", [])
+
+      assert Enum.map(rows, fn [value] -> Duckex.parse_time_tz(value) end) == [
+               {:ok, {~T[12:30:00], 7200}},
+               {:ok, {~T[08:15:30.25], -19800}}
+             ]
+
+      assert {:error, %Duckex.Error{}} =
+               @subject.query(conn, "SELECT ?::TIMETZ", [{~T[12:00:00], 16 * 3600}])
+    end
+
+    test "binds Time values", %{conn: conn} do
+      assert {:ok, %{rows: [[micros]]}} =
+               @subject.query(conn, "SELECT ?::TIME", [~T[08:15:30.250000]])
+
+      assert micros == (8 * 3600 + 15 * 60 + 30) * 1_000_000 + 250_000
+    end
+
     test "handles text types", %{conn: conn} do
       @subject.query!(conn, "CREATE TABLE test (val TEXT)", [])
       @subject.query!(conn, "INSERT INTO test VALUES (?)", ["Hello, DuckDB!"])
@@ -37,6 +184,15 @@ defmodule DuckexNifTest do
                @subject.query(conn, "SELECT * FROM test", [])
     end
 
+    test "handles large text and blob values", %{conn: conn} do
+      text = String.duplicate("duckdb ", 1_000_000)
+
+      assert {:ok, %{rows: [[^text, blob]]}} =
+               @subject.query(conn, "SELECT ?::TEXT, ?::TEXT::BLOB", [text, text])
+
+      assert Base.decode64!(blob) == text
+    end
+
     test "handles boolean types", %{conn: conn} do
       @subject.query!(conn, "CREATE TABLE test (val BOOLEAN)", [])
       @subject.query!(conn, "INSERT INTO test VALUES (?), (?)", [true, false])
@@ -85,12 +241,76 @@ defmodule DuckexNifTest do
       assert message =~ "Conversion Error"
     end
 
+    test "classifies errors", %{conn: conn} do
+      assert {:error, %Duckex.Error{kind: :syntax, code: "42601"}} =
+               @subject.query(conn, "INVALID SQL STATEMENT", [])
+
+      assert {:error, %Duckex.Error{kind: :catalog}} =
+               @subject.query(conn, "SELECT * FROM non_existent_table", [])
+
+      @subject.query!(conn, "CREATE TABLE test (val INTEGER NOT NULL)", [])
+
+      assert {:error, %Duckex.Error{kind: :constraint, code: "23000"}} =
+               @subject.query(conn, "INSERT INTO test VALUES (?)", [nil])
+    end
+
+    test "reports violated constraint", %{conn: conn} do
+      @subject.query!(
+        conn,
+        "CREATE TABLE users (id INTEGER PRIMARY KEY, email TEXT UNIQUE, name TEXT NOT NULL)",
+        []
+      )
+
+      @subject.query!(conn, "INSERT INTO users VALUES (1, 'a@example.com', 'A')", [])
+
+      assert {:error, %Duckex.Error{constraint: %{type: :not_null, column: "name"}}} =
+               @subject.query(conn, "INSERT INTO users VALUES (2, 'b@example.com', NULL)", [])
+
+      assert {:error,
+              %Duckex.Error{constraint: %{type: :primary_key, table: "users", column: "id"}}} =
+               @subject.query(conn, "INSERT INTO users VALUES (1, 'b@example.com', 'B')", [])
+
+      assert {:error,
+              %Duckex.Error{constraint: %{type: :unique, table: "users", column: "email"}}} =
+               @subject.query(conn, "INSERT INTO main.users VALUES (2, 'a@example.com', 'B')", [])
+
+      assert {:error, %Duckex.Error{constraint: nil}} =
+               @subject.query(conn, "SELECT * FROM non_existent_table", [])
+    end
+
+    test "reports table of violated key constraint", %{conn: conn} do
+      @subject.query!(conn, ~s(CREATE TABLE "Accounts" (id INTEGER PRIMARY KEY)), [])
+      @subject.query!(conn, ~s(INSERT INTO "Accounts" VALUES (1), (2)), [])
+      @subject.query!(conn, "CREATE TABLE customers (id INTEGER PRIMARY KEY)", [])
+      @subject.query!(conn, "CREATE TABLE orders (customer_id INTEGER REFERENCES customers)", [])
+
+      assert {:error, %Duckex.Error{constraint: %{type: :primary_key, table: "Accounts"}}} =
+               @subject.query(conn, ~s(UPDATE "Accounts" a SET id = 1 WHERE id = 2), [])
+
+      query = "WITH v AS (SELECT 2 AS id) INSERT INTO orders SELECT id FROM v"
+
+      assert {:error, %Duckex.Error{constraint: %{type: :foreign_key, table: "orders"}}} =
+               @subject.query(conn, query, [])
+    end
+
+    test "errors can be raised", %{conn: conn} do
+      assert_raise Duckex.Error, ~r/Parser Error/, fn ->
+        @subject.query!(conn, "INVALID SQL STATEMENT", [])
+      end
+    end
+
     test "returns error for invalid parameter count", %{conn: conn} do
       @subject.query!(conn, "CREATE TABLE test (a INTEGER, b INTEGER)", [])
 
       # Too few parameters
-      assert {:error, %Duckex.Error{}} =
+      assert {:error, %Duckex.Error{kind: :arity, arity: arity}} =
                @subject.query(conn, "INSERT INTO test VALUES (?, ?)", [1])
+
+      assert %{expected: 2, got: 1, sql: "INSERT INTO test VALUES (?, ?)"} = arity
+
+      # Too many parameters
+      assert {:error, %Duckex.Error{kind: :arity, arity: %{expected: 0, got: 1}}} =
+               @subject.query(conn, "SELECT 1", [1])
     end
   end
 
@@ -122,6 +342,106 @@ defmodule DuckexNifTest do
       assert {:ok, %{rows: [[1, nil], [2, "foo"]]}} =
                @subject.query(conn, "SELECT * FROM test ORDER BY id", [])
     end
+
+    test "rejects statement prepared on another connection", %{conn: conn} do
+      other = start_supervised!({@subject, attach: []}, id: :other)
+
+      {:ok, query} = @subject.prepare(other, "SELECT 1")
+
+      assert is_reference(query.stmt)
+
+      assert {:error, %Duckex.Error{kind: :invalid_input}} = @subject.execute(conn, query, [])
+      assert {:ok, _, %{rows: [[1]]}} = @subject.execute(other, query, [])
+    end
+  end
+
+  describe "named parameters" do
+    test "binds keyword list by name", %{conn: conn} do
+      assert {:ok, %{rows: [[3, "a"]]}} =
+               @subject.query(conn, "SELECT $x + $y, $name", y: 2, name: "a", x: 1)
+    end
+
+    test "ignores names in literals and comments", %{conn: conn} do
+      query = "SELECT $b || '$a', $a, $b -- $c"
+
+      assert {:ok, %{rows: [["y$a", "x", "y"]]}} = @subject.query(conn, query, a: "x", b: "y")
+    end
+
+    test "binds names DuckDB numbers in other order than they appear", %{conn: conn} do
+      @subject.query!(conn, "CREATE TABLE t (id INTEGER, name TEXT)", [])
+      @subject.query!(conn, "INSERT INTO t VALUES (1, 'a'), (2, 'b')", [])
+
+      update = "UPDATE t SET name = $name FROM (SELECT $id AS id) s WHERE t.id = s.id"
+
+      assert {:ok, %{rows: [[1]]}} = @subject.query(conn, update, name: "z", id: 2)
+
+      assert {:ok, %{rows: [[1, "a"], [2, "z"]]}} =
+               @subject.query(conn, "SELECT * FROM t ORDER BY id", [])
+    end
+
+    test "rejects unknown and missing names", %{conn: conn} do
+      assert {:error, %Duckex.Error{kind: :invalid_input}} =
+               @subject.query(conn, "SELECT $x", x: 1, y: 2)
+
+      assert {:error, %Duckex.Error{kind: :arity}} =
+               @subject.query(conn, "SELECT $x + $y", x: 1)
+    end
+  end
+
+  describe "quoting" do
+    test "quoted values round trip through SQL", %{conn: conn} do
+      name = ~s(weird "name"; DROP TABLE x)
+      value = "it's -- fine"
+
+      table = @subject.quote_identifier(["main", name])
+
+      @subject.query!(conn, "CREATE TABLE #{@subject.quote_identifier(name)} (v TEXT)")
+      @subject.query!(conn, "INSERT INTO #{table} VALUES (#{@subject.escape_literal(value)})")
+
+      assert {:ok, %{rows: [[^value]]}} = @subject.query(conn, "SELECT v FROM #{table}")
+    end
+  end
+
+  describe "split_statements" do
+    test "splits on semicolons outside of quotes and comments" do
+      sql = """
+      CREATE TABLE "a;b" (v TEXT);
+      /* one; /* nested; */ still comment; */ INSERT INTO "a;b" VALUES ('x;''y');
+      SELECT $$dollar; quoted$$, $tag$ $$; $tag$, E'esc\\';'
+      ;;
+      SELECT $1
+      """
+
+      assert [create, insert, select, param] = @subject.split_statements(sql)
+      assert create == ~s(CREATE TABLE "a;b" (v TEXT))
+      assert insert =~ ~r/VALUES \('x;''y'\)$/
+      assert select == "SELECT $$dollar; quoted$$, $tag$ $$; $tag$, E'esc\\';'"
+      assert param == "SELECT $1"
+    end
+
+    test "returned statements can be run one by one", %{conn: conn} do
+      sql = "CREATE TABLE t (v TEXT); INSERT INTO t VALUES ('a;b'); SELECT v FROM t;"
+
+      results =
+        for statement <- @subject.split_statements(sql), do: @subject.query!(conn, statement)
+
+      assert %{rows: [["a;b"]]} = List.last(results)
+    end
+  end
+
+  describe "validate" do
+    test "accepts valid SQL referencing unknown tables" do
+      assert :ok = @subject.validate("SELECT a FROM missing; CREATE TABLE t (id INTEGER)")
+    end
+
+    test "reports syntax errors with position" do
+      sql = "SELECT 1;\nSELECT 2 FROM t;\nSELECT 3 FROM FROM t"
+
+      assert {:error, [%{statement: 2, message: message, position: 14}]} =
+               @subject.validate(sql)
+
+      assert message =~ "syntax error"
+    end
   end
 
   describe "transactions" do
@@ -184,6 +504,65 @@ defmodule DuckexNifTest do
       assert DateTime.compare(ts2, ts1) == :gt
     end
 
+    test "handles DateTime parameters before 1970 and on leap days", %{conn: conn} do
+      for dt <- [
+            ~U[1969-12-31 23:59:59.999999Z],
+            ~U[1900-01-01 00:00:00Z],
+            ~U[2024-02-29 12:00:00Z],
+            ~U[2000-02-29 00:00:00Z]
+          ] do
+        assert {:ok, %{rows: [[ts]]}} = @subject.query(conn, "SELECT ?::TIMESTAMP", [dt])
+        assert DateTime.compare(ts, dt) == :eq
+      end
+    end
+
+    test "handles DateTime parameters with time zone offset", %{conn: conn} do
+      dt = %{~U[2025-01-01 12:00:00Z] | utc_offset: 3600, std_offset: 0, zone_abbr: "CET"}
+
+      assert {:ok, %{rows: [[ts]]}} = @subject.query(conn, "SELECT ?::TIMESTAMP", [dt])
+      assert ts == ~U[2025-01-01 11:00:00.000000Z]
+    end
+
+    test "decodes timestamps of other precisions", %{conn: conn} do
+      sql =
+        "SELECT '2025-01-01 12:00:00.123456789'::TIMESTAMP_NS, '2025-01-01 12:00:01'::TIMESTAMP_S"
+
+      assert {:ok, %{rows: [[ns, s]]}} = @subject.query(conn, sql)
+      assert ns == ~U[2025-01-01 12:00:00.123456Z]
+      assert s == ~U[2025-01-01 12:00:01Z]
+
+      assert {:ok, %{rows: [[ns, _]]}} = @subject.query(conn, sql, [], timestamp_ns: :integer)
+      assert ns == DateTime.to_unix(~U[2025-01-01 12:00:00Z], :nanosecond) + 123_456_789
+    end
+
+    test "handles Date parameters before 1970 and on leap days", %{conn: conn} do
+      for date <- [~D[1969-12-31], ~D[1900-01-01], ~D[2024-02-29]] do
+        assert {:ok, %{rows: [[true]]}} =
+                 @subject.query(conn, "SELECT ?::DATE = ?::DATE", [date, Date.to_iso8601(date)])
+      end
+    end
+
+    test "rejects out of range date fields", %{conn: conn} do
+      date = %{~D[2025-01-01] | month: 13}
+
+      assert {:error, %Duckex.Error{kind: :invalid_date, message: message}} =
+               @subject.query(conn, "SELECT ?::DATE", [date])
+
+      assert message =~ "month"
+
+      assert {:error, %Duckex.Error{kind: :invalid_date, message: message}} =
+               @subject.query(conn, "SELECT ?::DATE", [%{~D[2025-02-01] | day: 29}])
+
+      assert message =~ "day"
+
+      dt = %{~U[2025-01-01 00:00:00Z] | hour: 24}
+
+      assert {:error, %Duckex.Error{kind: :invalid_date, message: message}} =
+               @subject.query(conn, "SELECT ?::TIMESTAMP", [dt])
+
+      assert message =~ "hour"
+    end
+
     test "handles Date parameters", %{conn: conn} do
       @subject.query!(conn, "CREATE TABLE test (d DATE)", [])
 
@@ -250,17 +629,42 @@ defmodule DuckexNifTest do
   end
 
   describe "cache behavior" do
-    test "cache exhaustion returns error", %{conn: conn} do
-      @subject.query!(conn, "CREATE TABLE test (val INTEGER)", [])
+    test "full cache evicts least recently used statement", %{conn: conn} do
+      {:ok, first} = @subject.prepare(conn, "SELECT 0")
+      {:ok, second} = @subject.prepare(conn, "SELECT 1")
+
+      # Fill the rest of the cache (1024 statements), then use the first one.
+      # Statements are kept referenced, so they are not removed on GC
+      rest =
+        for i <- 2..1023 do
+          {:ok, q} = @subject.prepare(conn, "SELECT #{i}")
+          q
+        end
+
+      assert {:ok, _, _} = @subject.execute(conn, first, [])
+
+      # Cache is now full - next prepare evicts the second statement
+      assert {:ok, _} = @subject.prepare(conn, "SELECT 1024")
+      assert {:ok, _, %{rows: [[0]]}} = @subject.execute(conn, first, [])
+      assert {:error, %Duckex.Error{}} = @subject.execute(conn, second, [])
+      assert {:ok, %{evictions: 1}} = @subject.cache_stats(conn)
+      assert length(rest) == 1022
+    end
 
-      # Prepare 1024 statements (cache size)
-      for i <- 1..1024 do
-        {:ok, _q} = @subject.prepare(conn, "SELECT #{i}")
-      end
+    test "garbage collected statements are removed from cache" do
+      {:ok, resource} = Duckex.Native.new(":memory:", nil, [])
+
+      {pid, ref} =
+        spawn_monitor(fn ->
+          {:ok, %{rows: [[_stmt]]}} = Duckex.Native.prepare(resource, "SELECT 1")
+        end)
 
-      # Cache is now full - next prepare should return error
-      assert {:error, %Duckex.Error{message: message}} = @subject.prepare(conn, "SELECT 1025")
-      assert message =~ "Exhausted prepared statements cache"
+      assert_receive {:DOWN, ^ref, :process, ^pid, :normal}
+
+      # Give the destructor time to run
+      Process.sleep(100)
+
+      assert {:ok, %{size: 0}} = Duckex.Native.cache_stats(resource)
     end
 
     test "closed statements can be reused", %{conn: conn} do
@@ -306,4 +710,1446 @@ defmodule DuckexNifTest do
       assert {:ok, _q, %{rows: [[2]]}} = @subject.execute(conn, q2, [2])
     end
   end
+
+  describe "execute_batch" do
+    test "runs multiple statements in one call", %{conn: conn} do
+      assert :ok =
+               @subject.execute_batch(conn, """
+               CREATE TABLE test (val INTEGER);
+               INSERT INTO test VALUES (1);
+               INSERT INTO test VALUES (2);
+               """)
+
+      assert {:ok, %{rows: [[1], [2]]}} =
+               @subject.query(conn, "SELECT * FROM test ORDER BY val", [])
+    end
+
+    test "applies nothing when one of the statements fails", %{conn: conn} do
+      @subject.query!(conn, "CREATE TABLE test (val INTEGER)", [])
+
+      assert {:error, %Duckex.Error{}} =
+               @subject.execute_batch(conn, """
+               INSERT INTO test VALUES (1);
+               INSERT INTO test VALUES ('not a number');
+               """)
+
+      assert {:ok, %{rows: []}} = @subject.query(conn, "SELECT * FROM test", [])
+    end
+  end
+
+  describe "query_arrow" do
+    test "returns record batches as Arrow IPC streams", %{conn: conn} do
+      assert {:ok, [batch | _]} =
+               @subject.query_arrow(conn, "SELECT i FROM range(?) t(i)", [3000])
+
+      # Every batch is a complete IPC stream beginning with continuation marker
+      assert <<0xFF, 0xFF, 0xFF, 0xFF, _::binary>> = batch
+    end
+
+    test "returns error for invalid SQL", %{conn: conn} do
+      assert {:error, %Duckex.Error{}} = @subject.query_arrow(conn, "INVALID SQL", [])
+    end
+  end
+
+  describe "ingest" do
+    test "loads CSV written in chunks not aligned with rows", %{conn: conn} do
+      @subject.query!(conn, "CREATE TABLE events (id INTEGER, name TEXT)", [])
+
+      assert {:ok, ingest} = @subject.ingest(conn, :events, :csv, header: true)
+      assert :ok = @subject.ingest_write(ingest, "id,name\n1,a")
+      assert :ok = @subject.ingest_write(ingest, ["bc\n", "2,", "def\n"])
+      assert {:ok, 2} = @subject.ingest_finish(ingest)
+
+      assert {:ok, %{rows: [[1, "abc"], [2, "def"]]}} =
+               @subject.query(conn, "SELECT * FROM events ORDER BY id", [])
+
+      assert {:error, %Duckex.Error{kind: :closed}} = @subject.ingest_write(ingest, "3,x\n")
+    end
+
+    test "loads quoted CSV fields split across chunks", %{conn: conn} do
+      @subject.query!(conn, "CREATE TABLE events (id INTEGER, name TEXT)", [])
+
+      assert {:ok, ingest} = @subject.ingest(conn, :events, :csv)
+      assert :ok = @subject.ingest_write(ingest, "1,\"a,\n")
+      assert :ok = @subject.ingest_write(ingest, "\"\"b\"\"\"\r\n2,\n3,\"\"")
+      assert {:ok, 3} = @subject.ingest_finish(ingest)
+
+      assert {:ok, %{rows: [[1, "a,\n\"b\""], [2, nil], [3, ""]]}} =
+               @subject.query(conn, "SELECT * FROM events ORDER BY id", [])
+    end
+
+    test "loads gzipped newline-delimited JSON", %{conn: conn} do
+      @subject.query!(conn, "CREATE TABLE events (id INTEGER, tags TEXT[], at DATE)", [])
+
+      data =
+        :zlib.gzip(~s({"id": 1, "tags": ["a"], "at": "2024-01-02"}\n{"id": 2, "extra": true}\n))
+
+      <<first::binary-size(10), rest::binary>> = data
+
+      assert {:ok, ingest} = @subject.ingest(conn, :events, :json)
+      assert :ok = @subject.ingest_write(ingest, first)
+      assert :ok = @subject.ingest_write(ingest, rest)
+      assert {:ok, 2} = @subject.ingest_finish(ingest)
+
+      assert {:ok, %{rows: [[1, ["a"], ~D[2024-01-02]], [2, nil, nil]]}} =
+               @subject.query(conn, "SELECT * FROM events ORDER BY id", [])
+    end
+  end
+
+  describe "blob encoding" do
+    test "returns blobs in the requested encoding", %{conn: conn} do
+      query = "SELECT '\\x00\\xAB\\xFF'::BLOB AS b"
+
+      assert {:ok, %{rows: [["AKv/"]]}} = @subject.query(conn, query, [])
+      assert {:ok, %{rows: [[<<0, 0xAB, 0xFF>>]]}} = @subject.query(conn, query, [], blob: :raw)
+      assert {:ok, %{rows: [["00abff"]]}} = @subject.query(conn, query, [], blob: :hex)
+    end
+
+    test "applies to nested blobs", %{conn: conn} do
+      query = "SELECT ['\\x01'::BLOB, NULL] AS b"
+
+      assert {:ok, %{rows: [[["01", nil]]]}} = @subject.query(conn, query, [], blob: :hex)
+    end
+  end
+
+  describe "blob" do
+    test "reads blob in chunks", %{conn: conn} do
+      assert {:ok, %{handle: handle, size: 10} = blob} =
+               @subject.open_blob(conn, "SELECT ?::BLOB", ["0123456789"])
+
+      assert {:ok, "234"} = @subject.read_blob(handle, 2, 3)
+      assert {:ok, "89"} = @subject.read_blob(handle, 8, 100)
+      assert {:ok, ""} = @subject.read_blob(handle, 20, 5)
+
+      assert ["0123", "4567", "89"] = blob |> @subject.stream_blob(4) |> Enum.to_list()
+    end
+
+    test "requires single value", %{conn: conn} do
+      assert {:error, %Duckex.Error{kind: :invalid_input}} =
+               @subject.open_blob(conn, "SELECT 1::BLOB WHERE false")
+    end
+  end
+
+  describe "appender" do
+    test "appends chunks, flushing on threshold", %{conn: conn} do
+      @subject.query!(conn, "CREATE TABLE test (id INTEGER, name TEXT)", [])
+
+      assert {:ok, appender} = @subject.appender(conn, "test", flush_threshold: 3)
+
+      assert {:ok, 0} = @subject.append_chunk(appender, [[1, "a"], [2, "b"]])
+      assert {:ok, 3} = @subject.append_chunk(appender, [[3, "c"]])
+      assert {:ok, 3} = @subject.append_chunk(appender, [[4, nil]])
+      assert {:ok, 4} = @subject.close_appender(appender)
+
+      assert {:ok, %{rows: [[4, nil]]}} =
+               @subject.query(conn, "SELECT * FROM test WHERE id = 4", [])
+
+      assert {:error, %Duckex.Error{kind: :closed}} = @subject.append_chunk(appender, [[5, "e"]])
+    end
+
+    test "appends stream", %{conn: conn} do
+      @subject.query!(conn, "CREATE TABLE test (id INTEGER)", [])
+
+      stream = Stream.map(1..10_000, &[&1])
+
+      assert {:ok, 10_000} = @subject.append_stream(conn, "test", stream, chunk_size: 500)
+
+      assert {:ok, %{rows: [[50_005_000]]}} =
+               @subject.query(conn, "SELECT sum(id)::BIGINT FROM test", [])
+    end
+
+    test "fails for missing table", %{conn: conn} do
+      assert {:error, %Duckex.Error{}} = @subject.appender(conn, "non_existent_table")
+    end
+  end
+
+  describe "register_rows" do
+    test "loads maps and tuples into table", %{conn: conn} do
+      rows = [%{id: 1, name: "Alice"}, %{"id" => 2}, {3, "Carol"}, [4, "Dave"]]
+
+      assert {:ok, 4} =
+               @subject.register_rows(conn, "people", [id: :integer, name: :varchar], rows)
+
+      assert {:ok, %{rows: [[1, "Alice"], [2, nil], [3, "Carol"], [4, "Dave"]]}} =
+               @subject.query(conn, "SELECT * FROM people ORDER BY id", [])
+    end
+
+    test "rejects invalid rows and types", %{conn: conn} do
+      assert {:error, %Duckex.Error{kind: :invalid_input}} =
+               @subject.register_rows(conn, "t", [id: :integer], [{1, 2}])
+
+      assert {:error, %Duckex.Error{kind: :invalid_input}} =
+               @subject.register_rows(conn, "t", [id: "INTEGER; DROP TABLE x"], [])
+    end
+  end
+
+  describe "register_table" do
+    test "loads rows from enumerable", %{conn: conn} do
+      table = :ets.new(:source, [])
+      :ets.insert(table, for(i <- 1..2500, do: {i, "item #{i}"}))
+
+      columns = [id: :integer, name: :varchar]
+
+      assert {:ok, 2500} = @subject.register_table(conn, :items, columns, :ets.tab2list(table))
+
+      assert {:ok, %{rows: [[2500]]}} = @subject.query(conn, "SELECT count(*) FROM items", [])
+    end
+
+    test "creates table visible to other connections", %{conn: conn} do
+      assert {:ok, 1} = @subject.register_table(conn, "shared", [id: :integer], [[1]])
+
+      assert {:ok, %{rows: [[false]]}} =
+               @subject.query(
+                 conn,
+                 "SELECT temporary FROM duckdb_tables() WHERE table_name = 'shared'",
+                 []
+               )
+    end
+
+    test "waits for producer batch up to the timeout", %{conn: conn} do
+      producer = spawn_link(fn -> Process.sleep(:infinity) end)
+
+      assert {:timeout, _} =
+               catch_exit(
+                 @subject.register_table(conn, "slow", [n: :integer], producer, timeout: 50)
+               )
+    end
+
+    test "looks up map rows by atom and string keys", %{conn: conn} do
+      columns = [{"id", :integer}, {:name, :varchar}]
+      rows = [%{id: 1, name: "a"}, %{"id" => 2, "name" => "b"}]
+
+      assert {:ok, 2} = @subject.register_table(conn, "keyed", columns, rows)
+
+      assert {:ok, %{rows: [[1, "a"], [2, "b"]]}} =
+               @subject.query(conn, "SELECT id, name FROM keyed ORDER BY id", [])
+    end
+
+    test "pulls batches from producer process", %{conn: conn} do
+      producer =
+        spawn_link(fn ->
+          loop = fn loop, batches ->
+            receive do
+              {:"$gen_call", from, {:duckex_fetch, 2}} ->
+                case batches do
+                  [batch | rest] ->
+                    GenServer.reply(from, {:rows, batch})
+                    loop.(loop, rest)
+
+                  [] ->
+                    GenServer.reply(from, :done)
+                end
+            end
+          end
+
+          loop.(loop, [[[1], [2]], [[3]]])
+        end)
+
+      assert {:ok, 3} =
+               @subject.register_table(conn, "numbers", [n: :integer], producer, batch_size: 2)
+
+      assert {:ok, %{rows: [[6]]}} =
+               @subject.query(conn, "SELECT sum(n)::INTEGER FROM numbers", [])
+    end
+  end
+
+  describe "execute_to_file" do
+    @tag :tmp_dir
+    test "writes CSV with bound parameters", %{conn: conn, tmp_dir: tmp_dir} do
+      path = Path.join(tmp_dir, "out.csv")
+
+      assert {:ok, %{path: ^path, size: 8, rows: 3}} =
+               @subject.execute_to_file(conn, "SELECT i FROM range(?) t(i)", [3], path,
+                 header: true
+               )
+
+      assert File.read!(path) == "i\n0\n1\n2\n"
+    end
+
+    @tag :tmp_dir
+    test "writes Arrow IPC file", %{conn: conn, tmp_dir: tmp_dir} do
+      path = Path.join(tmp_dir, "out.arrow")
+
+      assert {:ok, %{rows: 2, size: size}} =
+               @subject.execute_to_file(conn, "SELECT 1 AS a UNION ALL SELECT 2", [], path)
+
+      assert <<"ARROW1", _::binary>> = data = File.read!(path)
+      assert byte_size(data) == size
+    end
+
+    @tag :tmp_dir
+    test "writes compressed Arrow IPC file", %{conn: conn, tmp_dir: tmp_dir} do
+      path = Path.join(tmp_dir, "out.feather")
+      query = "SELECT i, 'text' AS t FROM range(10000) t(i)"
+
+      assert {:ok, %{rows: 10_000, size: plain}} =
+               @subject.export_arrow(conn, query, [], path)
+
+      assert {:ok, %{rows: 10_000, size: compressed}} =
+               @subject.export_arrow(conn, query, [], path, compression: :zstd)
+
+      assert compressed < plain
+    end
+
+    test "rejects CSV options for Arrow", %{conn: conn} do
+      assert {:error, %Duckex.Error{message: "Option :header is not supported for Arrow"}} =
+               @subject.execute_to_file(conn, "SELECT 1", [], "out.arrow", header: true)
+    end
+  end
+
+  describe "copy_to" do
+    @tag :tmp_dir
+    test "exports query result to CSV", %{conn: conn, tmp_dir: tmp_dir} do
+      path = Path.join(tmp_dir, "out.csv")
+
+      assert {:ok, 3} =
+               @subject.copy_to(conn, "SELECT i FROM range(3) t(i)", path, :csv,
+                 delimiter: ";",
+                 header: true
+               )
+
+      assert File.read!(path) == "i\n0\n1\n2\n"
+    end
+
+    @tag :tmp_dir
+    test "exports query result to Parquet", %{conn: conn, tmp_dir: tmp_dir} do
+      path = Path.join(tmp_dir, "out.parquet")
+
+      assert {:ok, 2} =
+               @subject.copy_to(conn, "SELECT 1 AS a UNION ALL SELECT 2", path, :parquet,
+                 compression: :zstd
+               )
+
+      assert {:ok, %{rows: [[2]]}} =
+               @subject.query(conn, "SELECT count(*)::INTEGER FROM read_parquet(?)", [path])
+    end
+
+    test "rejects CSV only options for other formats", %{conn: conn} do
+      assert {:error, %Duckex.Error{message: message}} =
+               @subject.copy_to(conn, "SELECT 1", "out.json", :json, delimiter: ",")
+
+      assert message =~ "only supported for CSV"
+    end
+  end
+
+  describe "export_parquet" do
+    @tag :tmp_dir
+    test "returns Parquet file contents", %{conn: conn, tmp_dir: tmp_dir} do
+      assert {:ok, "PAR1" <> _ = parquet} =
+               @subject.export_parquet(conn, "SELECT i FROM range(10) t(i)", compression: :zstd)
+
+      path = Path.join(tmp_dir, "out.parquet")
+      File.write!(path, parquet)
+
+      assert {:ok, %{rows: [[10]]}} =
+               @subject.query(conn, "SELECT count(*)::INTEGER FROM read_parquet(?)", [path])
+    end
+  end
+
+  describe "export_database" do
+    @tag :tmp_dir
+    test "exports and imports whole database", %{conn: conn, tmp_dir: tmp_dir} do
+      @subject.query!(conn, "CREATE TABLE test AS SELECT * FROM range(100) t(val)", [])
+
+      assert :ok = @subject.export_database(conn, tmp_dir, compression: :zstd)
+      assert File.exists?(Path.join(tmp_dir, "schema.sql"))
+
+      other = start_supervised!({@subject, attach: []}, id: :other)
+
+      assert :ok = @subject.import_database(other, tmp_dir)
+      assert {:ok, %{rows: [[100]]}} = @subject.query(other, "SELECT count(*) FROM test", [])
+    end
+  end
+
+  describe "macros" do
+    test "creates and drops scalar macro", %{conn: conn} do
+      assert :ok = @subject.create_macro(conn, :normalize_email, [:email], "lower(trim(email))")
+
+      assert {:ok, %{rows: [["bob@example.com"]]}} =
+               @subject.query(conn, "SELECT normalize_email(?)", ["  Bob@Example.COM "])
+
+      assert :ok = @subject.drop_macro(conn, :normalize_email)
+
+      assert {:error, %Duckex.Error{kind: :catalog}} =
+               @subject.query(conn, "SELECT normalize_email('x')", [])
+    end
+  end
+
+  describe "create_fts_index" do
+    test "requires indexed columns", %{conn: conn} do
+      assert {:error, %Duckex.Error{message: "At least one column has to be indexed"}} =
+               @subject.create_fts_index(conn, :documents, :id, [])
+    end
+  end
+
+  describe "extensions" do
+    test "loads built-in extension", %{conn: conn} do
+      assert :ok = @subject.load_extension(conn, :parquet)
+    end
+
+    test "classifies missing extension", %{conn: conn} do
+      assert {:error, %Duckex.Error{kind: kind}} =
+               @subject.load_extension(conn, "/nonexistent/foo.duckdb_extension")
+
+      assert kind in [:not_found, :extension]
+    end
+  end
+
+  describe "geometry" do
+    test "leaves plain blobs untouched", %{conn: conn} do
+      assert {:ok, %{rows: [["AQI="]]}} =
+               @subject.query(conn, "SELECT '\\x01\\x02'::BLOB", [], geometry: :wkt)
+    end
+  end
+
+  describe "attach_iceberg" do
+    test "rejects unknown options before touching the network", %{conn: conn} do
+      assert {:error, %Duckex.Error{message: "Unsupported Iceberg option :foo"}} =
+               @subject.attach_iceberg(conn, "warehouse", foo: "bar")
+    end
+  end
+
+  describe "create_s3_secret" do
+    test "creates secret without exposing credentials", %{conn: conn} do
+      assert :ok =
+               @subject.create_s3_secret(conn, :my_s3,
+                 key_id: "AKIA'EXAMPLE",
+                 secret: "top-secret",
+                 region: "eu-west-1",
+                 url_style: :path
+               )
+
+      assert {:ok, %{rows: [["my_s3", "s3"]]}} =
+               @subject.query(conn, "SELECT name, type FROM duckdb_secrets()", [])
+    end
+  end
+
+  describe "create_gcs_secret" do
+    test "creates secret with HMAC keys", %{conn: conn} do
+      assert :ok = @subject.create_gcs_secret(conn, :my_gcs, key_id: "GOOG1", secret: "hmac")
+
+      assert {:ok, %{rows: [["my_gcs", "gcs"]]}} =
+               @subject.query(conn, "SELECT name, type FROM duckdb_secrets()", [])
+    end
+
+    test "rejects invalid provider", %{conn: conn} do
+      assert {:error, %Duckex.Error{message: "Invalid value for option :provider"}} =
+               @subject.create_gcs_secret(conn, :my_gcs, provider: "x; DROP")
+    end
+  end
+
+  describe "set_http_options" do
+    test "validates options before setting anything", %{conn: conn} do
+      assert {:error, %Duckex.Error{kind: :invalid_input}} =
+               @subject.set_http_options(conn, timeout: 30, proxy_port: 3128)
+
+      assert {:error, %Duckex.Error{message: "Invalid value for option :retries"}} =
+               @subject.set_http_options(conn, retries: "three")
+    end
+  end
+
+  describe "settings" do
+    test "sets and returns typed values", %{conn: conn} do
+      assert :ok = @subject.set_setting(conn, :threads, 2)
+      assert {:ok, 2} = @subject.get_setting(conn, :threads)
+
+      assert :ok = @subject.set_setting(conn, :preserve_insertion_order, false)
+      assert {:ok, false} = @subject.get_setting(conn, "preserve_insertion_order")
+
+      assert :ok = @subject.set_setting(conn, :memory_limit, "1GB")
+      assert {:ok, limit} = @subject.get_setting(conn, :memory_limit)
+      assert is_binary(limit)
+    end
+
+    test "rejects unknown settings", %{conn: conn} do
+      assert {:error, %Duckex.Error{kind: :not_found}} =
+               @subject.get_setting(conn, :no_such_setting)
+
+      assert {:error, %Duckex.Error{kind: :invalid_input}} =
+               @subject.set_setting(conn, "threads; DROP TABLE x", 1)
+    end
+  end
+
+  describe "profiling" do
+    test "returns JSON profile of the last query", %{conn: conn} do
+      assert :ok = @subject.enable_profiling(conn)
+
+      @subject.query!(conn, "SELECT sum(i) FROM range(1000) t(i)", [])
+
+      assert {:ok, %{"children" => _}} = @subject.last_profile(conn)
+
+      assert :ok = @subject.enable_profiling(conn, false)
+      assert {:error, %Duckex.Error{}} = @subject.last_profile(conn)
+    end
+  end
+
+  describe "explain" do
+    test "returns plan as nested maps", %{conn: conn} do
+      @subject.query!(conn, "CREATE TABLE test (val INTEGER)", [])
+
+      assert {:ok, [%{"name" => _, "children" => children}]} =
+               @subject.explain(conn, "SELECT val FROM test WHERE val > ?", params: [1])
+
+      assert is_list(children)
+    end
+  end
+
+  describe "column types" do
+    test "returns structured type descriptors", %{conn: conn} do
+      sql = """
+      SELECT 1::INTEGER, 1.5::DECIMAL(18, 3), [1, 2]::BIGINT[], {'a': 1, 'b': 'x'},
+             now()::TIMESTAMP, 'a'::VARCHAR, MAP {'k': 1.0::DOUBLE}
+      """
+
+      assert {:ok, %{types: types}} = @subject.query(conn, sql)
+
+      assert [
+               :integer,
+               {:decimal, 18, 3},
+               {:list, :bigint},
+               {:struct, [{"a", :integer}, {"b", :varchar}]},
+               {:timestamp, :microsecond},
+               :varchar,
+               {:map, :varchar, :double}
+             ] = types
+    end
+  end
+
+  describe "statement kind" do
+    test "classifies statements", %{conn: conn} do
+      for {sql, kind} <- [
+            {"CREATE TABLE test (id INTEGER)", :ddl},
+            {"INSERT INTO test VALUES (1)", :insert},
+            {"WITH t AS (SELECT 2) INSERT INTO test SELECT * FROM t", :insert},
+            {"-- comment\nSELECT 1", :select},
+            {"/* x */ SELECT 1", :select},
+            {"UPDATE test SET id = 2", :update},
+            {"WITH t AS (SELECT 1) SELECT * FROM t", :select},
+            {"WITH t AS MATERIALIZED (SELECT 3) INSERT INTO test FROM t", :insert},
+            {"(SELECT 1)", :select},
+            {"COMMENT ON TABLE test IS 'numbers'", :ddl},
+            {"DELETE FROM test", :delete},
+            {"TRUNCATE test", :delete},
+            {"BEGIN", :transaction},
+            {"COMMIT", :transaction},
+            {"SET threads = 2", :other}
+          ] do
+        assert {:ok, %{kind: ^kind}} = @subject.query(conn, sql)
+      end
+    end
+
+    test "returns changed rows and their count", %{conn: conn} do
+      @subject.query!(conn, "CREATE TABLE test (id INTEGER PRIMARY KEY, val TEXT)")
+
+      assert {:ok, %{rows: [[3]], rows_affected: 3}} =
+               @subject.query(conn, "INSERT INTO test VALUES (1, 'a'), (2, 'b'), (3, 'c')")
+
+      sql = "UPDATE test SET val = upper(val) WHERE id < 3 RETURNING id, val"
+
+      assert {:ok, %{rows: rows, rows_affected: 2}} = @subject.query(conn, sql)
+      assert Enum.sort(rows) == [[1, "A"], [2, "B"]]
+
+      assert {:ok, %{rows: [], rows_affected: 0}} =
+               @subject.query(conn, "DELETE FROM test WHERE id > 10 RETURNING id")
+
+      assert {:ok, %{rows_affected: nil}} = @subject.query(conn, "SELECT * FROM test")
+    end
+
+    test "counts changed rows when a column is named like RETURNING", %{conn: conn} do
+      @subject.query!(conn, "CREATE TABLE test (id INTEGER, returning_count INTEGER)")
+      @subject.query!(conn, "INSERT INTO test VALUES (1, 0), (2, 0)")
+
+      sql = "UPDATE test SET returning_count = 1 WHERE 'returning' <> ''"
+
+      assert {:ok, %{rows: [[2]], rows_affected: 2}} = @subject.query(conn, sql)
+    end
+  end
+
+  describe "timing" do
+    test "reports queue and execution time", %{conn: conn} do
+      assert {:ok, %{queue_time_us: queue, execute_time_us: execute}} =
+               @subject.query(conn, "SELECT count(*) FROM range(1000000)")
+
+      assert is_integer(queue) and queue >= 0
+      assert is_integer(execute) and execute > 0
+    end
+  end
+
+  describe "set_log_handler" do
+    test "notifies handler about executed queries", %{conn: conn} do
+      assert :ok = @subject.set_log_handler(conn, self())
+
+      @subject.query!(conn, "SELECT * FROM range(3)")
+
+      assert_receive {:duckex_query, "SELECT * FROM range(3)", duration, 3}
+      assert is_integer(duration)
+
+      assert :ok = @subject.set_log_handler(conn, nil)
+
+      @subject.query!(conn, "SELECT 1")
+
+      refute_receive {:duckex_query, "SELECT 1", _, _}
+    end
+  end
+
+  describe "result limits" do
+    test "fails queries exceeding limits", %{conn: conn} do
+      assert {:error, %Duckex.Error{kind: :result_too_large}} =
+               @subject.query(conn, "SELECT * FROM range(1000)", [], max_rows: 100)
+
+      sql = "SELECT repeat('x', 1000) FROM range(10)"
+
+      assert {:error, %Duckex.Error{kind: :result_too_large}} =
+               @subject.query(conn, sql, [], max_result_bytes: 5_000)
+
+      assert {:ok, %{num_rows: 100}} =
+               @subject.query(conn, "SELECT * FROM range(100)", [], max_rows: 100)
+    end
+
+    test "uses connection limits by default" do
+      conn = start_supervised!({@subject, max_rows: 10}, id: :limited)
+
+      assert {:error, %Duckex.Error{kind: :result_too_large}} =
+               @subject.query(conn, "SELECT * FROM range(11)")
+
+      assert {:ok, %{num_rows: 11}} =
+               @subject.query(conn, "SELECT * FROM range(11)", [], max_rows: 20)
+    end
+  end
+
+  describe "named in-memory database" do
+    test "is shared by connections opened with the same name" do
+      first = start_supervised!({@subject, database: ":memory:shared"}, id: :first)
+      second = start_supervised!({@subject, database: ":memory:shared"}, id: :second)
+      other = start_supervised!({@subject, database: ":memory:other"}, id: :other)
+
+      @subject.query!(first, "CREATE TABLE shared AS SELECT 42 AS answer", [])
+
+      assert {:ok, %{rows: [[42]]}} = @subject.query(second, "SELECT answer FROM shared", [])
+
+      assert {:error, %Duckex.Error{kind: :catalog}} =
+               @subject.query(other, "SELECT answer FROM shared", [])
+    end
+  end
+
+  describe "after_connect" do
+    test "runs statements before the connection is available" do
+      conn =
+        start_supervised!(
+          {@subject,
+           after_connect: ["SET threads = 3", "CREATE TABLE setup AS SELECT 1 AS ready"]},
+          id: :after_connect
+        )
+
+      assert {:ok, 3} = @subject.get_setting(conn, :threads)
+      assert {:ok, %{rows: [[1]]}} = @subject.query(conn, "SELECT ready FROM setup", [])
+    end
+
+    test "fails to open on failing statement" do
+      assert {:error, %Duckex.Error{kind: :syntax}} =
+               Duckex.Native.new(":memory:", nil, after_connect: ["SELEC 1"])
+    end
+  end
+
+  describe "database URI" do
+    @tag :tmp_dir
+    test "opens database with settings from query string", %{tmp_dir: tmp_dir} do
+      path = Path.join(tmp_dir, "uri.duckdb")
+
+      conn =
+        start_supervised!(
+          {@subject, database: "duckdb://#{path}?threads=2&preserve_insertion_order=false"},
+          id: :uri
+        )
+
+      assert {:ok, %{database: ^path}} = @subject.status(conn)
+
+      assert {:ok, %{rows: [[2, false]]}} =
+               @subject.query(
+                 conn,
+                 "SELECT current_setting('threads'), current_setting('preserve_insertion_order')",
+                 []
+               )
+    end
+
+    @tag :tmp_dir
+    test "keeps plus sign in path", %{tmp_dir: tmp_dir} do
+      path = Path.join(tmp_dir, "a+b.duckdb")
+
+      conn =
+        start_supervised!(
+          {@subject, database: "duckdb://#{path}?threads=2"},
+          id: :uri_plus
+        )
+
+      assert {:ok, %{database: ^path}} = @subject.status(conn)
+      assert File.exists?(path)
+    end
+
+    test "fails on unknown setting" do
+      assert {:error, %Duckex.Error{}} = Duckex.Native.new("duckdb://?no_such_setting=1")
+    end
+  end
+
+  describe "extension options" do
+    test "configures unsigned extensions and custom repository" do
+      conn =
+        start_supervised!(
+          {@subject,
+           allow_unsigned_extensions: true,
+           custom_extension_repository: "https://extensions.example.com"},
+          id: :extensions
+        )
+
+      assert {:ok, %{rows: [[true, "https://extensions.example.com"]]}} =
+               @subject.query(
+                 conn,
+                 "SELECT current_setting('allow_unsigned_extensions'), " <>
+                   "current_setting('custom_extension_repository')",
+                 []
+               )
+    end
+
+    test "disables extension autoinstall and autoload" do
+      conn =
+        start_supervised!(
+          {@subject, autoinstall_known_extensions: false, autoload_known_extensions: false},
+          id: :autoload
+        )
+
+      assert {:ok, %{rows: [[false, false]]}} =
+               @subject.query(
+                 conn,
+                 "SELECT current_setting('autoinstall_known_extensions'), " <>
+                   "current_setting('autoload_known_extensions')",
+                 []
+               )
+    end
+
+    test "loads extensions at connect time" do
+      conn = start_supervised!({@subject, extensions: [:parquet]}, id: :preload)
+
+      assert {:ok, %{rows: [[true]]}} =
+               @subject.query(
+                 conn,
+                 "SELECT loaded FROM duckdb_extensions() WHERE extension_name = 'parquet'",
+                 []
+               )
+    end
+  end
+
+  describe "time zone" do
+    test "is set at open" do
+      conn = start_supervised!({@subject, timezone: "America/New_York"}, id: :timezone)
+
+      assert {:ok, "America/New_York"} = @subject.get_setting(conn, "TimeZone")
+    end
+
+    test "is changed with set_timezone", %{conn: conn} do
+      assert :ok = @subject.set_timezone(conn, "Asia/Tokyo")
+      assert {:ok, "Asia/Tokyo"} = @subject.get_setting(conn, "TimeZone")
+
+      assert {:error, %Duckex.Error{}} = @subject.set_timezone(conn, "Mars/Olympus_Mons")
+    end
+  end
+
+  describe "temp_usage" do
+    @tag :tmp_dir
+    test "reports configured temporary directory", %{tmp_dir: tmp_dir} do
+      conn =
+        start_supervised!(
+          {@subject, temp_directory: tmp_dir, max_temp_directory_size: 1_000_000_000},
+          id: :spill
+        )
+
+      assert {:ok, %{temp_directory: ^tmp_dir, files: files, size: size}} =
+               @subject.temp_usage(conn)
+
+      assert is_integer(files) and is_integer(size)
+    end
+  end
+
+  describe "memory_stats" do
+    test "reports buffer manager usage", %{conn: conn} do
+      {:ok, _} = @subject.query(conn, "CREATE TABLE mem AS SELECT range AS i FROM range(100000)")
+
+      assert {:ok, stats} = @subject.memory_stats(conn)
+      assert %{memory_usage: usage, peak_memory_usage: peak, memory_limit: limit} = stats
+      assert usage > 0 and peak >= usage
+      assert is_binary(limit)
+      assert stats.by_tag["BASE_TABLE"] > 0
+    end
+  end
+
+  describe "describe" do
+    test "returns columns without running the query", %{conn: conn} do
+      @subject.query!(conn, "CREATE TABLE test (id INTEGER, name TEXT)", [])
+
+      assert {:ok, [%{name: "id", type: "INTEGER"}, %{name: "label", type: "VARCHAR"}]} =
+               @subject.describe(conn, "SELECT id, upper(name) AS label FROM test")
+
+      assert {:error, %Duckex.Error{kind: :catalog}} =
+               @subject.describe(conn, "SELECT * FROM non_existent_table")
+    end
+
+    test "returns nullability and origin of columns", %{conn: conn} do
+      @subject.query!(conn, "CREATE TABLE users (id INTEGER NOT NULL, name TEXT)")
+
+      assert {:ok,
+              [
+                %{name: "id", nullable: false, table: "users", column: "id"},
+                %{name: "label", table: nil, column: nil}
+              ]} = @subject.describe(conn, "SELECT id, upper(name) AS label FROM users")
+
+      assert {:ok, [%{column: "id", nullable: false}, %{column: "name", nullable: true}]} =
+               @subject.describe(conn, "SELECT * FROM users")
+    end
+
+    test "adds nullability and origin to result columns", %{conn: conn} do
+      @subject.query!(conn, "CREATE TABLE users (id INTEGER NOT NULL, name TEXT)")
+      @subject.query!(conn, "INSERT INTO users VALUES (1, 'a')")
+
+      query = "SELECT id, upper(name) AS label FROM users WHERE id = ?"
+
+      assert {:ok, %{rows: [[1, "A"]], column_info: info}} =
+               @subject.query(conn, query, [1], column_info: true)
+
+      assert [
+               %{nullable: false, table: "users", column: "id"},
+               %{nullable: nil, table: nil, column: nil}
+             ] = info
+
+      assert {:ok, %{column_info: nil}} = @subject.query(conn, query, [1])
+    end
+  end
+
+  describe "statement_params" do
+    test "returns parameter count and types", %{conn: conn} do
+      @subject.query!(conn, "CREATE TABLE test (id INTEGER, name TEXT)", [])
+
+      {:ok, query} = @subject.prepare(conn, "INSERT INTO test VALUES (?, ?)")
+
+      assert {:ok, %{count: 2, types: ["INTEGER", "VARCHAR"]}} =
+               @subject.statement_params(conn, query)
+    end
+
+    test "returns nil type when it cannot be inferred", %{conn: conn} do
+      {:ok, query} = @subject.prepare(conn, "SELECT ?")
+
+      assert {:ok, %{count: 1, types: [nil]}} = @subject.statement_params(conn, query)
+    end
+  end
+
+  describe "status" do
+    test "reports idle connection", %{conn: conn} do
+      assert {:ok,
+              %{state: :idle, database: ":memory:", access_mode: _, error: nil, queued: 0}} =
+               @subject.status(conn)
+    end
+
+    test "reports busy connection" do
+      {:ok, resource} = Duckex.Native.new(":memory:")
+      {:ok, %{rows: [[stmt]]}} = Duckex.Native.prepare(resource, "SELECT count(*) FROM range(?)")
+
+      task =
+        Task.async(fn ->
+          Duckex.Native.execute(resource, stmt, [10_000_000_000], timeout: 1_000)
+        end)
+
+      # Give the task time to start the query
+      Process.sleep(100)
+
+      assert {:ok, %{state: :busy}} = @subject.status(resource)
+
+      Task.await(task)
+    end
+  end
+
+  describe "attached databases" do
+    @tag :tmp_dir
+    test "attaches, lists and detaches databases", %{conn: conn, tmp_dir: tmp_dir} do
+      path = Path.join(tmp_dir, "other.duckdb")
+
+      assert {:ok, %{name: "other", path: ^path, read_only: false}} =
+               @subject.attach(conn, path)
+
+      @subject.query!(conn, "CREATE TABLE other.test AS SELECT 1 AS val", [])
+
+      assert {:ok, [%{name: "memory", path: nil}, %{name: "other"}]} =
+               @subject.list_databases(conn)
+
+      assert :ok = @subject.detach(conn, :other)
+
+      assert {:ok, %{name: "ro", read_only: true}} =
+               @subject.attach(conn, path, as: "ro", options: [read_only: true])
+
+      assert {:ok, %{rows: [[1]]}} = @subject.query(conn, "SELECT val FROM ro.test", [])
+
+      assert :ok = @subject.detach(conn, "ro")
+      assert {:ok, [%{name: "memory"}]} = @subject.list_databases(conn)
+    end
+  end
+
+  describe "database_size" do
+    @tag :tmp_dir
+    test "reports storage statistics in bytes", %{tmp_dir: tmp_dir} do
+      {:ok, conn} = @subject.start_link(database: Path.join(tmp_dir, "size.duckdb"))
+
+      @subject.query!(conn, "CREATE TABLE test AS SELECT * FROM range(100000)", [])
+      :ok = @subject.checkpoint(conn)
+
+      assert {:ok, [%{name: "size"} = size]} = @subject.database_size(conn)
+      assert size.block_size > 0
+      assert size.used_blocks > 0
+      assert size.total_blocks == size.used_blocks + size.free_blocks
+      assert size.size == size.total_blocks * size.block_size
+      assert size.wal_size == nil
+    end
+  end
+
+  describe "checkpoint" do
+    @tag :tmp_dir
+    test "flushes WAL into the database file", %{tmp_dir: tmp_dir} do
+      {:ok, conn} = @subject.start_link(database: Path.join(tmp_dir, "test.duckdb"))
+
+      @subject.query!(conn, "CREATE TABLE test AS SELECT * FROM range(1000)", [])
+
+      assert :ok = @subject.checkpoint(conn)
+      assert :ok = @subject.checkpoint(conn, force: true)
+      refute File.exists?(Path.join(tmp_dir, "test.duckdb.wal"))
+    end
+  end
+
+  describe "disconnect" do
+    @tag :tmp_dir
+    test "closes connection and releases the database file", %{tmp_dir: tmp_dir} do
+      path = Path.join(tmp_dir, "test.duckdb")
+
+      {:ok, resource} = Duckex.Native.new(path)
+      {:ok, _} = Duckex.Native.begin(resource)
+      {:ok, %{rows: [[stmt]]}} = Duckex.Native.prepare(resource, "SELECT 1")
+
+      assert {:ok, _} = Duckex.Native.disconnect(resource)
+
+      assert {:error, %Duckex.Error{kind: :closed}} = Duckex.Native.execute(resource, stmt, [])
+      assert {:ok, %{state: :closed}} = Duckex.Native.status(resource)
+
+      # File lock is released, so database can be opened again
+      assert {:ok, _} = Duckex.Native.new(path)
+    end
+  end
+
+  describe "version" do
+    test "returns bundled DuckDB version" do
+      assert {:ok, %{library_version: "v" <> _, source_id: source_id}} = @subject.version()
+      assert is_binary(source_id)
+    end
+  end
+
+  describe "statement cache" do
+    test "reports usage", %{conn: conn} do
+      query = @subject.prepare!(conn, "SELECT 1")
+      {:ok, _} = @subject.execute(conn, query, [])
+
+      assert {:ok, %{size: size, capacity: capacity, hits: hits}} = @subject.cache_stats(conn)
+      assert size >= 1
+      assert capacity >= size
+      assert hits >= 1
+    end
+
+    test "clears cached statements", %{conn: conn} do
+      query = @subject.prepare!(conn, "SELECT 1")
+
+      assert :ok = @subject.cache_clear(conn)
+      assert {:ok, %{size: 0}} = @subject.cache_stats(conn)
+      assert {:error, %Duckex.Error{}} = @subject.execute(conn, query, [])
+    end
+
+    test "resizes", %{conn: conn} do
+      first = for i <- 1..3, do: @subject.prepare!(conn, "SELECT #{i}")
+      last = @subject.prepare!(conn, "SELECT 4")
+
+      assert :ok = @subject.cache_resize(conn, 2)
+      assert {:ok, %{size: 2, capacity: 2}} = @subject.cache_stats(conn)
+      assert {:ok, _, %{rows: [[4]]}} = @subject.execute(conn, last, [])
+      assert length(first) == 3
+    end
+  end
+
+  describe "progress" do
+    test "reports idle connection", %{conn: conn} do
+      assert {:ok, resource} = @subject.resource(conn)
+
+      assert %{running: false, query: nil, percentage: nil, rows_processed: 0} =
+               @subject.progress(resource)
+    end
+
+    test "reports percentage of running query", %{conn: conn} do
+      assert {:ok, resource} = @subject.resource(conn)
+
+      query = "SELECT count(*) FROM range(1000000000) a WHERE a.range % 7 = 3"
+      task = Task.async(fn -> @subject.query(conn, query, [], timeout: 60_000) end)
+
+      progress =
+        Stream.repeatedly(fn ->
+          Process.sleep(20)
+          @subject.progress(resource)
+        end)
+        |> Stream.take(500)
+        |> Enum.find(&(&1.running and is_float(&1.percentage)))
+
+      assert %{query: ^query, percentage: percentage, total_rows: total} = progress
+      assert percentage >= 0.0 and percentage <= 100.0
+      assert total > 0
+
+      Task.await(task, 60_000)
+    end
+  end
+
+  describe "json format" do
+    test "serializes rows as array of objects", %{conn: conn} do
+      query = """
+      SELECT 1 AS id, 'a"b' AS name, DATE '2024-01-02' AS day, NULL AS missing,
+        [1.5, 2.0]::DOUBLE[] AS list, {'x': 1} AS nested
+      """
+
+      assert {:ok, %{rows: json, num_rows: 1}} = @subject.query(conn, query, [], format: :json)
+
+      assert json ==
+               ~s([{"id":1,"name":"a\\"b","day":"2024-01-02","missing":null,) <>
+                 ~s("list":[1.5,2.0],"nested":{"x":1}}])
+    end
+
+    test "serializes empty result", %{conn: conn} do
+      assert {:ok, %{rows: "[]", num_rows: 0}} =
+               @subject.query(conn, "SELECT 1 AS a WHERE false", [], format: :json)
+    end
+  end
+
+  describe "csv format" do
+    test "serializes rows with header", %{conn: conn} do
+      query = """
+      SELECT 1 AS id, 'a,"b"' AS name, NULL AS missing, [1, 2] AS list
+      UNION ALL SELECT 2, 'c', 'x', []
+      ORDER BY id
+      """
+
+      assert {:ok, %{rows: csv, num_rows: 2}} = @subject.query(conn, query, [], format: :csv)
+
+      assert csv ==
+               "id,name,missing,list\r\n" <>
+                 ~s(1,"a,""b""",,"[1,2]"\r\n) <>
+                 "2,c,x,[]\r\n"
+    end
+
+    test "uses custom delimiter and quote", %{conn: conn} do
+      assert {:ok, %{rows: "a;b\r\n'x;y';z\r\n"}} =
+               @subject.query(conn, "SELECT 'x;y' AS a, 'z' AS b", [],
+                 format: :csv,
+                 delimiter: ";",
+                 quote: "'"
+               )
+    end
+  end
+
+  describe "columnar layout" do
+    test "returns list per column", %{conn: conn} do
+      @subject.query!(conn, "CREATE TABLE test (id INTEGER, name TEXT)", [])
+      @subject.query!(conn, "INSERT INTO test VALUES (1, 'a'), (2, NULL)", [])
+
+      assert {:ok, %{rows: [[1, 2], ["a", nil]], num_rows: 2, layout: :columnar}} =
+               @subject.query(conn, "SELECT * FROM test ORDER BY id", [], layout: :columnar)
+    end
+
+    test "returns empty columns for empty result", %{conn: conn} do
+      assert {:ok, %{rows: [[], []], num_rows: 0}} =
+               @subject.query(conn, "SELECT 1 AS a, 2 AS b WHERE false", [], layout: :columnar)
+    end
+  end
+
+  describe "chunked encoding" do
+    test "returns all rows of result larger than chunk", %{conn: conn} do
+      query = "SELECT i, i::TEXT FROM range(25) t(i) ORDER BY i"
+
+      assert {:ok, %{rows: rows, num_rows: 25}} = @subject.query(conn, query, [], chunk_size: 10)
+      assert rows == for(i <- 0..24, do: [i, to_string(i)])
+
+      assert {:ok, %{rows: [ids, _], num_rows: 25}} =
+               @subject.query(conn, query, [], chunk_size: 10, layout: :columnar)
+
+      assert ids == Enum.to_list(0..24)
+    end
+  end
+
+  describe "lazy results" do
+    test "fetches rows on demand", %{conn: conn} do
+      query = "SELECT i FROM range(5) t(i) ORDER BY i"
+
+      assert {:ok, %{rows: [], num_rows: 5} = result} =
+               @subject.query(conn, query, [], lazy: true)
+
+      assert {:ok, %{rows: [[0], [1]]}, :more} = @subject.fetch(result, 2)
+      assert {:ok, %{rows: [[2], [3]]}, :more} = @subject.fetch(result, 2)
+      assert {:ok, %{rows: [[4]]}, :done} = @subject.fetch(result, 2)
+    end
+
+    test "closes result", %{conn: conn} do
+      {:ok, result} = @subject.query(conn, "SELECT * FROM range(5)", [], lazy: true)
+
+      assert :ok = @subject.close_result(result)
+      assert {:ok, %{rows: []}, :done} = @subject.fetch(result, 2)
+    end
+
+    test "streams rows in chunks", %{conn: conn} do
+      query = "SELECT i FROM range(25) t(i) ORDER BY i"
+      {:ok, result} = @subject.query(conn, query, [], lazy: true)
+
+      assert Enum.to_list(@subject.stream_result(result, 10)) == Enum.map(0..24, &[&1])
+    end
+
+    test "streams columnar chunks", %{conn: conn} do
+      query = "SELECT i FROM range(5) t(i) ORDER BY i"
+      {:ok, result} = @subject.query(conn, query, [], lazy: true, layout: :columnar)
+
+      assert [[[0, 1, 2]], [[3, 4]]] = Enum.to_list(@subject.stream_result(result, 3))
+    end
+
+    test "closes result when stream halts", %{conn: conn} do
+      {:ok, result} = @subject.query(conn, "SELECT * FROM range(25)", [], lazy: true)
+
+      assert [_, _] = result |> @subject.stream_result(10) |> Enum.take(2)
+      assert {:ok, %{rows: []}, :done} = @subject.fetch(result, 2)
+    end
+  end
+
+  describe "arrow stream" do
+    test "exposes result as Arrow C stream", %{conn: conn} do
+      assert {:ok, stream} = @subject.query_arrow_stream(conn, "SELECT * FROM range(?)", [10])
+      assert {:ok, address} = @subject.arrow_stream_address(stream)
+      assert is_integer(address) and address > 0
+      assert {:ok, ^address} = @subject.arrow_stream_address(stream)
+    end
+  end
+
+  describe "query_tensors" do
+    test "packs numeric columns into binaries", %{conn: conn} do
+      query = "SELECT i::INTEGER AS x, i / 2 AS y FROM range(3) t(i)"
+
+      assert {:ok, [x, y]} = @subject.query_tensors(conn, query)
+
+      assert %{name: "x", type: {:s, 32}, shape: {3}} = x
+      assert x.data == <<0::native-32, 1::native-32, 2::native-32>>
+
+      assert %{name: "y", type: {:f, 64}, shape: {3}} = y
+      assert y.data == <<0.0::float-native-64, 0.5::float-native-64, 1.0::float-native-64>>
+    end
+
+    test "rejects non-numeric columns", %{conn: conn} do
+      assert {:error, %Duckex.Error{kind: :invalid_input}} =
+               @subject.query_tensors(conn, "SELECT 'a' AS s")
+    end
+  end
+
+  describe "load_csv_binary" do
+    test "loads CSV data into temporary table", %{conn: conn} do
+      csv = "id,name\n1,foo\n2,bar\n"
+
+      assert {:ok, 2} = @subject.load_csv_binary(conn, "upload", csv)
+
+      assert {:ok, %{rows: [[1, "foo"], [2, "bar"]]}} =
+               @subject.query(conn, "SELECT id, name FROM upload ORDER BY id", [])
+    end
+
+    test "accepts parsing options", %{conn: conn} do
+      csv = ["skipped line\n", "a;b\n", "1;NA\n"]
+
+      assert {:ok, 1} =
+               @subject.load_csv_binary(conn, "upload", csv,
+                 skip: 1,
+                 delimiter: ";",
+                 header: true,
+                 null: "NA"
+               )
+
+      assert {:ok, %{rows: [[1, nil]]}} = @subject.query(conn, "SELECT a, b FROM upload", [])
+    end
+  end
+
+  describe "load_ndjson_binary" do
+    test "loads JSON lines into table", %{conn: conn} do
+      data = [~s({"id": 1, "msg": "foo"}\n), ~s({"id": 2, "msg": "bar"}\n)]
+
+      assert {:ok, %{rows: 2, columns: columns}} = @subject.load_ndjson_binary(conn, "logs", data)
+      assert [%{name: "id", type: "BIGINT"}, %{name: "msg", type: "VARCHAR"}] = columns
+
+      assert {:ok, %{rows: [[1, "foo"], [2, "bar"]]}} =
+               @subject.query(conn, "SELECT id, msg FROM logs ORDER BY id", [])
+    end
+  end
+
+  describe "sniff_csv" do
+    @tag :tmp_dir
+    test "detects dialect and columns", %{conn: conn, tmp_dir: tmp_dir} do
+      path = Path.join(tmp_dir, "data.csv")
+      File.write!(path, "id|name\n1|foo\n2|bar\n")
+
+      assert {:ok, %{delimiter: "|", header: true, columns: columns}} =
+               @subject.sniff_csv(conn, path)
+
+      assert [%{name: "id", type: "BIGINT"}, %{name: "name", type: "VARCHAR"}] = columns
+    end
+  end
+
+  describe "execute_many" do
+    test "executes statement for each params row", %{conn: conn} do
+      @subject.query!(conn, "CREATE TABLE t (a INTEGER, b TEXT)", [])
+      query = @subject.prepare!(conn, "INSERT INTO t VALUES (?, ?)")
+
+      assert {:ok, 3} = @subject.execute_many(conn, query, [[1, "a"], [2, "b"], [3, nil]])
+
+      assert {:ok, %{rows: [[1, "a"], [2, "b"], [3, nil]]}} =
+               @subject.query(conn, "SELECT * FROM t ORDER BY a", [])
+    end
+
+    test "rolls back all rows on error", %{conn: conn} do
+      @subject.query!(conn, "CREATE TABLE t (a INTEGER PRIMARY KEY)", [])
+      query = @subject.prepare!(conn, "INSERT INTO t VALUES (?)")
+
+      assert {:error, %Duckex.Error{kind: :constraint}} =
+               @subject.execute_many(conn, query, [[1], [2], [1]])
+
+      assert {:ok, %{rows: [[0]]}} = @subject.query(conn, "SELECT count(*) FROM t", [])
+    end
+  end
+
+  describe "async execute" do
+    test "sends result to the caller", %{conn: conn} do
+      query = @subject.prepare!(conn, "SELECT $1::INTEGER + 1 AS n")
+
+      assert {:ok, ref} = @subject.execute_async(conn, query, [41])
+      assert {:ok, %{rows: [[42]]}} = @subject.await(ref)
+    end
+
+    test "sends error to the caller", %{conn: conn} do
+      query = @subject.prepare!(conn, "SELECT error('boom')")
+
+      assert {:ok, ref} = @subject.execute_async(conn, query, [])
+      assert {:error, %Duckex.Error{}} = @subject.await(ref)
+    end
+
+    test "runs queries in submission order", %{conn: conn} do
+      query = @subject.prepare!(conn, "SELECT $1::INTEGER AS n")
+
+      refs =
+        for n <- 1..5 do
+          {:ok, ref} = @subject.execute_async(conn, query, [n])
+          ref
+        end
+
+      results =
+        for _ <- refs do
+          assert_receive {:duckex_result, ref, {:ok, %{rows: [[n]]}}}
+          {ref, n}
+        end
+
+      assert results == Enum.zip(refs, 1..5)
+    end
+
+    test "interrupts query of exited caller", %{conn: conn} do
+      query = @subject.prepare!(conn, "SELECT count(*) FROM range(1000000000000)")
+      test = self()
+
+      {pid, monitor} =
+        spawn_monitor(fn ->
+          {:ok, _ref} = @subject.execute_async(conn, query, [])
+          send(test, :submitted)
+        end)
+
+      assert_receive :submitted
+      assert_receive {:DOWN, ^monitor, :process, ^pid, :normal}
+
+      assert {:ok, %{rows: [[1]]}} = @subject.query(conn, "SELECT 1", [], timeout: 5_000)
+    end
+  end
+
+  describe "read" do
+    test "runs concurrent reads on connection clones", %{conn: conn} do
+      @subject.query!(conn, "CREATE TABLE reads AS SELECT range AS i FROM range(10)", [])
+      {:ok, resource} = @subject.resource(conn)
+
+      results =
+        1..4
+        |> Task.async_stream(fn n ->
+          @subject.read(resource, "SELECT count(*) FROM reads WHERE i < $1", [n])
+        end)
+        |> Enum.map(fn {:ok, {:ok, %{rows: [[count]]}}} -> count end)
+
+      assert results == [1, 2, 3, 4]
+    end
+
+    test "does not see uncommitted changes", %{conn: conn} do
+      @subject.query!(conn, "CREATE TABLE reads AS SELECT 1 AS i", [])
+      {:ok, resource} = @subject.resource(conn)
+
+      @subject.transaction(conn, fn conn ->
+        @subject.query!(conn, "INSERT INTO reads VALUES (2)", [])
+
+        assert {:ok, %{rows: [[1]]}} = @subject.read(resource, "SELECT count(*) FROM reads")
+      end)
+    end
+
+    test "rejects writes", %{conn: conn} do
+      {:ok, resource} = @subject.resource(conn)
+
+      assert {:error, %Duckex.Error{kind: :invalid_input}} =
+               @subject.read(resource, "CREATE TABLE nope (i INTEGER)")
+
+      @subject.query!(conn, "CREATE TABLE t (a INT)")
+
+      assert {:error, %Duckex.Error{kind: :invalid_input}} =
+               @subject.read(resource, "WITH v AS (SELECT 1) INSERT INTO t FROM v")
+    end
+  end
+
+  describe "transaction_status" do
+    test "tracks transactions", %{conn: conn} do
+      assert {:ok, :idle} = @subject.transaction_status(conn)
+
+      @subject.transaction(conn, fn conn ->
+        assert {:ok, :transaction} = @subject.transaction_status(conn)
+        assert :transaction = DBConnection.status(conn)
+      end)
+
+      assert {:ok, :idle} = @subject.transaction_status(conn)
+    end
+
+    test "tracks plain transaction queries", %{conn: conn} do
+      {:ok, resource} = @subject.resource(conn)
+
+      @subject.query!(conn, "BEGIN TRANSACTION", [])
+      assert {:ok, :transaction} = @subject.transaction_status(resource)
+
+      @subject.query!(conn, "ROLLBACK", [])
+      assert {:ok, :idle} = @subject.transaction_status(resource)
+    end
+
+    test "rejects writes in read-only transaction", %{conn: conn} do
+      @subject.query!(conn, "CREATE TABLE t (a INT)", [])
+
+      assert {:error, %Duckex.Error{kind: :read_only}} =
+               @subject.transaction(
+                 conn,
+                 fn conn ->
+                   assert {:ok, %{rows: []}} = @subject.query(conn, "SELECT * FROM t", [])
+
+                   case @subject.query(conn, "INSERT INTO t VALUES (1)", []) do
+                     {:error, error} -> @subject.rollback(conn, error)
+                     {:ok, _} -> :inserted
+                   end
+                 end,
+                 read_only: true
+               )
+
+      assert {:ok, %{rows: [[1]]}} = @subject.query(conn, "INSERT INTO t VALUES (1)", [])
+    end
+
+    test "rejects writes behind comments or CTEs in read-only transaction", %{conn: conn} do
+      @subject.query!(conn, "CREATE TABLE t (a INT)", [])
+
+      writes = ["-- note\nINSERT INTO t VALUES (1)", "WITH v AS (SELECT 1) INSERT INTO t FROM v"]
+
+      for sql <- writes do
+        assert {:error, %Duckex.Error{kind: :read_only}} =
+                 @subject.transaction(
+                   conn,
+                   fn conn ->
+                     case @subject.query(conn, sql, []) do
+                       {:error, error} -> @subject.rollback(conn, error)
+                       {:ok, _} -> :inserted
+                     end
+                   end,
+                   read_only: true
+                 )
+      end
+    end
+
+    test "runs batch inside open transaction", %{conn: conn} do
+      @subject.transaction(conn, fn conn ->
+        assert :ok =
+                 @subject.execute_batch(conn, "CREATE TABLE t (a INT); INSERT INTO t VALUES (1)")
+      end)
+
+      assert {:ok, %{rows: [[1]]}} = @subject.query(conn, "SELECT a FROM t", [])
+    end
+  end
+
+  describe "nested transactions" do
+    test "nested begin fails and keeps the open transaction" do
+      {:ok, resource} = Duckex.Native.new(":memory:")
+      {:ok, _} = Duckex.Native.begin(resource)
+
+      assert {:error, %Duckex.Error{kind: :not_implemented}} = Duckex.Native.begin(resource)
+      assert {:ok, :transaction} = Duckex.Native.transaction_status(resource)
+
+      assert {:ok, _} = Duckex.Native.commit(resource)
+      assert {:ok, :idle} = Duckex.Native.transaction_status(resource)
+    end
+
+    test "nested transaction/3 runs within the outer one", %{conn: conn} do
+      @subject.query!(conn, "CREATE TABLE t (a INT)", [])
+
+      assert {:ok, :outer} =
+               @subject.transaction(conn, fn conn ->
+                 @subject.query!(conn, "INSERT INTO t VALUES (1)", [])
+
+                 assert {:ok, :inner} =
+                          @subject.transaction(conn, fn conn ->
+                            @subject.query!(conn, "INSERT INTO t VALUES (2)", [])
+                            :inner
+                          end)
+
+                 :outer
+               end)
+
+      assert {:ok, %{rows: [[2]]}} = @subject.query(conn, "SELECT count(*) FROM t", [])
+    end
+  end
+
+  describe "query timeout" do
+    test "interrupts query running longer than the timeout", %{conn: conn} do
+      assert {:error, %Duckex.Error{kind: :timeout}} =
+               @subject.query(
+                 conn,
+                 "SELECT count(*) FROM range(1000000000) a, range(1000000000) b",
+                 [],
+                 query_timeout: 100
+               )
+
+      # Connection is still usable afterwards
+      assert {:ok, %{rows: [[1]]}} = @subject.query(conn, "SELECT 1", [])
+    end
+
+    test "does not affect queries finishing in time", %{conn: conn} do
+      assert {:ok, %{rows: [[1]]}} = @subject.query(conn, "SELECT 1", [], query_timeout: 5_000)
+    end
+  end
+
+  describe "lock timeout" do
+    test "returns busy error when connection is busy for too long" do
+      {:ok, resource} = Duckex.Native.new(":memory:", nil, lock_timeout: 50)
+      {:ok, %{rows: [[stmt]]}} = Duckex.Native.prepare(resource, "SELECT count(*) FROM range(?)")
+
+      task =
+        Task.async(fn ->
+          Duckex.Native.execute(resource, stmt, [10_000_000_000], timeout: 1_000)
+        end)
+
+      # Give the task time to start the query
+      Process.sleep(100)
+
+      assert {:error, %Duckex.Error{kind: :busy}} = Duckex.Native.execute(resource, stmt, [1])
+
+      Task.await(task)
+    end
+  end
 end